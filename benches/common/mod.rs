@@ -0,0 +1,69 @@
+//! Shared helpers of the packing benchmarks.
+
+use image_atlas::*;
+use std::ops::RangeInclusive;
+
+/// Returns glyph-like extents of `count` glyphs at each font size.
+///
+/// Each glyph is `widths` percent of the font size wide and `heights` percent of it tall, drawn
+/// by xorshift from `seed` so runs are comparable.
+pub fn glyph_extents(
+    seed: u64,
+    font_sizes: &[u32],
+    count: u32,
+    widths: RangeInclusive<u32>,
+    heights: RangeInclusive<u32>,
+) -> Vec<(u32, u32)> {
+    let mut state = seed;
+    let mut next = |range: &RangeInclusive<u32>| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        range.start() + (state % (range.end() - range.start() + 1) as u64) as u32
+    };
+
+    let mut extents = Vec::new();
+    for &font_size in font_sizes {
+        for _ in 0..count {
+            let width = font_size * next(&widths) / 100;
+            let height = font_size * next(&heights) / 100;
+            extents.push((width.max(1), height.max(1)));
+        }
+    }
+    extents
+}
+
+/// Packs the extents as blank entries with each algorithm, and prints the page count, the mean
+/// occupancy of full pages and the time.
+pub fn compare(extents: &[(u32, u32)], mip: AtlasMipOption, algorithms: &[(&str, PackAlgorithm)]) {
+    let entries = extents
+        .iter()
+        .map(|&(width, height)| AtlasEntry::new(image::GrayImage::new(width, height)))
+        .collect::<Vec<_>>();
+
+    for &(name, pack_algorithm) in algorithms {
+        let start = std::time::Instant::now();
+        let atlas = create_atlas(&AtlasDescriptor {
+            max_page_count: 16,
+            size: 512,
+            mip,
+            pack_algorithm,
+            entries: &entries,
+            ..Default::default()
+        })
+        .unwrap();
+        let elapsed = start.elapsed();
+
+        // the last page is partially filled, so only the full pages are compared.
+        let stats = atlas.stats();
+        let full_pages = &stats.per_page[..stats.per_page.len() - 1];
+        let occupancy = full_pages.iter().sum::<f32>() / full_pages.len().max(1) as f32;
+        println!(
+            "{:<16} pages: {:>2}  occupancy of full pages: {:>5.1}%  time: {:?}",
+            name,
+            atlas.page_count,
+            occupancy * 100.0,
+            elapsed,
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Compares the packing time and occupancy of packing algorithms on thousands of glyph entries.
+//!
+//! Run with `cargo bench --features shelf`.
+
+mod common;
+
+use image_atlas::*;
+
+fn main() {
+    // about 5000 glyphs (a CJK-sized character set) at small font sizes.
+    let extents = common::glyph_extents(
+        0x9e37_79b9_7f4a_7c15,
+        &[10, 12, 14, 16, 20],
+        1000,
+        40..=100,
+        60..=110,
+    );
+
+    common::compare(
+        &extents,
+        AtlasMipOption::NoMipWithPadding(1),
+        &[
+            ("rectangle_pack", PackAlgorithm::RectanglePack),
+            ("shelf", PackAlgorithm::Shelf),
+        ],
+    );
+}
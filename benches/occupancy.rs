@@ -0,0 +1,27 @@
+//! Compares the occupancy of packing algorithms on a glyph-like set of entries.
+//!
+//! Run with `cargo bench --features skyline`.
+
+mod common;
+
+use image_atlas::*;
+
+fn main() {
+    // printable ASCII at several font sizes.
+    let extents = common::glyph_extents(
+        0x2545_f491_4f6c_dd1d,
+        &[12, 16, 24, 32, 48, 64],
+        95,
+        30..=90,
+        50..=120,
+    );
+
+    common::compare(
+        &extents,
+        AtlasMipOption::NoMip,
+        &[
+            ("rectangle_pack", PackAlgorithm::RectanglePack),
+            ("skyline", PackAlgorithm::Skyline),
+        ],
+    );
+}
@@ -10,6 +10,22 @@
 //!
 //! This library uses `image` crate for image processing and `rectangle-pack` crate for computing element layout.
 //!
+//! # Features
+//!
+//! - `std` (default): Atlas generation with `image` crate. Without this feature, the crate is
+//!   `no_std` with `alloc`, and provides the data types such as [Texcoord](Texcoord),
+//!   [AtlasStats](AtlasStats), [AtlasError](AtlasError) and the option enums, and the layout
+//!   computation of [plan_atlas](plan_atlas), [minimum_size](minimum_size) and
+//!   [estimate_memory](estimate_memory) over [BlankTexture](BlankTexture).
+//! - `serde`: Serialization of the data types (and `Atlas` with `std`).
+//! - `png`: Saving pages as PNG files, and loading entries from PNG files by
+//!   [entries_from_dir](entries_from_dir).
+//! - `hdr`, `exr`: Saving pages of `f32` pixels as Radiance HDR or OpenEXR files.
+//! - `skyline`, `shelf`: A skyline or shelf packer selectable by [PackAlgorithm](PackAlgorithm).
+//! - `glam`, `mint`: Conversions of the min and max positions of [Texcoord32](Texcoord32) and
+//!   [Texcoord64](Texcoord64) into `glam` or `mint` vectors.
+//! - `json`: Exporting frames of entries as JSON of sprite tools (e.g. TexturePacker).
+//!
 //! # Examples
 //!
 //! ```rust
@@ -22,15 +38,242 @@
 //!     entries: &[AtlasEntry {
 //!         texture: image::RgbImage::new(512, 512),
 //!         mip: AtlasEntryMipOption::Clamp,
+//!         ..Default::default()
 //!     }],
+//!     ..Default::default()
 //! })
 //! .unwrap();
 //!
 //! let texcoord = &atlas.texcoords[0];
 //! let texture = &atlas.textures[texcoord.page as usize].mip_maps[0];
 //! ```
+//!
+//! # Option interactions
+//!
+//! ## Entries
+//!
+//! These notes apply to the fields of [AtlasEntry](AtlasEntry).
+//!
+//! `mip` is the single tiling option of the entry, and `gutter_wrap` and `mip_wrap` override it
+//! separately. `gutter_wrap` decides what neighbors sampling across the entry edge see in the
+//! page, and `mip_wrap` decides what the downscale filter reads beyond the entry edges with
+//! `MipWithBlock` (or `MipWithBlockRect`). For example, a tiling texture with `Repeat` as
+//! `mip_wrap` and `Clamp` as `gutter_wrap` keeps its period in every mip map, while its gutter
+//! only extends its own edges. A downscale with `Repeat` or `Mirror` resizes the entry with a
+//! tiled margin around it, so it costs more than that of `Clamp` (up to nine times for the
+//! coarsest mip maps).
+//! `Mip` and `MipWithPadding` resize the whole page at once, so `mip_wrap` is not used there and
+//! the filter reads the gutter filled by `gutter_wrap`.
+//!
+//! `mip_filter`, `anchor` and `generate_mips` are applied only with `MipWithBlock` (or
+//! `MipWithBlockRect`), where each entry is resized individually into a region rounded up to
+//! block multiples. A centered entry has symmetric gutters, so its mip maps bleed evenly on both
+//! sides. An entry without `generate_mips` (e.g. a glyph sampled 1:1) writes only mip level 0, and
+//! its region of coarser mip maps is left as the background. The `mip_level_count` of the pages
+//! stays uniform. `Mip` and `MipWithPadding` resize the whole page at once, so the descriptor
+//! filter is always used there.
+//!
+//! Entries of the same `group` are packed into one page (e.g. the tiles of a level, to reduce
+//! texture binds at runtime). Each group starts on a page which has room for all of its entries,
+//! so grouping may increase the page count. A group which does not fit in any single page is
+//! packed ungrouped, while the other groups are kept. `group` is ignored by `Skyline` and `Shelf`
+//! packing and by [insert](Atlas::insert).
+//!
+//! An entry whose `size` differs from its texture is resized before packing by `sampling` of the
+//! description, as if the resized texture were given (so `source_width` and `source_height` of
+//! its texcoord are the resized ones). Otherwise texture pixels are copied as is, since the
+//! block-rounded region of an entry only adds gutter around it and never rescales it.
+//!
+//! A trimmed entry stores only the tight bounding box of pixels whose alpha is not zero. The
+//! cropped offset and the original size are recorded in [Texcoord](Texcoord). Trimming is a no-op
+//! for pixels without alpha channel and for fully transparent textures.
+//!
+//! `depth` is read only by [create_volume_atlas](create_volume_atlas), where the texture is a
+//! brick of `depth` slices from top to bottom, each `height / depth` tall. The other functions
+//! pack the texture as a 2D image regardless of it.
+//!
+//! An entry with `bake_rotations` is packed with three more copies rotated clockwise by 90, 180
+//! and 270 degrees (with `size` swapped for odd turns), so a sprite drawn at a fixed quarter turn
+//! only picks another texcoord instead of rotating its vertices or texcoords. The copies are
+//! packed like other entries but are not listed in `texcoords`; their texcoords are stored in
+//! `baked_rotations` of [Atlas](Atlas) and looked up by
+//! [rotation_texcoords](Atlas::rotation_texcoords). It is ignored by
+//! [create_volume_atlas](create_volume_atlas) and [insert](Atlas::insert).
+//!
+//! Entries with `pin_page` are packed into their page (counted from `page_base`, like `page` of
+//! [Texcoord](Texcoord)) before any other entry, and the other entries are packed around them.
+//! `pin_page` wins over `group`, and it also pins the rotated copies of `bake_rotations`. If the
+//! pinned entries do not fit in their page (within `max_entries_per_page` too), or the page is out
+//! of `max_page_count`, generation fails with `PinnedPageOverflow` instead of adding pages. With
+//! `MipWithBlock` (or `MipWithBlockRect`), a pinned entry keeps a block of its own rather than
+//! sharing one with other small entries. A pinned entry is deduplicated only with the ones of the
+//! same `pin_page`, and `pin_page` is ignored by [create_volume_atlas](create_volume_atlas) and
+//! [insert](Atlas::insert).
+//!
+//! ## Descriptions
+//!
+//! These notes apply to the fields of [AtlasDescriptor](AtlasDescriptor).
+//!
+//! `extrude` is applied only with `NoMip` and `NoMipWithPadding`. Unlike padding, the extruded
+//! border always repeats the edge pixels regardless of the tiling option, and the padding is
+//! reserved outside of the extruded border. Texcoord still covers the original texture only.
+//!
+//! With `origin_alignment`, `min_x` and `min_y` of every texcoord are multiples of it, so each
+//! entry starts on a block boundary of block-compressed formats (e.g. 4 for BC and ETC). Each rect
+//! is packed `origin_alignment - 1` pixels wider and taller, and the entry is moved within it to
+//! the next aligned position. It is applied only with `NoMip`, `NoMipWithPadding`, `Mip` and
+//! `MipWithPadding`, since block options place entries by the half-block gutter.
+//!
+//! With `bleed_alpha`, every fully transparent pixel in the padded region of each entry takes the
+//! color of the nearest pixel that is not fully transparent, keeping its zero alpha, so bilinear
+//! filtering at sprite edges does not blend in the (typically black) color of transparent pixels.
+//! It is applied only with `NoMip` and `NoMipWithPadding`, after the entry is written.
+//!
+//! Each `reserve` region is packed like an entry with the same padding, but nothing is written to
+//! it, so it keeps `background` until filled at runtime. Its texcoord is stored in `reserved` of
+//! [Atlas](Atlas), and it is reported as index `entries.len() + i` by errors.
+//!
+//! With `stable`, entries are sorted by area (descending), then by width, height and original index
+//! before packing, and the results are mapped back to the original indices. The layout therefore
+//! does not change when the entries are shuffled, except among entries of the same extent.
+//!
+//! With `balance_pages`, entries are packed again after the initial packing, from the largest
+//! one, into the least occupied page that has room among the pages used by the initial packing,
+//! so the page count does not grow and the entries are spread over the pages (see `per_page` of
+//! [AtlasStats](AtlasStats)). The initial layout is kept when the balanced one does not fit, or
+//! when an entry has a `group`.
+//!
+//! With `max_entries_per_page`, entries (and reserved regions) are packed page by page, from the
+//! largest one, and a page takes no more entries once it holds that many, even if more would fit
+//! (e.g. for a limit of texture descriptors per draw). Deduplicated entries count once, and
+//! distinct `occupied` texcoords count on their pages. `group` and `balance_pages` are ignored
+//! then. [insert](Atlas::insert) skips the pages which hold as many distinct texcoords.
+//!
+//! With `max_page_count` of zero, as many pages as needed are used, and only a finite count that is
+//! too small fails packing.
+//!
+//! With `min_page_count`, empty pages filled with `background` are appended after packing until
+//! `page_count` reaches it, so the pages fill an array binding of fixed layer count. The empty
+//! pages are available to [insert](Atlas::insert).
+//!
+//! `page_base` offsets `page` of every texcoord, so the pages can be layers of one array texture
+//! shared by multiple atlas builds. Each `occupied` texcoord on a page in
+//! `page_base..page_base + max_page_count` (or above `page_base` when unbounded) is marked as
+//! used before packing, expanded by the padding (or half-block gutter) of this description, so a
+//! new build can fill the free space of pages produced by another build (e.g. by passing its
+//! `texcoords`).
+//!
+//! Each entry listed in `preserve` is placed back at its texcoord of a previous generation
+//! (its rect marked as used before packing), and the other entries are packed into the free space
+//! around them, so the texcoords cached from that generation stay valid across rebakes (e.g.
+//! with larger `max_page_count` or new entries). The options deciding the layout (`size`, `mip`,
+//! `extrude`, `origin_alignment`, `allow_rotation`, `page_base` and the entry size, trim and
+//! anchor) must match the previous generation, otherwise generation fails with
+//! `PreservedMismatch`. A preserved entry takes whole blocks instead of a cell of
+//! `sub_block_size`, and it wins over `pin_page`. A preserved entry is deduplicated only with the
+//! ones of the same previous texcoord, and `preserve` is ignored by [plan_atlas](plan_atlas),
+//! [create_atlas_planned](create_atlas_planned) and [create_volume_atlas](create_volume_atlas). See
+//! [create_atlas_preserving](create_atlas_preserving).
+//!
+//! With `collect_free_rects`, the free sections tracked by the packer are returned in
+//! `free_rects` of [Atlas](Atlas). It is off by default since reading every section costs extra
+//! probing after packing.
+//!
+//! With `collect_packed_rects`, the placements of the packer are returned in `packed_rects` of
+//! [Atlas](Atlas) before they are converted to texcoords, to diagnose the layout.
+//!
+//! With `shrink_pages`, each page is cropped after generation to the smallest power-of-two
+//! square covering its entries, reserved regions and their padding, so a page whose contents fit
+//! in a corner takes less memory and upload bandwidth. The realized size is stored in `size` of
+//! each [Texture](Texture) and of each texcoord on the page, and coarse mip maps beyond the
+//! cropped size are dropped from `mip_maps`. Pages then differ in size, so it is opt-in (array
+//! textures need a uniform size), and the atlas does not support [insert](Atlas::insert).
+//!
+//! With `mip_range`, only the mip levels in the range are generated and kept. Mip levels from
+//! `mip_range.end` are not generated, like `max_mip_level_count`, and the mip levels between the
+//! base mip map and `mip_range.start` are never allocated. The base mip map is the source of the
+//! others, so it is written and dropped at the end. Each texture then holds the mip levels from
+//! `mip_range.start` (`mip_maps[0]` is that mip level and `size` is its side), `mip_start` of
+//! [Atlas](Atlas) is `mip_range.start`, and `mip_level_count` counts the kept mip levels.
+//! Texcoords stay in pixels of mip level 0. When the base mip map is dropped, the atlas does not
+//! support [insert](Atlas::insert). It fails with `MipLevelOutOfRange` if `mip_range.start` is
+//! not less than the mip map count, and it is ignored with `NoMip` and `NoMipWithPadding`.
+//!
+//! With `relaxed_mip`, mip options accept any nonzero `size` (e.g. 1920 for a screen-sized
+//! atlas). The mip chain then stops where the page side becomes odd: `mip_level_count` is the
+//! number of exact halvings of `size` plus one (`size.trailing_zeros() + 1`, so 8 levels down to
+//! 15x15 for 1920), and each mip map is `size >> mip_level` square. An odd `size` has only the
+//! base mip map. For power-of-two sizes this is the full mip chain, so the option only widens
+//! the accepted sizes.
+//!
+//! With `strict_gutter`, `Mip` and `MipWithPadding` fail with `InsufficientGutter` unless the
+//! padding keeps neighbors out of the mip filter at the coarsest generated mip level. These
+//! options downscale the whole page, so a texel at the entry edge reads up to `reach` texels of
+//! its mip level beyond the edge (0 for `Nearest`, 1 for `Linear`, 2 for `Cubic` and 3 for
+//! `Gaussian` and `Lanczos3`), while the padding halves at each mip level. The padding needed is
+//! `reach << (mip_level_count - 1)` pixels at mip level 0, so lower `max_mip_level_count` (or
+//! `mip_range.end`) or widen the padding to satisfy it. `MipWithBlock` and `MipWithBlockRect`
+//! always pass: each entry is downscaled alone with its edges extended by `mip_wrap`, so the
+//! filter never reads the gutter, and the mip chain is capped where the half-block gutter is
+//! still half a texel, the footprint of bilinear sampling. It is off by default since the
+//! default `Mip` has no padding.
+//!
+//! With `retain_sources`, a copy of each entry (with the texture as an image buffer) is stored in
+//! `sources` of [Atlas](Atlas), so an entry can be written again by
+//! [rebake_entry](Atlas::rebake_entry) without the caller keeping the images. The copies take as
+//! much memory as the entries, so it is off by default.
+//!
+//! With `downscale_oversized`, an entry whose width or height (its `size` if given) does not fit
+//! in a page with its padding (or block gutter) is resized by the mip filter (or `sampling`
+//! without mip map) to the largest extent that fits, keeping the aspect ratio, instead of failing
+//! with `EntryTooLarge`. The applied
+//! factor is stored in `scale_factors` of [Atlas](Atlas) to correct world-space sizing. It is not
+//! applied by [insert](Atlas::insert).
+//!
+//! With `sub_block_size`, an entry that fits in one block with a gutter of half the sub-block
+//! size is packed into a shared block in cells of `sub_block_size` instead of taking whole blocks,
+//! so many small sprites waste less of the page. The mip chain is then capped at
+//! `log2(sub_block_size) + 1` levels, where the gutter of the cells is still half a texel. It must
+//! be a power of two of at least 2 and smaller than the smaller block side, and is only valid with
+//! `MipWithBlock` and `MipWithBlockRect`. Reserved regions always take whole blocks, entries in
+//! shared blocks are not kept together by `group`, and shared blocks are not listed in
+//! `packed_rects`.
+//!
+//! `on_progress` is called on the calling thread before packing and after each step of packing,
+//! blitting and mip map generation, so GUI callers can drive a progress bar. It is not called by
+//! [insert](Atlas::insert).
+//!
+//! With `channel_swizzle`, channel `i` of every written pixel is channel `channel_swizzle[i]` of
+//! the entry (e.g. `[2, 1, 0, 3]` stores RGBA entries as BGRA). Only the first
+//! `P::CHANNEL_COUNT` indices are used. Each entry is swizzled as it is copied, so `background`
+//! is given in the output order, and mip map generation treats the last output channel as alpha.
+//!
+//! `rectangle_pack` does not rotate rects by itself, so when `allow_rotation` is enabled, the
+//! entries are packed as they are, with the tall ones transposed (x and y swapped), and with the
+//! wide ones transposed, and the layout with the fewest pages (then the smallest last page) is
+//! kept. An entry which fits in a page only transposed is transposed alone. A transposed entry
+//! has its texcoord marked with `rotated`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
-use std::{collections::BTreeMap, error, fmt};
+use alloc::{collections::BTreeMap, rc::Rc, vec, vec::Vec};
+use core::{cell::RefCell, error, fmt, ops::Range};
+
+#[cfg(feature = "std")]
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    fs,
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    path,
+};
+
+#[cfg(feature = "std")]
+use image::{EncodableLayout, GenericImageView, Pixel, Primitive};
+#[cfg(feature = "std")]
+use num_traits::{NumCast, ToPrimitive};
 
 /// A filter type using by mip map geration.
 ///
@@ -40,6 +283,11 @@ use std::{collections::BTreeMap, error, fmt};
 /// - `Gaussian`: Gaussian filter.
 /// - `Lanczos3`: Lanczos with window 3 filter.
 ///
+/// Every filter is valid for integer and float subpixels. Float subpixels (e.g. `Rgba32FImage`)
+/// are filtered in float precision without clamping to `0.0..=1.0`, so HDR and negative values
+/// are kept. The overshoot of `Cubic` and `Lanczos3` is clamped to the value range of the source
+/// for float subpixels, like saturation for integer subpixels.
+///
 /// See the [FilterType](image::imageops::FilterType) for details.
 #[repr(C)]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
@@ -53,6 +301,7 @@ pub enum AtlasMipFilter {
     Lanczos3,
 }
 
+#[cfg(feature = "std")]
 impl From<AtlasMipFilter> for image::imageops::FilterType {
     #[inline]
     fn from(value: AtlasMipFilter) -> Self {
@@ -73,6 +322,9 @@ impl From<AtlasMipFilter> for image::imageops::FilterType {
 /// - `Mip(filter, padding size)`: Mip map generation.
 /// - `MipWithPadding(filter, padding size)`: Mip map generation with padding.
 /// - `MipWithBlock(filter, block size)`: Mip map generation with block. block size must be power of two.
+/// - `MipWithBlockRect(filter, block width, block height)`: Mip map generation with non-square
+///   block. block width and height must be power of two, and mip maps are generated up to the
+///   smaller one.
 #[repr(C)]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -83,8 +335,123 @@ pub enum AtlasMipOption {
     Mip(AtlasMipFilter),
     MipWithPadding(AtlasMipFilter, u32),
     MipWithBlock(AtlasMipFilter, u32),
+    MipWithBlockRect(AtlasMipFilter, u32, u32),
+}
+
+/// A color space of texture using by mip map generation.
+///
+/// - `Linear`: Texture is resized as is.
+/// - `Srgb`: Texture is converted into linear space before resizing and back into sRGB space after
+///   resizing. Alpha channel is always treated as linear.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AtlasMipColorSpace {
+    #[default]
+    Linear,
+    Srgb,
+}
+
+/// A packing strategy using by texture atlas generation.
+///
+/// - `ContainsSmallestBox`: Splits free space so that the smallest remaining box is as small as
+///   possible (`volume_heuristic` and `contains_smallest_box` of `rectangle_pack`).
+/// - `ContainsLargestBox`: Splits free space so that the largest remaining box is as large as
+///   possible, which keeps room for large entries packed later.
+///
+/// Pages are flat (depth is 1), so the box size is the area of each free region.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PackHeuristic {
+    #[default]
+    ContainsSmallestBox,
+    ContainsLargestBox,
+}
+
+/// A packing algorithm using by texture atlas generation.
+///
+/// - `RectanglePack`: Guillotine packing of `rectangle_pack`, which splits free space into boxes
+///   by [PackHeuristic](PackHeuristic).
+/// - `Skyline`: Skyline bottom-left packing (requires `skyline` feature). Entries are sorted by height
+///   and each one is placed at the lowest (then leftmost) position on the skylines of the free
+///   sections of the first page that fits it. It suits many small entries of similar height such
+///   as glyphs, but which one is denser depends on the entries (`cargo bench --features skyline`
+///   compares them).
+/// - `Shelf`: Shelf (row) packing (requires `shelf` feature). Entries are sorted by height and placed
+///   left to right in rows, on the shortest row of the first page that fits, or on a new row
+///   below. It takes time linear in the rows per entry, so it suits thousands of tiny entries
+///   such as glyphs (`cargo bench --features shelf` compares it with `RectanglePack`).
+///
+/// `Skyline` and `Shelf` ignore `pack_heuristic` and `stable`, since the entries are always packed
+/// in the canonical order (height descending, then width and original index). The space beyond
+/// each skyline, or right of each row and below the last row, is kept free for
+/// [insert](Atlas::insert), which always packs by `rectangle_pack`. A gap enclosed by the skyline,
+/// or the space above an entry shorter than its row, is not reused.
+///
+/// Every variant exists regardless of features, and generation with an algorithm whose feature
+/// is not enabled returns `PackAlgorithmDisabled`.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PackAlgorithm {
+    #[default]
+    RectanglePack,
+    Skyline,
+    Shelf,
+}
+
+/// A signed distance field option using by texture atlas generation.
+///
+/// - `spread`: A distance in pixels mapped to the full value range on each side of the edge.
+///
+/// An entry is thresholded into a mask by alpha (or the first channel for pixels without alpha)
+/// at the half value, and every channel is replaced by the signed distance to the mask edge.
+/// The distance is mapped as `0.5 + distance / (2 * spread)`, where inside is positive, so the
+/// edge is at the half value.
+///
+/// Equality compares the bit patterns of `spread`.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SdfParams {
+    pub spread: f32,
+}
+
+impl PartialEq for SdfParams {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.spread.to_bits() == other.spread.to_bits()
+    }
+}
+
+impl Eq for SdfParams {}
+
+/// An unsharp mask option applied to each generated mip map.
+///
+/// - `amount`: A strength of the mask, where `0.0` leaves the mip map as is.
+///
+/// After resizing, each color channel is moved away from a gaussian blur (sigma of one texel) of
+/// the mip map by `amount` times their difference, in the same color space and alpha mode as
+/// the resize. This restores the contrast lost by the downsampling filter. Alpha is kept as is.
+///
+/// Equality compares the bit patterns of `amount`.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SharpenParams {
+    pub amount: f32,
+}
+
+impl PartialEq for SharpenParams {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.amount.to_bits() == other.amount.to_bits()
+    }
 }
 
+impl Eq for SharpenParams {}
+
 /// A tiling method using by texture atlas generation.
 ///
 /// - `Clamp`: No tiling.
@@ -100,441 +467,6885 @@ pub enum AtlasEntryMipOption {
     Mirror,
 }
 
-/// A texture atlas generation entry description.
+/// A placement of entry within its block-rounded region.
 ///
-/// - `texture`: A input texture.
-/// - `mip`: A mip map tiling option.
-#[derive(Clone, PartialEq, Eq, Default, Debug)]
+/// - `TopLeft`: The entry is placed after the half-block gutter, and the rounding slack is added
+///   to the right and bottom.
+/// - `Center`: The entry is centered, and the rounding slack is split into both sides.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct AtlasEntry<I: image::GenericImageView> {
-    pub texture: I,
-    pub mip: AtlasEntryMipOption,
+pub enum AtlasEntryAnchor {
+    #[default]
+    TopLeft,
+    Center,
 }
 
-/// A texture atlas generation description.
+/// A sampling method of entry resized to its `size`.
 ///
-/// - `max_page_count`: A maximum output texture count.
-/// - `size`: A texture width and height (same width and height).
-/// - `mip`: A mip map method option.
-/// - `entries`: A input texture entries.
-#[derive(Clone, PartialEq, Eq, Default, Debug)]
-pub struct AtlasDescriptor<'a, I: image::GenericImageView> {
-    pub max_page_count: u32,
-    pub size: u32,
-    pub mip: AtlasMipOption,
-    pub entries: &'a [AtlasEntry<I>],
+/// - `Nearest`: Each pixel is copied from the nearest source pixel.
+/// - `Bilinear`: Each pixel is interpolated from the four nearest source pixels.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AtlasSampling {
+    #[default]
+    Nearest,
+    Bilinear,
 }
 
-/// Creates a new texture atlas.
-///
-/// # Errors
+/// A texture of entries, as far as the layout computation reads it.
 ///
-/// Returns an error if:
-/// - `max_page_count` is zero.
-/// - `size` is not power of two.
-/// - `block_size` is not power of two.
-/// - `entries` is empty.
-/// - Packing error occurred.
+/// With `std` feature, it is implemented for every [GenericImageView](image::GenericImageView).
+/// [BlankTexture](BlankTexture) is a texture of a size without pixels, so a description can be
+/// laid out by [plan_atlas](plan_atlas) and [minimum_size](minimum_size) without `std`.
+pub trait AtlasTexture {
+    /// A pixel type of the texture, and of `background` of the description.
+    type Pixel: Copy;
+
+    /// Returns the width and height of the texture.
+    fn extent(&self) -> (u32, u32);
+
+    /// Returns the region (x, y, width, height) kept by `trim` of the entry. The default is the
+    /// whole texture.
+    #[inline]
+    fn trim_bounds(&self) -> (u32, u32, u32, u32) {
+        let (width, height) = self.extent();
+        (0, 0, width, height)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I: image::GenericImageView> AtlasTexture for I {
+    type Pixel = I::Pixel;
+
+    #[inline]
+    fn extent(&self) -> (u32, u32) {
+        self.dimensions()
+    }
+
+    #[inline]
+    fn trim_bounds(&self) -> (u32, u32, u32, u32) {
+        trim_bounds(self)
+    }
+}
+
+/// A texture of a size without pixels.
 ///
-/// See the [AtlasError](AtlasError) for details.
+/// - `width`: A texture width.
+/// - `height`: A texture height.
 ///
 /// # Examples
 ///
 /// ```rust
 /// use image_atlas::*;
 ///
-/// let atlas = create_atlas(&AtlasDescriptor {
-///     max_page_count: 8,
-///     size: 2048,
-///     mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 32),
-///     entries: &[AtlasEntry {
-///         texture: image::RgbImage::new(512, 512),
-///         mip: AtlasEntryMipOption::Clamp,
-///     }],
-/// })
-/// .unwrap();
+/// let entry = AtlasEntry::new(BlankTexture::new(64, 32));
+/// assert_eq!(entry.texture.extent(), (64, 32));
 /// ```
-#[rustfmt::skip]
-pub fn create_atlas<I>(desc: &AtlasDescriptor<'_, I>) -> Result<Atlas<I::Pixel>, AtlasError>
-where
-    I: image::GenericImage,
-    I::Pixel: 'static,
-{
-    match desc.mip {
-        AtlasMipOption::NoMip => {
-            create_atlas_with_padding(desc.max_page_count, desc.size, 0, desc.entries)
-        }
-        AtlasMipOption::NoMipWithPadding(padding) => {
-            create_atlas_with_padding(desc.max_page_count, desc.size, padding, desc.entries)
-        }
-        AtlasMipOption::Mip(filter) => {
-            create_atlas_mip_with_padding(desc.max_page_count, desc.size, filter, 0, desc.entries)
-        }
-        AtlasMipOption::MipWithPadding(filter, padding) => {
-            create_atlas_mip_with_padding(desc.max_page_count, desc.size, filter, padding, desc.entries)
-        }
-        AtlasMipOption::MipWithBlock(filter, block_size) => {
-            create_atlas_mip_with_block(desc.max_page_count, desc.size, filter, block_size, desc.entries)
-        }
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlankTexture {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BlankTexture {
+    /// Returns a new blank texture of the size.
+    #[inline]
+    pub const fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
     }
 }
 
-#[inline]
-fn create_atlas_with_padding<I>(
-    max_page_count: u32,
-    size: u32,
-    padding: u32,
-    entries: &[AtlasEntry<I>],
-) -> Result<Atlas<I::Pixel>, AtlasError>
-where
-    I: image::GenericImage,
-    I::Pixel: 'static,
-{
-    if max_page_count == 0 {
-        return Err(AtlasError::ZeroMaxPageCount);
+impl AtlasTexture for BlankTexture {
+    type Pixel = ();
+
+    #[inline]
+    fn extent(&self) -> (u32, u32) {
+        (self.width, self.height)
     }
+}
 
-    if entries.is_empty() {
-        return Err(AtlasError::ZeroEntry);
+/// A texture atlas generation entry description.
+///
+/// - `texture`: A input texture.
+/// - `mip`: A mip map tiling option.
+/// - `trim`: Whether transparent margins are cropped before packing.
+/// - `mip_filter`: A mip map filter overriding the one of [AtlasMipOption](AtlasMipOption)
+///   (`None` uses the descriptor filter).
+/// - `anchor`: A placement within the block-rounded region.
+/// - `generate_mips`: Whether mip maps of the entry are generated (default `true`).
+/// - `group`: A group id of entries to be packed into the same page (`None` is ungrouped).
+/// - `size`: A width and height of the entry in the atlas (`None` is the texture size).
+/// - `depth`: A number of slices stacked vertically in the texture (`None` is one slice).
+/// - `gutter_wrap`: A tiling option filling the padding (or gutter) ring (`None` uses `mip`).
+/// - `mip_wrap`: A tiling option of the entry edges while downscaling mip maps (`None` uses
+///   `mip`).
+/// - `bake_rotations`: Whether copies of the entry rotated by 90, 180 and 270 degrees are packed
+///   too.
+/// - `pin_page`: A page the entry is forced onto (`None` lets the packer choose).
+///
+/// See [Option interactions](crate#option-interactions) for how the options affect each other.
+///
+/// Besides struct literals, an entry can be constructed by [new](AtlasEntry::new) and the
+/// chainable setters of each option.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AtlasEntry<I: AtlasTexture> {
+    pub texture: I,
+    pub mip: AtlasEntryMipOption,
+    pub trim: bool,
+    pub mip_filter: Option<AtlasMipFilter>,
+    pub anchor: AtlasEntryAnchor,
+    pub generate_mips: bool,
+    pub group: Option<u32>,
+    pub size: Option<(u32, u32)>,
+    pub depth: Option<u32>,
+    pub gutter_wrap: Option<AtlasEntryMipOption>,
+    pub mip_wrap: Option<AtlasEntryMipOption>,
+    pub bake_rotations: bool,
+    pub pin_page: Option<u32>,
+}
+
+impl<I: AtlasTexture + Default> Default for AtlasEntry<I> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Default::default())
     }
+}
 
-    let mut rects = rectangle_pack::GroupedRectsToPlace::<_, ()>::new();
-    for (i, entry) in entries.iter().enumerate() {
-        let rect = rectangle_pack::RectToInsert::new(
-            entry.texture.width() + padding * 2,
-            entry.texture.height() + padding * 2,
-            1,
-        );
-        rects.push_rect(i, None, rect);
+impl<I: AtlasTexture> AtlasEntry<I> {
+    /// Returns a new entry of the texture with default options (`Clamp` tiling).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use image_atlas::*;
+    ///
+    /// let entry = AtlasEntry::new(image::RgbImage::new(64, 64))
+    ///     .mip(AtlasEntryMipOption::Repeat)
+    ///     .group(Some(1));
+    /// ```
+    #[inline]
+    pub fn new(texture: I) -> Self {
+        Self::with_mip(texture, AtlasEntryMipOption::Clamp)
     }
 
-    let mut target_bins = BTreeMap::new();
-    for i in 0..max_page_count {
-        target_bins.insert(i, rectangle_pack::TargetBin::new(size, size, 1));
+    /// Returns a new entry of the texture with the tiling option and default other options.
+    #[inline]
+    pub fn with_mip(texture: I, mip: AtlasEntryMipOption) -> Self {
+        Self {
+            texture,
+            mip,
+            trim: Default::default(),
+            mip_filter: Default::default(),
+            anchor: Default::default(),
+            generate_mips: true,
+            group: Default::default(),
+            size: Default::default(),
+            depth: Default::default(),
+            gutter_wrap: Default::default(),
+            mip_wrap: Default::default(),
+            bake_rotations: Default::default(),
+            pin_page: Default::default(),
+        }
     }
 
-    let locations = rectangle_pack::pack_rects(
-        &rects,
-        &mut target_bins,
-        &rectangle_pack::volume_heuristic,
-        &rectangle_pack::contains_smallest_box,
-    )?;
+    /// Returns new entries of the textures of `(key, texture)` pairs with default options, and
+    /// their keys in the same order.
+    ///
+    /// The pairs are sorted by key (ties keep the iteration order), so the order of entries (and
+    /// packing) is deterministic even from a `HashMap`, and `keys[i]` looks up `texcoords[i]` of
+    /// the atlas.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use image_atlas::*;
+    ///
+    /// let images = std::collections::HashMap::from([
+    ///     ("grass", image::RgbaImage::new(32, 32)),
+    ///     ("stone", image::RgbaImage::new(32, 32)),
+    /// ]);
+    /// let (entries, keys) = AtlasEntry::from_iter_with_keys(images);
+    /// assert_eq!(keys, ["grass", "stone"]);
+    /// ```
+    #[inline]
+    pub fn from_iter_with_keys<K, T>(iter: T) -> (Vec<Self>, Vec<K>)
+    where
+        K: Ord,
+        T: IntoIterator<Item = (K, I)>,
+    {
+        let mut pairs = iter.into_iter().collect::<Vec<_>>();
+        pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        pairs
+            .into_iter()
+            .map(|(key, texture)| (Self::new(texture), key))
+            .unzip()
+    }
 
-    let mut page_count = 0;
-    let mut texcoords = vec![Texcoord::default(); entries.len()];
-    for (&i, &(page, location)) in locations.packed_locations() {
-        page_count = u32::max(page_count, page + 1);
+    /// Sets a mip map tiling option.
+    #[inline]
+    pub fn mip(mut self, mip: AtlasEntryMipOption) -> Self {
+        self.mip = mip;
+        self
+    }
 
-        let texcoord = Texcoord {
-            page,
-            min_x: location.x() + padding,
-            min_y: location.y() + padding,
-            max_x: location.x() + location.width() - padding,
-            max_y: location.y() + location.height() - padding,
-            size,
-        };
-        texcoords[i] = texcoord;
+    /// Sets whether transparent margins are cropped before packing.
+    #[inline]
+    pub fn trim(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self
     }
 
-    let mip_level_count = 1;
-    let mut textures = vec![Texture::new(size, mip_level_count); page_count as usize];
-    for (&i, &(page, location)) in locations.packed_locations() {
-        let entry = &entries[i];
+    /// Sets a mip map filter overriding the one of [AtlasMipOption](AtlasMipOption).
+    #[inline]
+    pub fn mip_filter(mut self, mip_filter: AtlasMipFilter) -> Self {
+        self.mip_filter = Some(mip_filter);
+        self
+    }
 
-        let src = resample(
-            &entry.texture,
-            entry.mip,
-            padding,
-            padding,
-            location.width(),
-            location.height(),
-        );
+    /// Sets a placement within the block-rounded region.
+    #[inline]
+    pub fn anchor(mut self, anchor: AtlasEntryAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
 
-        let target = &mut textures[page as usize].mip_maps[0];
-        image::imageops::replace(target, &src, location.x() as i64, location.y() as i64);
+    /// Sets whether mip maps of the entry are generated.
+    #[inline]
+    pub fn generate_mips(mut self, generate_mips: bool) -> Self {
+        self.generate_mips = generate_mips;
+        self
     }
 
-    Ok(Atlas {
-        page_count,
-        size,
-        mip_level_count,
-        textures,
-        texcoords,
-    })
-}
+    /// Sets a group id of entries to be packed into the same page.
+    #[inline]
+    pub fn group(mut self, group: Option<u32>) -> Self {
+        self.group = group;
+        self
+    }
 
-#[inline]
-fn create_atlas_mip_with_padding<I>(
-    max_page_count: u32,
-    size: u32,
-    filter: AtlasMipFilter,
-    padding: u32,
-    entries: &[AtlasEntry<I>],
-) -> Result<Atlas<I::Pixel>, AtlasError>
-where
-    I: image::GenericImage,
-    I::Pixel: 'static,
-{
-    if max_page_count == 0 {
-        return Err(AtlasError::ZeroMaxPageCount);
+    /// Sets a width and height of the entry in the atlas.
+    #[inline]
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = Some((width, height));
+        self
     }
 
-    if !size.is_power_of_two() {
-        return Err(AtlasError::InvalidSize(size));
+    /// Sets a number of slices stacked vertically in the texture.
+    #[inline]
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
     }
 
-    if entries.is_empty() {
-        return Err(AtlasError::ZeroEntry);
+    /// Sets a tiling option filling the padding (or gutter) ring.
+    #[inline]
+    pub fn gutter_wrap(mut self, gutter_wrap: AtlasEntryMipOption) -> Self {
+        self.gutter_wrap = Some(gutter_wrap);
+        self
     }
 
-    let mut rects = rectangle_pack::GroupedRectsToPlace::<_, ()>::new();
-    for (i, entry) in entries.iter().enumerate() {
-        let rect = rectangle_pack::RectToInsert::new(
-            entry.texture.width() + padding * 2,
-            entry.texture.height() + padding * 2,
+    /// Sets a tiling option of the entry edges while downscaling mip maps.
+    #[inline]
+    pub fn mip_wrap(mut self, mip_wrap: AtlasEntryMipOption) -> Self {
+        self.mip_wrap = Some(mip_wrap);
+        self
+    }
+
+    /// Sets whether copies of the entry rotated by 90, 180 and 270 degrees are packed too.
+    #[inline]
+    pub fn bake_rotations(mut self, bake_rotations: bool) -> Self {
+        self.bake_rotations = bake_rotations;
+        self
+    }
+
+    /// Sets the page the entry is forced onto.
+    #[inline]
+    pub fn pin_page(mut self, pin_page: u32) -> Self {
+        self.pin_page = Some(pin_page);
+        self
+    }
+
+    /// Returns the tiling options of the gutter ring and of mip map downscaling.
+    #[cfg(feature = "std")]
+    #[inline]
+    fn wraps(&self) -> (AtlasEntryMipOption, AtlasEntryMipOption) {
+        (
+            self.gutter_wrap.unwrap_or(self.mip),
+            self.mip_wrap.unwrap_or(self.mip),
+        )
+    }
+
+    /// Returns a copy of entry whose texture is mapped by `f`.
+    #[cfg(feature = "std")]
+    #[inline]
+    fn map_texture<J, F>(&self, f: F) -> AtlasEntry<J>
+    where
+        J: AtlasTexture,
+        F: FnOnce(&I) -> J,
+    {
+        AtlasEntry {
+            texture: f(&self.texture),
+            mip: self.mip,
+            trim: self.trim,
+            mip_filter: self.mip_filter,
+            anchor: self.anchor,
+            generate_mips: self.generate_mips,
+            group: self.group,
+            size: self.size,
+            depth: self.depth,
+            gutter_wrap: self.gutter_wrap,
+            mip_wrap: self.mip_wrap,
+            bake_rotations: self.bake_rotations,
+            pin_page: self.pin_page,
+        }
+    }
+}
+
+/// A stage of texture atlas generation reported by progress.
+///
+/// - `Packing`: Computing the layout of entries.
+/// - `Blitting`: Writing entries into the base mip map.
+/// - `MipGeneration`: Generating mip maps (per page, or per entry with `MipWithBlock`).
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AtlasProgressStage {
+    Packing,
+    Blitting,
+    MipGeneration,
+}
+
+/// A progress of texture atlas generation.
+///
+/// - `stage`: A current stage.
+/// - `done`: A count of finished steps in the stage.
+/// - `total`: A count of all steps in the stage.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AtlasProgress {
+    pub stage: AtlasProgressStage,
+    pub done: u32,
+    pub total: u32,
+}
+
+/// A progress callback of texture atlas generation.
+///
+/// Equality compares the address of callback, and debug format omits it.
+#[derive(Clone, Copy)]
+pub struct AtlasProgressFn<'a>(pub &'a dyn Fn(AtlasProgress));
+
+impl PartialEq for AtlasProgressFn<'_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for AtlasProgressFn<'_> {}
+
+impl fmt::Debug for AtlasProgressFn<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AtlasProgressFn").finish_non_exhaustive()
+    }
+}
+
+/// A texture atlas generation description.
+///
+/// - `max_page_count`: A maximum output texture count (0 is unbounded).
+/// - `min_page_count`: A minimum output texture count.
+/// - `size`: A texture width and height (same width and height, power of two for mip options
+///   without `relaxed_mip`; see [Pow2Size](Pow2Size)).
+/// - `mip`: A mip map method option.
+/// - `mip_color_space`: A color space used by mip map generation.
+/// - `premultiply_alpha`: Whether color channels are premultiplied by alpha during mip map
+///   generation (requires a pixel type with alpha channel).
+/// - `mip_sharpen`: An unsharp mask applied after each resize of mip map generation (`None`
+///   keeps the resized mip maps as is; see [SharpenParams](SharpenParams)).
+/// - `max_mip_level_count`: A maximum mip map count of output texture (`None` is full mip chain).
+/// - `mip_range`: A range of mip levels kept in output texture (`None` keeps all mip levels).
+/// - `relaxed_mip`: Whether mip options accept `size` that is not power of two.
+/// - `strict_gutter`: Whether the padding must cover the mip filter footprint at every mip level.
+/// - `allow_rotation`: Whether entries may be rotated by 90 degrees to improve packing density.
+/// - `deduplicate`: Whether pixel-identical entries share one location in the atlas.
+/// - `pack_heuristic`: A packing strategy.
+/// - `pack_algorithm`: A packing algorithm.
+/// - `stable`: Whether entries are packed in a canonical order independent of input order.
+/// - `balance_pages`: Whether entries are redistributed to even out the occupancy of pages.
+/// - `max_entries_per_page`: A maximum count of entries in each page (`None` is unlimited).
+/// - `sdf`: A signed distance field option (`None` stores the raw pixels).
+/// - `extrude`: A border width duplicated outward from the edge pixels of each entry.
+/// - `origin_alignment`: A multiple of the position of each entry in pixels (power of two).
+/// - `sub_block_size`: A cell size of shared blocks for small entries with block options.
+/// - `bleed_alpha`: Whether the color of opaque pixels is spread into transparent pixels.
+/// - `sampling`: A sampling method of entries resized to their `size`.
+/// - `downscale_oversized`: Whether entries larger than a page are downscaled to fit.
+/// - `channel_swizzle`: A source channel of each output channel (`None` keeps the order).
+/// - `background`: A color filling unused regions of output texture (`None` is zero-filled).
+/// - `reserve`: A width and height of blank regions packed without source texture.
+/// - `page_base`: A page index of the first output texture.
+/// - `occupied`: A texcoord of regions already used in the pages, which entries avoid.
+/// - `preserve`: An entry index and its previous texcoord, which the entry keeps.
+/// - `collect_free_rects`: Whether the free regions left by packing are returned.
+/// - `collect_packed_rects`: Whether the raw placements of the packer are returned.
+/// - `shrink_pages`: Whether each page is cropped to the power-of-two square of its contents.
+/// - `retain_sources`: Whether copies of the entries are kept in the atlas for re-baking.
+/// - `on_progress`: A callback reporting the progress of generation.
+/// - `entries`: A input texture entries.
+///
+/// See [Option interactions](crate#option-interactions) for how the options affect each other.
+///
+/// See the [AtlasDescriptorBuilder](AtlasDescriptorBuilder) for constructing with validation.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AtlasDescriptor<'a, I: AtlasTexture> {
+    pub max_page_count: u32,
+    pub min_page_count: u32,
+    pub size: u32,
+    pub mip: AtlasMipOption,
+    pub mip_color_space: AtlasMipColorSpace,
+    pub premultiply_alpha: bool,
+    pub mip_sharpen: Option<SharpenParams>,
+    pub max_mip_level_count: Option<u32>,
+    pub mip_range: Option<Range<u32>>,
+    pub relaxed_mip: bool,
+    pub strict_gutter: bool,
+    pub allow_rotation: bool,
+    pub deduplicate: bool,
+    pub pack_heuristic: PackHeuristic,
+    pub pack_algorithm: PackAlgorithm,
+    pub stable: bool,
+    pub balance_pages: bool,
+    pub max_entries_per_page: Option<u32>,
+    pub sdf: Option<SdfParams>,
+    pub extrude: u32,
+    pub origin_alignment: u32,
+    pub sub_block_size: Option<u32>,
+    pub bleed_alpha: bool,
+    pub sampling: AtlasSampling,
+    pub downscale_oversized: bool,
+    pub channel_swizzle: Option<[usize; 4]>,
+    pub background: Option<I::Pixel>,
+    pub reserve: &'a [(u32, u32)],
+    pub page_base: u32,
+    pub occupied: &'a [Texcoord],
+    pub preserve: &'a [(usize, Texcoord)],
+    pub collect_free_rects: bool,
+    pub collect_packed_rects: bool,
+    pub shrink_pages: bool,
+    pub retain_sources: bool,
+    pub on_progress: Option<AtlasProgressFn<'a>>,
+    pub entries: &'a [AtlasEntry<I>],
+}
+
+#[cfg(feature = "std")]
+impl<'a, I: image::GenericImageView> AtlasDescriptor<'a, I> {
+    /// Returns a new builder of texture atlas generation description.
+    #[inline]
+    pub fn builder() -> AtlasDescriptorBuilder<'a, I> {
+        AtlasDescriptorBuilder::new()
+    }
+}
+
+impl<I: AtlasTexture> AtlasDescriptor<'_, I> {
+    /// Returns a copy of description with the other entries.
+    #[inline]
+    fn with_entries<'b, J>(&'b self, entries: &'b [AtlasEntry<J>]) -> AtlasDescriptor<'b, J>
+    where
+        J: AtlasTexture<Pixel = I::Pixel>,
+    {
+        self.with_entries_and_background(entries, self.background)
+    }
+
+    /// Reports a progress of generation to `on_progress`.
+    #[inline]
+    fn report(&self, stage: AtlasProgressStage, done: u32, total: u32) {
+        if let Some(on_progress) = self.on_progress {
+            (on_progress.0)(AtlasProgress { stage, done, total });
+        }
+    }
+
+    /// Returns a copy of description with the other entries and background of their pixel type.
+    #[inline]
+    fn with_entries_and_background<'b, J>(
+        &'b self,
+        entries: &'b [AtlasEntry<J>],
+        background: Option<J::Pixel>,
+    ) -> AtlasDescriptor<'b, J>
+    where
+        J: AtlasTexture,
+    {
+        AtlasDescriptor {
+            max_page_count: self.max_page_count,
+            min_page_count: self.min_page_count,
+            size: self.size,
+            mip: self.mip,
+            mip_color_space: self.mip_color_space,
+            premultiply_alpha: self.premultiply_alpha,
+            mip_sharpen: self.mip_sharpen,
+            max_mip_level_count: self.max_mip_level_count,
+            mip_range: self.mip_range.clone(),
+            relaxed_mip: self.relaxed_mip,
+            strict_gutter: self.strict_gutter,
+            allow_rotation: self.allow_rotation,
+            deduplicate: self.deduplicate,
+            pack_heuristic: self.pack_heuristic,
+            pack_algorithm: self.pack_algorithm,
+            stable: self.stable,
+            balance_pages: self.balance_pages,
+            max_entries_per_page: self.max_entries_per_page,
+            sdf: self.sdf,
+            extrude: self.extrude,
+            origin_alignment: self.origin_alignment,
+            sub_block_size: self.sub_block_size,
+            bleed_alpha: self.bleed_alpha,
+            sampling: self.sampling,
+            downscale_oversized: self.downscale_oversized,
+            channel_swizzle: self.channel_swizzle,
+            background,
+            reserve: self.reserve,
+            page_base: self.page_base,
+            occupied: self.occupied,
+            preserve: self.preserve,
+            collect_free_rects: self.collect_free_rects,
+            collect_packed_rects: self.collect_packed_rects,
+            shrink_pages: self.shrink_pages,
+            retain_sources: self.retain_sources,
+            on_progress: self.on_progress,
+            entries,
+        }
+    }
+}
+
+impl<I: AtlasTexture> Default for AtlasDescriptor<'_, I> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_page_count: Default::default(),
+            min_page_count: Default::default(),
+            size: Default::default(),
+            mip: Default::default(),
+            mip_color_space: Default::default(),
+            premultiply_alpha: Default::default(),
+            mip_sharpen: Default::default(),
+            max_mip_level_count: Default::default(),
+            mip_range: Default::default(),
+            relaxed_mip: Default::default(),
+            strict_gutter: Default::default(),
+            allow_rotation: Default::default(),
+            deduplicate: Default::default(),
+            pack_heuristic: Default::default(),
+            pack_algorithm: Default::default(),
+            stable: Default::default(),
+            balance_pages: Default::default(),
+            max_entries_per_page: Default::default(),
+            sdf: Default::default(),
+            extrude: Default::default(),
+            origin_alignment: 1,
+            sub_block_size: Default::default(),
+            bleed_alpha: Default::default(),
+            sampling: Default::default(),
+            downscale_oversized: Default::default(),
+            channel_swizzle: Default::default(),
+            background: Default::default(),
+            reserve: &[],
+            page_base: Default::default(),
+            occupied: &[],
+            preserve: &[],
+            collect_free_rects: Default::default(),
+            collect_packed_rects: Default::default(),
+            shrink_pages: Default::default(),
+            retain_sources: Default::default(),
+            on_progress: Default::default(),
+            entries: &[],
+        }
+    }
+}
+
+/// A builder of texture atlas generation description.
+///
+/// All fields start from the default of [AtlasDescriptor](AtlasDescriptor) and the invariants
+/// checked by [create_atlas](create_atlas) are validated at `build`.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let entries = [AtlasEntry {
+///     texture: image::RgbImage::new(512, 512),
+///     mip: AtlasEntryMipOption::Clamp,
+///     ..Default::default()
+/// }];
+///
+/// let desc = AtlasDescriptor::builder()
+///     .max_page_count(8)
+///     .size(2048)
+///     .mip(AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 32))
+///     .entries(&entries)
+///     .build()
+///     .unwrap();
+///
+/// let atlas = create_atlas(&desc).unwrap();
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Default)]
+pub struct AtlasDescriptorBuilder<'a, I: image::GenericImageView> {
+    desc: AtlasDescriptor<'a, I>,
+}
+
+#[cfg(feature = "std")]
+impl<I> PartialEq for AtlasDescriptorBuilder<'_, I>
+where
+    I: image::GenericImageView + PartialEq,
+    I::Pixel: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.desc == other.desc
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I> Eq for AtlasDescriptorBuilder<'_, I>
+where
+    I: image::GenericImageView + Eq,
+    I::Pixel: Eq,
+{
+}
+
+#[cfg(feature = "std")]
+impl<I> fmt::Debug for AtlasDescriptorBuilder<'_, I>
+where
+    I: image::GenericImageView + fmt::Debug,
+    I::Pixel: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtlasDescriptorBuilder")
+            .field("desc", &self.desc)
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, I: image::GenericImageView> AtlasDescriptorBuilder<'a, I> {
+    /// Returns a new builder with default fields.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            desc: Default::default(),
+        }
+    }
+
+    /// Sets a maximum output texture count.
+    #[inline]
+    pub fn max_page_count(mut self, max_page_count: u32) -> Self {
+        self.desc.max_page_count = max_page_count;
+        self
+    }
+
+    /// Sets a minimum output texture count.
+    #[inline]
+    pub fn min_page_count(mut self, min_page_count: u32) -> Self {
+        self.desc.min_page_count = min_page_count;
+        self
+    }
+
+    /// Sets a texture width and height (same width and height).
+    #[inline]
+    pub fn size(mut self, size: u32) -> Self {
+        self.desc.size = size;
+        self
+    }
+
+    /// Sets a texture width and height of power of two, which is valid for every mip option.
+    #[inline]
+    pub fn pow2_size(mut self, size: Pow2Size) -> Self {
+        self.desc.size = size.get();
+        self
+    }
+
+    /// Sets a mip map method option.
+    #[inline]
+    pub fn mip(mut self, mip: AtlasMipOption) -> Self {
+        self.desc.mip = mip;
+        self
+    }
+
+    /// Sets a mip map method option together with its texture width and height of power of two.
+    #[inline]
+    pub fn mip_with_size(mut self, mip: AtlasMipOption, size: Pow2Size) -> Self {
+        self.desc.mip = mip;
+        self.desc.size = size.get();
+        self
+    }
+
+    /// Sets a color space used by mip map generation.
+    #[inline]
+    pub fn mip_color_space(mut self, mip_color_space: AtlasMipColorSpace) -> Self {
+        self.desc.mip_color_space = mip_color_space;
+        self
+    }
+
+    /// Sets whether color channels are premultiplied by alpha during mip map generation.
+    #[inline]
+    pub fn premultiply_alpha(mut self, premultiply_alpha: bool) -> Self {
+        self.desc.premultiply_alpha = premultiply_alpha;
+        self
+    }
+
+    /// Sets an unsharp mask applied after each resize of mip map generation.
+    #[inline]
+    pub fn mip_sharpen(mut self, mip_sharpen: SharpenParams) -> Self {
+        self.desc.mip_sharpen = Some(mip_sharpen);
+        self
+    }
+
+    /// Sets a maximum mip map count of output texture.
+    #[inline]
+    pub fn max_mip_level_count(mut self, max_mip_level_count: u32) -> Self {
+        self.desc.max_mip_level_count = Some(max_mip_level_count);
+        self
+    }
+
+    /// Sets a range of mip levels kept in output texture.
+    #[inline]
+    pub fn mip_range(mut self, mip_range: Range<u32>) -> Self {
+        self.desc.mip_range = Some(mip_range);
+        self
+    }
+
+    /// Sets whether mip options accept `size` that is not power of two.
+    #[inline]
+    pub fn relaxed_mip(mut self, relaxed_mip: bool) -> Self {
+        self.desc.relaxed_mip = relaxed_mip;
+        self
+    }
+
+    /// Sets whether the padding must cover the mip filter footprint at every mip level.
+    #[inline]
+    pub fn strict_gutter(mut self, strict_gutter: bool) -> Self {
+        self.desc.strict_gutter = strict_gutter;
+        self
+    }
+
+    /// Sets whether entries may be rotated by 90 degrees to improve packing density.
+    #[inline]
+    pub fn allow_rotation(mut self, allow_rotation: bool) -> Self {
+        self.desc.allow_rotation = allow_rotation;
+        self
+    }
+
+    /// Sets whether pixel-identical entries share one location in the atlas.
+    #[inline]
+    pub fn deduplicate(mut self, deduplicate: bool) -> Self {
+        self.desc.deduplicate = deduplicate;
+        self
+    }
+
+    /// Sets a packing strategy.
+    #[inline]
+    pub fn pack_heuristic(mut self, pack_heuristic: PackHeuristic) -> Self {
+        self.desc.pack_heuristic = pack_heuristic;
+        self
+    }
+
+    /// Sets a packing algorithm.
+    #[inline]
+    pub fn pack_algorithm(mut self, pack_algorithm: PackAlgorithm) -> Self {
+        self.desc.pack_algorithm = pack_algorithm;
+        self
+    }
+
+    /// Sets whether entries are packed in a canonical order independent of input order.
+    #[inline]
+    pub fn stable(mut self, stable: bool) -> Self {
+        self.desc.stable = stable;
+        self
+    }
+
+    /// Sets whether entries are redistributed to even out the occupancy of pages.
+    #[inline]
+    pub fn balance_pages(mut self, balance_pages: bool) -> Self {
+        self.desc.balance_pages = balance_pages;
+        self
+    }
+
+    /// Sets a maximum count of entries in each page.
+    #[inline]
+    pub fn max_entries_per_page(mut self, max_entries_per_page: u32) -> Self {
+        self.desc.max_entries_per_page = Some(max_entries_per_page);
+        self
+    }
+
+    /// Sets a page index of the first output texture.
+    #[inline]
+    pub fn page_base(mut self, page_base: u32) -> Self {
+        self.desc.page_base = page_base;
+        self
+    }
+
+    /// Sets a texcoord of regions already used in the pages.
+    #[inline]
+    pub fn occupied(mut self, occupied: &'a [Texcoord]) -> Self {
+        self.desc.occupied = occupied;
+        self
+    }
+
+    /// Sets an entry index and its previous texcoord kept by the entry.
+    #[inline]
+    pub fn preserve(mut self, preserve: &'a [(usize, Texcoord)]) -> Self {
+        self.desc.preserve = preserve;
+        self
+    }
+
+    /// Sets whether the free regions left by packing are returned.
+    #[inline]
+    pub fn collect_free_rects(mut self, collect_free_rects: bool) -> Self {
+        self.desc.collect_free_rects = collect_free_rects;
+        self
+    }
+
+    /// Sets whether the raw placements of the packer are returned.
+    #[inline]
+    pub fn collect_packed_rects(mut self, collect_packed_rects: bool) -> Self {
+        self.desc.collect_packed_rects = collect_packed_rects;
+        self
+    }
+
+    /// Sets whether each page is cropped to the power-of-two square of its contents.
+    #[inline]
+    pub fn shrink_pages(mut self, shrink_pages: bool) -> Self {
+        self.desc.shrink_pages = shrink_pages;
+        self
+    }
+
+    /// Sets whether copies of the entries are kept in the atlas for re-baking.
+    #[inline]
+    pub fn retain_sources(mut self, retain_sources: bool) -> Self {
+        self.desc.retain_sources = retain_sources;
+        self
+    }
+
+    /// Sets a callback reporting the progress of generation.
+    #[inline]
+    pub fn on_progress(mut self, on_progress: &'a dyn Fn(AtlasProgress)) -> Self {
+        self.desc.on_progress = Some(AtlasProgressFn(on_progress));
+        self
+    }
+
+    /// Sets a signed distance field option.
+    #[inline]
+    pub fn sdf(mut self, sdf: SdfParams) -> Self {
+        self.desc.sdf = Some(sdf);
+        self
+    }
+
+    /// Sets a border width duplicated outward from the edge pixels of each entry.
+    #[inline]
+    pub fn extrude(mut self, extrude: u32) -> Self {
+        self.desc.extrude = extrude;
+        self
+    }
+
+    /// Sets a multiple of the position of each entry in pixels (power of two).
+    #[inline]
+    pub fn origin_alignment(mut self, origin_alignment: u32) -> Self {
+        self.desc.origin_alignment = origin_alignment;
+        self
+    }
+
+    /// Sets a cell size of shared blocks for small entries with block options.
+    #[inline]
+    pub fn sub_block_size(mut self, sub_block_size: u32) -> Self {
+        self.desc.sub_block_size = Some(sub_block_size);
+        self
+    }
+
+    /// Sets whether the color of opaque pixels is spread into transparent pixels.
+    #[inline]
+    pub fn bleed_alpha(mut self, bleed_alpha: bool) -> Self {
+        self.desc.bleed_alpha = bleed_alpha;
+        self
+    }
+
+    /// Sets a sampling method of entries resized to their `size`.
+    #[inline]
+    pub fn sampling(mut self, sampling: AtlasSampling) -> Self {
+        self.desc.sampling = sampling;
+        self
+    }
+
+    /// Sets whether entries larger than a page are downscaled to fit.
+    #[inline]
+    pub fn downscale_oversized(mut self, downscale_oversized: bool) -> Self {
+        self.desc.downscale_oversized = downscale_oversized;
+        self
+    }
+
+    /// Sets a source channel of each output channel.
+    #[inline]
+    pub fn channel_swizzle(mut self, channel_swizzle: [usize; 4]) -> Self {
+        self.desc.channel_swizzle = Some(channel_swizzle);
+        self
+    }
+
+    /// Sets a color filling unused regions of output texture.
+    #[inline]
+    pub fn background(mut self, background: I::Pixel) -> Self {
+        self.desc.background = Some(background);
+        self
+    }
+
+    /// Sets a width and height of blank regions packed without source texture.
+    #[inline]
+    pub fn reserve(mut self, reserve: &'a [(u32, u32)]) -> Self {
+        self.desc.reserve = reserve;
+        self
+    }
+
+    /// Sets a input texture entries.
+    #[inline]
+    pub fn entries(mut self, entries: &'a [AtlasEntry<I>]) -> Self {
+        self.desc.entries = entries;
+        self
+    }
+
+    /// Returns a texture atlas generation description.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `size` is not power of two for mip map options (or is zero with `relaxed_mip`).
+    /// - `block_size` is not power of two.
+    /// - `origin_alignment` is not power of two.
+    /// - `sub_block_size` is not power of two from 2 to less than the block, or has no block
+    ///   option.
+    /// - `block_size` is larger than `size`.
+    /// - `premultiply_alpha` or `bleed_alpha` is enabled for pixel without alpha channel.
+    /// - `channel_swizzle` refers to a channel out of the pixel.
+    /// - `sdf` has a `spread` which is not a positive finite number.
+    /// - `max_mip_level_count` is zero.
+    /// - `max_entries_per_page` is zero.
+    /// - `mip_range` is empty for mip map options.
+    /// - `strict_gutter` is enabled and the padding is narrower than the mip filter footprint.
+    /// - `min_page_count` is larger than nonzero `max_page_count`.
+    /// - `entries` is empty.
+    /// - `preserve` refers to an entry out of `entries`.
+    ///
+    /// See the [AtlasError](AtlasError) for details.
+    #[inline]
+    pub fn build(self) -> Result<AtlasDescriptor<'a, I>, AtlasError> {
+        validate(&self.desc)?;
+        Ok(self.desc)
+    }
+}
+
+/// Creates a new texture atlas.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - `size` is not power of two for mip map options (or is zero with `relaxed_mip`).
+/// - `block_size` is not power of two.
+/// - `origin_alignment` is not power of two.
+/// - `sub_block_size` is not power of two from 2 to less than the block, or has no block option.
+/// - `block_size` is larger than `size`.
+/// - `premultiply_alpha` or `bleed_alpha` is enabled for pixel without alpha channel.
+/// - `channel_swizzle` refers to a channel out of the pixel.
+/// - `sdf` has a `spread` which is not a positive finite number.
+/// - `max_mip_level_count` is zero.
+/// - `max_entries_per_page` is zero.
+/// - `mip_range` is empty for mip map options.
+/// - `mip_range` starts at or after the mip map count for mip map options.
+/// - `strict_gutter` is enabled and the padding is narrower than the mip filter footprint.
+/// - `min_page_count` is larger than nonzero `max_page_count`.
+/// - `entries` and `reserve` are empty.
+/// - `preserve` refers to an entry out of `entries`.
+/// - An entry has zero width or height.
+/// - An entry is larger than `size`.
+/// - An entry has a non-finite float subpixel with mip map generation.
+/// - Entries pinned to a page do not fit in it, or the page is out of the pages.
+/// - An entry in `preserve` can not be placed back at its previous texcoord.
+/// - A position or extent of the layout overflows `u32`.
+/// - An internal invariant is violated (a bug of this crate).
+/// - Packing error occurred.
+///
+/// See the [AtlasError](AtlasError) for details.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let atlas = create_atlas(&AtlasDescriptor {
+///     max_page_count: 8,
+///     size: 2048,
+///     mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 32),
+///     entries: &[AtlasEntry {
+///         texture: image::RgbImage::new(512, 512),
+///         mip: AtlasEntryMipOption::Clamp,
+///         ..Default::default()
+///     }],
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// ```
+#[cfg(feature = "std")]
+#[rustfmt::skip]
+pub fn create_atlas<I>(desc: &AtlasDescriptor<'_, I>) -> Result<Atlas<I::Pixel>, AtlasError>
+where
+    I: image::GenericImage,
+    I::Pixel: 'static,
+{
+    create_atlas_with_buffers(desc, &mut Vec::new())
+}
+
+/// Creates a new texture atlas from owned entries.
+///
+/// `entries` replaces `entries` of the description, so the description can be built without
+/// entries and the entries can be built in place without keeping a borrowed slice alive.
+///
+/// # Errors
+///
+/// Returns an error in the same condition as [create_atlas](create_atlas).
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// fn build(textures: Vec<image::RgbImage>) -> Atlas<image::Rgb<u8>> {
+///     let entries = textures
+///         .into_iter()
+///         .map(|texture| AtlasEntry {
+///             texture,
+///             ..Default::default()
+///         })
+///         .collect();
+///     let desc = AtlasDescriptor {
+///         max_page_count: 8,
+///         size: 2048,
+///         mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 32),
+///         ..Default::default()
+///     };
+///     create_atlas_owned(&desc, entries).unwrap()
+/// }
+///
+/// let atlas = build(vec![image::RgbImage::new(512, 512)]);
+/// assert_eq!(atlas.texcoords.len(), 1);
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn create_atlas_owned<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    entries: Vec<AtlasEntry<I>>,
+) -> Result<Atlas<I::Pixel>, AtlasError>
+where
+    I: image::GenericImage,
+    I::Pixel: 'static,
+{
+    create_atlas(&desc.with_entries(&entries))
+}
+
+/// Creates a new texture atlas into the existing atlas, reusing its texture buffers.
+///
+/// Each texture of `atlas` whose size and mip map count match the new layout is cleared in place
+/// instead of reallocated, which avoids allocator churn when regenerating an atlas repeatedly.
+/// Otherwise new textures are allocated. The atlas is left unchanged on error.
+///
+/// # Errors
+///
+/// Returns an error in the same condition as [create_atlas](create_atlas).
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let desc = AtlasDescriptor {
+///     max_page_count: 8,
+///     size: 2048,
+///     mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 32),
+///     entries: &[AtlasEntry {
+///         texture: image::RgbImage::new(512, 512),
+///         mip: AtlasEntryMipOption::Clamp,
+///         ..Default::default()
+///     }],
+///     ..Default::default()
+/// };
+///
+/// let mut atlas = create_atlas(&desc).unwrap();
+/// create_atlas_into(&mut atlas, &desc).unwrap();
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn create_atlas_into<I>(
+    atlas: &mut Atlas<I::Pixel>,
+    desc: &AtlasDescriptor<'_, I>,
+) -> Result<(), AtlasError>
+where
+    I: image::GenericImage,
+    I::Pixel: 'static,
+{
+    let mut buffers = std::mem::take(&mut atlas.textures);
+    match create_atlas_with_buffers(desc, &mut buffers) {
+        Ok(result) => {
+            *atlas = result;
+            Ok(())
+        }
+        Err(err) => {
+            atlas.textures = buffers;
+            Err(err)
+        }
+    }
+}
+
+/// Creates a new texture atlas where entries keep their texcoords of a previous generation.
+///
+/// `previous` pairs an entry index with its texcoord from an earlier atlas (e.g. cached UVs of
+/// shipped assets), and replaces `preserve` of the description. Those entries are placed back at
+/// their texcoords, and the other entries are packed into the free space around them, so a rebake
+/// with new entries or a larger `max_page_count` does not shift the texcoords already in use.
+/// The previous texcoords need the layout options of their generation (see
+/// [AtlasDescriptor](AtlasDescriptor)).
+///
+/// # Errors
+///
+/// Returns an error in the same condition as [create_atlas](create_atlas).
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let desc = AtlasDescriptor {
+///     max_page_count: 1,
+///     size: 256,
+///     mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 16),
+///     entries: &[AtlasEntry::new(image::RgbaImage::new(64, 64))],
+///     ..Default::default()
+/// };
+/// let atlas = create_atlas(&desc).unwrap();
+///
+/// let entries = [
+///     AtlasEntry::new(image::RgbaImage::new(96, 96)),
+///     AtlasEntry::new(image::RgbaImage::new(64, 64)),
+/// ];
+/// let previous = [(1, atlas.texcoords[0])];
+/// let desc = AtlasDescriptor {
+///     max_page_count: 2,
+///     entries: &entries,
+///     ..desc
+/// };
+/// let rebaked = create_atlas_preserving(&desc, &previous).unwrap();
+/// assert_eq!(rebaked.texcoords[1], atlas.texcoords[0]);
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn create_atlas_preserving<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    previous: &[(usize, Texcoord)],
+) -> Result<Atlas<I::Pixel>, AtlasError>
+where
+    I: image::GenericImage,
+    I::Pixel: 'static,
+{
+    create_atlas(&AtlasDescriptor {
+        preserve: previous,
+        ..desc.with_entries(desc.entries)
+    })
+}
+
+/// Computes the layout of entries from their width and height, without pixels.
+///
+/// Each size is packed like an entry of `desc` (with rotation, padding and block) and its texcoord
+/// is stored in `texcoords` in order. `entries` of `desc` are ignored, and its `reserve` regions
+/// are packed after the sizes. `deduplicate`, `trim` and `retain_sources` do not apply since no
+/// pixel is read, and `shrink_pages` and `mip_range` apply only to generated pages. It is
+/// available without `std` feature, so the layout can be computed where `image` is unavailable.
+///
+/// # Errors
+///
+/// Returns an error in the same condition as [create_atlas](create_atlas) except for the checks
+/// of the pixel type, where an entry is a size.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let desc = AtlasDescriptor::<BlankTexture> {
+///     max_page_count: 1,
+///     size: 256,
+///     mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 16),
+///     ..Default::default()
+/// };
+///
+/// let layout = plan_atlas(&desc, &[(64, 64), (32, 48)]).unwrap();
+/// assert_eq!(layout.page_count, 1);
+/// assert_eq!(layout.texcoords.len(), 2);
+/// ```
+#[inline]
+pub fn plan_atlas<I: AtlasTexture>(
+    desc: &AtlasDescriptor<'_, I>,
+    sizes: &[(u32, u32)],
+) -> Result<AtlasLayout, AtlasError> {
+    let (rotations, mut layout) = planned_layout(desc, sizes)?;
+    for texcoord in &mut layout.texcoords {
+        texcoord.source_width = texcoord.max_x - texcoord.min_x;
+        texcoord.source_height = texcoord.max_y - texcoord.min_y;
+    }
+    let reserved = layout.texcoords.split_off(sizes.len());
+    let mut texcoords = layout.texcoords;
+    planned_texcoords(&mut texcoords, sizes, &rotations);
+
+    Ok(AtlasLayout {
+        page_count: layout.page_count,
+        size: desc.size,
+        mip_level_count: layout.mip_level_count,
+        page_base: desc.page_base,
+        texcoords,
+        reserved,
+        free_rects: layout.free_rects,
+        packed_rects: layout.packed_rects,
+    })
+}
+
+/// Packs sizes as reserved regions followed by `reserve` regions of the description, and
+/// returns whether each size is stored transposed with the layout.
+#[inline]
+fn planned_layout<I: AtlasTexture>(
+    desc: &AtlasDescriptor<'_, I>,
+    sizes: &[(u32, u32)],
+) -> Result<(Vec<bool>, PackedLayout), AtlasError> {
+    pack_oriented(desc.allow_rotation, sizes, (&[], &[]), |rotations| {
+        let reserve = planned_extents(desc, sizes, rotations);
+        let desc = AtlasDescriptor {
+            deduplicate: false,
+            retain_sources: false,
+            reserve: &reserve,
+            preserve: &[],
+            ..desc.with_entries::<I>(&[])
+        };
+        validate_layout(&desc)?;
+        pack_layout(&desc, &reserve, &[])
+    })
+}
+
+/// Returns the sizes as laid out followed by `reserve` regions of the description.
+#[inline]
+fn planned_extents<I: AtlasTexture>(
+    desc: &AtlasDescriptor<'_, I>,
+    sizes: &[(u32, u32)],
+    rotations: &[bool],
+) -> Vec<(u32, u32)> {
+    sizes
+        .iter()
+        .zip(rotations)
+        .map(|(&(width, height), &rotated)| match rotated {
+            true => (height, width),
+            false => (width, height),
+        })
+        .chain(desc.reserve.iter().copied())
+        .collect()
+}
+
+/// Sets the orientation and the source size of the texcoord of each size.
+#[inline]
+fn planned_texcoords(texcoords: &mut [Texcoord], sizes: &[(u32, u32)], rotations: &[bool]) {
+    for ((texcoord, &(width, height)), &rotated) in texcoords.iter_mut().zip(sizes).zip(rotations) {
+        texcoord.rotated = rotated;
+        texcoord.source_width = width;
+        texcoord.source_height = height;
+    }
+}
+
+/// Creates a new texture atlas laid out from the width and height of entries, without their
+/// textures.
+///
+/// The pages are laid out as [plan_atlas](plan_atlas) computes and keep `background`. Then each
+/// entry is written by [bake_entry](Atlas::bake_entry) one at a time, so the full set of source
+/// images never has to be held in memory.
+///
+/// `block_waste` and `alpha_coverage` of an entry are filled when it is baked. Baking needs the
+/// packing state, so `shrink_pages` or `mip_range` starting above zero leaves an atlas which can
+/// not be baked.
+///
+/// # Errors
+///
+/// Returns an error in the same condition as [create_atlas](create_atlas), where an entry is a
+/// size.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let desc = AtlasDescriptor::<image::RgbaImage> {
+///     max_page_count: 1,
+///     size: 256,
+///     mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 16),
+///     ..Default::default()
+/// };
+///
+/// let mut atlas = create_atlas_planned(&desc, &[(64, 64), (32, 48)]).unwrap();
+/// for (index, &(width, height)) in [(64, 64), (32, 48)].iter().enumerate() {
+///     let texture = image::RgbaImage::new(width, height);
+///     atlas.bake_entry(index, &AtlasEntry::new(texture)).unwrap();
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn create_atlas_planned<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    sizes: &[(u32, u32)],
+) -> Result<Atlas<I::Pixel>, AtlasError>
+where
+    I: image::GenericImage,
+    I::Pixel: 'static,
+{
+    let (rotations, _) = planned_layout(desc, sizes)?;
+    let reserve = planned_extents(desc, sizes, &rotations);
+    let desc = AtlasDescriptor {
+        deduplicate: false,
+        retain_sources: false,
+        reserve: &reserve,
+        preserve: &[],
+        ..desc.with_entries::<I>(&[])
+    };
+
+    let mut atlas = create_atlas(&desc)?;
+    let reserved = atlas.reserved.split_off(sizes.len());
+    atlas.texcoords = std::mem::replace(&mut atlas.reserved, reserved);
+    planned_texcoords(&mut atlas.texcoords, sizes, &rotations);
+    atlas.block_waste = vec![0; sizes.len()];
+    atlas.scale_factors = vec![1.0; sizes.len()];
+    atlas.alpha_coverage = vec![Vec::new(); sizes.len()];
+    atlas.baked_rotations = vec![None; sizes.len()];
+    Ok(atlas)
+}
+
+/// Creates a new 3D texture atlas whose pages are volumes of `size` in width, height and depth.
+///
+/// Each entry is a brick of `depth` slices stacked vertically in its texture, and the bricks are
+/// packed by the depth dimension of `rectangle_pack`, which 2D atlases fix to one. The padding of
+/// the mip option (or half-block gutter) surrounds each brick on all three axes and is filled by
+/// its tiling option. Mip maps are not generated, and blocks do not round bricks.
+///
+/// Only `max_page_count`, `min_page_count`, `size`, `mip`, `pack_heuristic`, `stable`,
+/// `background`, `page_base` and `entries` (`texture`, `mip`, `group` and `depth`) of the
+/// description are used, so bricks are always packed by `rectangle_pack` (skyline and shelf
+/// packing are 2D). Rows of the texture beyond a multiple of `depth` are ignored.
+///
+/// # Errors
+///
+/// Returns an error in the same condition as [create_atlas](create_atlas), where an entry is too
+/// large when its brick with padding exceeds `size` on any axis.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let atlas = create_volume_atlas(&AtlasDescriptor {
+///     max_page_count: 1,
+///     size: 64,
+///     mip: AtlasMipOption::NoMipWithPadding(1),
+///     entries: &[AtlasEntry::new(image::RgbaImage::new(16, 16 * 8)).depth(8)],
+///     ..Default::default()
+/// })
+/// .unwrap();
+///
+/// let texcoord = atlas.texcoords[0];
+/// assert_eq!(texcoord.max_z - texcoord.min_z, 8);
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn create_volume_atlas<I>(
+    desc: &AtlasDescriptor<'_, I>,
+) -> Result<VolumeAtlas<I::Pixel>, AtlasError>
+where
+    I: image::GenericImageView,
+{
+    validate(desc)?;
+
+    let AtlasDescriptor { size, entries, .. } = *desc;
+    let (padding, _) = mip_layout(desc.mip, 0);
+
+    let mut extents = Vec::with_capacity(entries.len());
+    let mut rects = Vec::with_capacity(entries.len());
+    for (i, entry) in entries.iter().enumerate() {
+        let (width, height) = entry.texture.dimensions();
+        let depth = entry.depth.unwrap_or(1);
+        let height = height.checked_div(depth).unwrap_or(0);
+        if width == 0 || height == 0 {
+            return Err(AtlasError::ZeroSizedEntry { index: i });
+        }
+
+        let padded = |extent| padded(extent, padding).ok_or(AtlasError::Overflow);
+        let rect =
+            rectangle_pack::RectToInsert::new(padded(width)?, padded(height)?, padded(depth)?);
+        if rect.width() > size || rect.height() > size || rect.depth() > size {
+            return Err(AtlasError::EntryTooLarge {
+                index: i,
+                width: rect.width(),
+                height: rect.height(),
+                max: size,
+            });
+        }
+
+        extents.push((width, height, depth));
+        rects.push((i, rect));
+    }
+
+    let groups = entries.iter().map(|entry| entry.group).collect::<Vec<_>>();
+    let pack = |page_count| {
+        let mut target_bins = (0..page_count)
+            .map(|page| (page, rectangle_pack::TargetBin::new(size, size, size)))
+            .collect();
+        pack_rects(
+            &rects,
+            &groups,
+            &mut target_bins,
+            (size, size),
+            desc.pack_heuristic,
+            PackAlgorithm::RectanglePack,
+            desc.stable,
+        )
+    };
+    let locations = if desc.max_page_count != 0 {
+        pack(desc.max_page_count)?
+    } else {
+        let volume = rects
+            .iter()
+            .map(|(_, rect)| rect.width() as u64 * rect.height() as u64 * rect.depth() as u64)
+            .sum::<u64>();
+        let bin_volume = (size as u64).saturating_pow(3);
+        let mut page_count = u32::try_from(volume.div_ceil(bin_volume)).unwrap_or(u32::MAX);
+        loop {
+            match pack(page_count.max(desc.min_page_count).max(1)) {
+                Err(_) if (page_count as usize) < rects.len() => {
+                    page_count = page_count.saturating_mul(2)
+                }
+                result => break result?,
+            }
+        }
+    };
+
+    let page_count = locations
+        .values()
+        .map(|&(page, _)| page + 1)
+        .fold(desc.min_page_count, u32::max);
+    let slice = match desc.background {
+        Some(background) => image::ImageBuffer::from_pixel(size, size, background),
+        None => image::ImageBuffer::new(size, size),
+    };
+    let mut pages = vec![vec![slice; size as usize]; page_count as usize];
+    let mut texcoords = vec![VolumeTexcoord::default(); entries.len()];
+    for (&i, &(page, location)) in &locations {
+        let entry = &entries[i];
+        let (width, height, depth) = extents[i];
+        for z in 0..location.depth() {
+            let (gutter_wrap, _) = entry.wraps();
+            let src_z = wrap(gutter_wrap, z as i32 - padding as i32, depth as i32) as u32;
+            let view = entry.texture.view(0, src_z * height, width, height);
+            let src = resample(
+                &*view,
+                gutter_wrap,
+                false,
+                (padding, padding),
+                (location.width(), location.height()),
+                (width, height),
+                AtlasSampling::Nearest,
+            )?;
+            let target = &mut pages[page as usize][(location.z() + z) as usize];
+            image::imageops::replace(target, &src, location.x() as i64, location.y() as i64);
+        }
+
+        let page = desc
+            .page_base
+            .checked_add(page)
+            .ok_or(AtlasError::Overflow)?;
+        let (min_x, min_y, min_z) = (
+            location.x() + padding,
+            location.y() + padding,
+            location.z() + padding,
+        );
+        texcoords[i] = VolumeTexcoord {
+            page,
+            min_x,
+            min_y,
+            min_z,
+            max_x: min_x + width,
+            max_y: min_y + height,
+            max_z: min_z + depth,
+            size,
+        };
+    }
+
+    Ok(VolumeAtlas {
+        page_count,
+        size,
+        pages,
+        texcoords,
+    })
+}
+
+#[cfg(feature = "std")]
+#[rustfmt::skip]
+#[inline]
+fn create_atlas_with_buffers<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    buffers: &mut Vec<Texture<I::Pixel>>,
+) -> Result<Atlas<I::Pixel>, AtlasError>
+where
+    I: image::GenericImageView,
+    I::Pixel: 'static,
+{
+    validate(desc)?;
+
+    if desc.retain_sources {
+        let desc = AtlasDescriptor {
+            retain_sources: false,
+            ..desc.with_entries(desc.entries)
+        };
+
+        let mut atlas = create_atlas_with_buffers(&desc, buffers)?;
+        atlas.sources = desc
+            .entries
+            .iter()
+            .map(|entry| entry.map_texture(owned_texture))
+            .collect();
+        if let Some(packer) = &mut atlas.packer {
+            packer.retain_sources = true;
+        }
+        return Ok(atlas);
+    }
+
+    if let Some(sdf) = desc.sdf {
+        let entries = desc
+            .entries
+            .iter()
+            .map(|entry| entry.map_texture(|texture| signed_distance_field(texture, sdf)))
+            .collect::<Vec<_>>();
+        let desc = AtlasDescriptor {
+            sdf: None,
+            ..desc.with_entries(&entries)
+        };
+
+        let mut atlas = create_atlas_with_buffers(&desc, buffers)?;
+        if let Some(packer) = &mut atlas.packer {
+            packer.sdf = Some(sdf);
+        }
+        return Ok(atlas);
+    }
+
+    if let Some(swizzle) = desc.channel_swizzle {
+        let entries = desc
+            .entries
+            .iter()
+            .map(|entry| entry.map_texture(|texture| swizzle_channels(texture, swizzle)))
+            .collect::<Vec<_>>();
+        let desc = AtlasDescriptor {
+            channel_swizzle: None,
+            ..desc.with_entries(&entries)
+        };
+
+        let mut atlas = create_atlas_with_buffers(&desc, buffers)?;
+        if let Some(packer) = &mut atlas.packer {
+            packer.channel_swizzle = Some(swizzle);
+        }
+        return Ok(atlas);
+    }
+
+    if desc.entries.iter().any(|entry| entry.bake_rotations) {
+        let baked = (0..desc.entries.len())
+            .filter(|&i| desc.entries[i].bake_rotations)
+            .collect::<Vec<_>>();
+        let copies = baked
+            .iter()
+            .flat_map(|&i| (1..4).map(move |turns| rotate_entry(&desc.entries[i], turns)));
+        let entries = desc
+            .entries
+            .iter()
+            .map(|entry| entry.map_texture(|_| BakedTexture::Entry(&entry.texture)))
+            .chain(copies)
+            .map(|entry| AtlasEntry { bake_rotations: false, ..entry })
+            .collect::<Vec<_>>();
+
+        let mut atlas = create_atlas_unbaked(&desc.with_entries(&entries), buffers)?;
+        split_baked_rotations(&mut atlas, &baked);
+        return Ok(atlas);
+    }
+
+    create_atlas_unbaked(desc, buffers)
+}
+
+/// Generates the atlas from entries without `bake_rotations`, whose rotated copies are already
+/// appended, so the texture type borrowing the entries is not nested by the recursion.
+#[cfg(feature = "std")]
+#[rustfmt::skip]
+#[inline]
+fn create_atlas_unbaked<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    buffers: &mut Vec<Texture<I::Pixel>>,
+) -> Result<Atlas<I::Pixel>, AtlasError>
+where
+    I: image::GenericImageView,
+    I::Pixel: 'static,
+{
+    if desc.downscale_oversized {
+        let (border, (block_width, block_height)) = mip_layout(desc.mip, desc.extrude);
+        let limit = if (block_width, block_height) != (1, 1) {
+            desc.size.saturating_sub(block_width.max(block_height))
+        } else {
+            let slack = desc.origin_alignment - 1;
+            desc.size.saturating_sub(border.saturating_mul(2).saturating_add(slack))
+        };
+        let scales = desc
+            .entries
+            .iter()
+            .map(|entry| downscale(entry.size.unwrap_or(entry.texture.dimensions()), limit))
+            .collect::<Vec<_>>();
+
+        let filter = match desc.mip {
+            AtlasMipOption::NoMip | AtlasMipOption::NoMipWithPadding(_) => None,
+            AtlasMipOption::Mip(filter)
+            | AtlasMipOption::MipWithPadding(filter, _)
+            | AtlasMipOption::MipWithBlock(filter, _)
+            | AtlasMipOption::MipWithBlockRect(filter, _, _) => Some(filter),
+        };
+
+        if scales.iter().any(|&(_, scale)| scale != 1.0) {
+            let entries = desc
+                .entries
+                .iter()
+                .zip(&scales)
+                .map(|(entry, &(size, scale))| {
+                    let texture = &entry.texture;
+                    let filter = filter.map(|filter| entry.mip_filter.unwrap_or(filter));
+                    let texture = match filter {
+                        Some(filter) if scale != 1.0 => {
+                            let options = (desc.mip_color_space, desc.premultiply_alpha, None);
+                            resize(texture, size.0, size.1, filter, options)
+                        }
+                        _ => {
+                            resample(texture, entry.mip, false, (0, 0), size, size, desc.sampling)?
+                        }
+                    };
+                    Ok(AtlasEntry {
+                        size: None,
+                        ..entry.map_texture(|_| texture)
+                    })
+                })
+                .collect::<Result<Vec<_>, AtlasError>>()?;
+            let desc = AtlasDescriptor {
+                downscale_oversized: false,
+                ..desc.with_entries(&entries)
+            };
+
+            let mut atlas = create_atlas_unbaked(&desc, buffers)?;
+            atlas.scale_factors = scales.iter().map(|&(_, scale)| scale).collect();
+            return Ok(atlas);
+        }
+    }
+
+    if desc.entries.iter().any(|entry| resized(entry).is_some()) {
+        let entries = desc
+            .entries
+            .iter()
+            .map(|entry| resize_entry(entry, desc.sampling))
+            .collect::<Result<Vec<_>, AtlasError>>()?;
+        return create_atlas_unbaked(&desc.with_entries(&entries), buffers);
+    }
+
+    if !matches!(desc.mip, AtlasMipOption::NoMip | AtlasMipOption::NoMipWithPadding(_)) {
+        let textures = desc.entries.iter().map(|entry| &entry.texture).collect::<Vec<_>>();
+        if let Some(index) = non_finite_entry(&textures) {
+            return Err(AtlasError::NonFiniteSubpixel { index });
+        }
+    }
+
+    let mut atlas = match desc.mip {
+        AtlasMipOption::NoMip => {
+            create_atlas_with_padding(desc, buffers, 0)
+        }
+        AtlasMipOption::NoMipWithPadding(padding) => {
+            create_atlas_with_padding(desc, buffers, padding)
+        }
+        AtlasMipOption::Mip(filter) => {
+            create_atlas_mip_with_padding(desc, buffers, filter, 0)
+        }
+        AtlasMipOption::MipWithPadding(filter, padding) => {
+            create_atlas_mip_with_padding(desc, buffers, filter, padding)
+        }
+        AtlasMipOption::MipWithBlock(filter, _) | AtlasMipOption::MipWithBlockRect(filter, _, _) => {
+            create_atlas_mip_with_block(desc, buffers, filter)
+        }
+    }?;
+
+    for &(index, previous) in desc.preserve {
+        let texcoord = atlas.texcoords[index];
+        let extent = |t: Texcoord| (t.page, t.min_x, t.min_y, t.max_x, t.max_y);
+        if extent(texcoord) != extent(previous) {
+            return Err(AtlasError::PreservedMismatch { index });
+        }
+    }
+
+    if desc.shrink_pages {
+        shrink_pages(&mut atlas);
+    }
+
+    let mip_start = mip_start(desc);
+    if mip_start > 0 {
+        keep_mip_range(&mut atlas, mip_start);
+    }
+
+    Ok(atlas)
+}
+
+/// Drops the mip maps below `mip_start`, so `mip_maps[0]` of each texture is the mip level
+/// `mip_start`.
+///
+/// Each texture becomes the chain from `mip_start` with its size and padding at that mip level,
+/// and a page cropped by `shrink_pages` to fewer mip maps is left with none. The packing state
+/// blits into the base mip map, so it is dropped.
+#[cfg(feature = "std")]
+#[inline]
+fn keep_mip_range<P: image::Pixel>(atlas: &mut Atlas<P>, mip_start: u32) {
+    for texture in &mut atlas.textures {
+        let dropped = texture.mip_maps.len().min(mip_start as usize);
+        texture.mip_maps.drain(..dropped);
+        texture.size >>= mip_start;
+        texture.mip_level_count = texture.mip_level_count.saturating_sub(mip_start);
+        texture.padding >>= mip_start;
+    }
+    atlas.mip_level_count -= mip_start;
+    atlas.mip_start = mip_start;
+    atlas.packer = None;
+}
+
+/// Crops each page to the smallest power-of-two square covering its contents with padding.
+///
+/// A power of two keeps every option valid, and it is a multiple of block size whenever it covers
+/// a block location, so block-rounded regions are never cut. The packing state no longer matches
+/// the pages, so it is dropped.
+#[cfg(feature = "std")]
+#[inline]
+fn shrink_pages<P: image::Pixel + 'static>(atlas: &mut Atlas<P>) {
+    let mut sizes = vec![1; atlas.textures.len()];
+    for texcoord in atlas.texcoords.iter().chain(&atlas.reserved) {
+        let page = (texcoord.page - atlas.page_base) as usize;
+        let extent = u32::max(texcoord.max_x, texcoord.max_y) + atlas.textures[page].padding;
+        sizes[page] = u32::max(sizes[page], round_up_pow2(extent).min(atlas.size));
+    }
+
+    for (texture, &size) in atlas.textures.iter_mut().zip(&sizes) {
+        if size >= texture.size {
+            continue;
+        }
+
+        let mip_level_count = texture.mip_level_count.min(size.ilog2() + 1);
+        texture.mip_maps.truncate(mip_level_count as usize);
+        for (mip_level, mip_map) in texture.mip_maps.iter_mut().enumerate() {
+            let size = size >> mip_level;
+            *mip_map = image::imageops::crop_imm(mip_map, 0, 0, size, size).to_image();
+        }
+        texture.size = size;
+        texture.mip_level_count = mip_level_count;
+    }
+
+    for texcoord in atlas.texcoords.iter_mut().chain(&mut atlas.reserved) {
+        texcoord.size = sizes[(texcoord.page - atlas.page_base) as usize];
+    }
+    for (free_rects, &size) in atlas.free_rects.iter_mut().zip(&sizes) {
+        free_rects.retain_mut(|texcoord| {
+            texcoord.max_x = texcoord.max_x.min(size);
+            texcoord.max_y = texcoord.max_y.min(size);
+            texcoord.size = size;
+            texcoord.min_x < texcoord.max_x && texcoord.min_y < texcoord.max_y
+        });
+    }
+    atlas.packer = None;
+}
+
+/// Creates a new texture atlas from entries of dynamic image.
+///
+/// Each entry is converted into RGBA8 before packing, so textures of different pixel types (e.g.
+/// loaded image files and procedurally generated images) can be mixed in one atlas.
+///
+/// # Errors
+///
+/// Returns an error in the same condition as [create_atlas](create_atlas).
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let atlas = create_atlas_dynamic(&AtlasDescriptor {
+///     max_page_count: 8,
+///     size: 2048,
+///     mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 32),
+///     entries: &[
+///         AtlasEntry {
+///             texture: image::RgbImage::new(512, 512).into(),
+///             mip: AtlasEntryMipOption::Clamp,
+///             ..Default::default()
+///         },
+///         AtlasEntry {
+///             texture: image::GrayImage::new(256, 256).into(),
+///             mip: AtlasEntryMipOption::Clamp,
+///             ..Default::default()
+///         },
+///     ],
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn create_atlas_dynamic(
+    desc: &AtlasDescriptor<'_, image::DynamicImage>,
+) -> Result<Atlas<image::Rgba<u8>>, AtlasError> {
+    let entries = desc
+        .entries
+        .iter()
+        .map(|entry| entry.map_texture(image::DynamicImage::to_rgba8))
+        .collect::<Vec<_>>();
+    create_atlas(&desc.with_entries(&entries))
+}
+
+/// Loads every image file in a directory as entries, and returns them with their file stems.
+///
+/// Files are sorted by file name, so the order of entries (and packing) is deterministic, and the
+/// stems are in the same order for keying texcoords. Subdirectories and files whose extension is
+/// not a readable image format are skipped. Formats are enabled by `png`, `hdr` and `exr`
+/// features.
+///
+/// # Errors
+///
+/// Returns an error when the directory can not be read, or a file can not be decoded. A decode
+/// error is of `io::ErrorKind::InvalidData` with the file path in the message.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use image_atlas::*;
+///
+/// let (entries, names) =
+///     entries_from_dir("assets/sprites".as_ref(), AtlasEntryMipOption::Clamp).unwrap();
+/// let atlas = create_atlas_dynamic(&AtlasDescriptor {
+///     max_page_count: 8,
+///     size: 2048,
+///     mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 32),
+///     entries: &entries,
+///     ..Default::default()
+/// })
+/// .unwrap();
+///
+/// let texcoords = names.iter().zip(&atlas.texcoords).collect::<std::collections::HashMap<_, _>>();
+/// ```
+#[cfg(feature = "png")]
+pub fn entries_from_dir(
+    path: &path::Path,
+    mip: AtlasEntryMipOption,
+) -> io::Result<(Vec<AtlasEntry<image::DynamicImage>>, Vec<String>)> {
+    let mut paths = Vec::new();
+    for dir_entry in fs::read_dir(path)? {
+        let path = dir_entry?.path();
+        let readable =
+            image::ImageFormat::from_path(&path).is_ok_and(|format| format.reading_enabled());
+        if path.is_file() && readable {
+            paths.push(path);
+        }
+    }
+    paths.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+
+    let mut entries = Vec::with_capacity(paths.len());
+    let mut names = Vec::with_capacity(paths.len());
+    for path in paths {
+        let texture = image::open(&path).map_err(|err| match err {
+            image::ImageError::IoError(err) => err,
+            err => io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), err),
+            ),
+        })?;
+        entries.push(AtlasEntry {
+            texture,
+            mip,
+            ..Default::default()
+        });
+        let stem = path.file_stem().unwrap_or_default();
+        names.push(stem.to_string_lossy().into_owned());
+    }
+    Ok((entries, names))
+}
+
+/// Returns a checkerboard texture of two colors alternating in square cells of `cell_size`.
+///
+/// The top-left cell has `colors[0]`, and cells at the right and bottom edges are cut by the
+/// extent. A zero `cell_size` is treated as 1.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let texture = checkerboard(4, 4, 2, [image::Luma([0u8]), image::Luma([255])]);
+/// assert_eq!(texture.get_pixel(2, 0), &image::Luma([255]));
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn checkerboard<P>(
+    width: u32,
+    height: u32,
+    cell_size: u32,
+    colors: [P; 2],
+) -> image::ImageBuffer<P, Vec<P::Subpixel>>
+where
+    P: image::Pixel,
+{
+    let cell_size = cell_size.max(1);
+    image::ImageBuffer::from_fn(width, height, |x, y| {
+        colors[((x / cell_size + y / cell_size) & 1) as usize]
+    })
+}
+
+/// Returns a magenta and black checkerboard texture with cells of `cell_size`, a placeholder for
+/// entries which fail to load.
+///
+/// See the [checkerboard](checkerboard) for other colors.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let entry = AtlasEntry::new(debug_texture(64, 64, 8));
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn debug_texture(width: u32, height: u32, cell_size: u32) -> image::RgbaImage {
+    let colors = [image::Rgba([255, 0, 255, 255]), image::Rgba([0, 0, 0, 255])];
+    checkerboard(width, height, cell_size, colors)
+}
+
+/// Returns the smallest power of two greater than or equal to `size`.
+///
+/// This is a valid `size` (or `block_size`) for mip options. Zero rounds up to 1, and values above
+/// `2^31` saturate to `2^31`, the largest power of two in `u32`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(image_atlas::round_up_pow2(1000), 1024);
+/// assert_eq!(image_atlas::round_up_pow2(1024), 1024);
+/// ```
+#[inline]
+pub const fn round_up_pow2(size: u32) -> u32 {
+    match size.checked_next_power_of_two() {
+        Some(size) => size,
+        None => 1 << 31,
+    }
+}
+
+/// A power-of-two page size, valid for every mip option.
+///
+/// The power of two is checked at construction, so a descriptor whose size is set by
+/// [pow2_size](AtlasDescriptorBuilder::pow2_size) or
+/// [mip_with_size](AtlasDescriptorBuilder::mip_with_size) never fails with `InvalidSize`. The
+/// plain `u32` `size` is still accepted, and it is checked only for mip options. With `serde`
+/// feature, it is serialized as `u32` and checked on deserialization.
+///
+/// # Examples
+///
+/// ```
+/// use image_atlas::Pow2Size;
+///
+/// assert_eq!(Pow2Size::new(1024), Pow2Size::from_log2(10));
+/// assert_eq!(Pow2Size::new(1000), None);
+/// assert_eq!(Pow2Size::from_log2(10).unwrap().get(), 1024);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u32", into = "u32"))]
+pub struct Pow2Size(u32);
+
+impl Pow2Size {
+    /// Returns a size if it is a power of two.
+    #[inline]
+    pub const fn new(size: u32) -> Option<Self> {
+        match size.is_power_of_two() {
+            true => Some(Self(size)),
+            false => None,
+        }
+    }
+
+    /// Returns a size of `2^log2`, or `None` if it overflows `u32`.
+    #[inline]
+    pub const fn from_log2(log2: u32) -> Option<Self> {
+        match 1u32.checked_shl(log2) {
+            Some(size) => Some(Self(size)),
+            None => None,
+        }
+    }
+
+    /// Returns the size as `u32`.
+    #[inline]
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the base-2 logarithm of the size.
+    #[inline]
+    pub const fn log2(self) -> u32 {
+        self.0.ilog2()
+    }
+}
+
+impl From<Pow2Size> for u32 {
+    #[inline]
+    fn from(value: Pow2Size) -> Self {
+        value.get()
+    }
+}
+
+impl TryFrom<u32> for Pow2Size {
+    type Error = AtlasError;
+
+    #[inline]
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Self::new(value).ok_or(AtlasError::InvalidSize(value))
+    }
+}
+
+/// The smallest page size tried by [create_atlas_auto](create_atlas_auto).
+pub const AUTO_MIN_SIZE: u32 = 256;
+
+/// The largest page size tried by [create_atlas_auto](create_atlas_auto).
+pub const AUTO_MAX_SIZE: u32 = 16384;
+
+/// Creates a new texture atlas with the smallest power-of-two page size that fits all entries.
+///
+/// Page sizes from [AUTO_MIN_SIZE](AUTO_MIN_SIZE) to [AUTO_MAX_SIZE](AUTO_MAX_SIZE) are tried in
+/// increasing order, and the first one that packs all entries in `max_page_count` pages is used.
+/// With unbounded `max_page_count` of zero, it is the first size fitting the largest entry.
+///
+/// # Errors
+///
+/// Returns an error in the same condition as [create_atlas](create_atlas), except that packing
+/// failures and a block larger than the page are retried with the next size. If even the largest
+/// size fails, its error is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let atlas = create_atlas_auto(
+///     1,
+///     AtlasMipOption::NoMip,
+///     &[AtlasEntry {
+///         texture: image::RgbImage::new(300, 300),
+///         ..Default::default()
+///     }],
+/// )
+/// .unwrap();
+/// assert_eq!(atlas.size, 512);
+/// ```
+#[cfg(feature = "std")]
+pub fn create_atlas_auto<I>(
+    max_page_count: u32,
+    mip: AtlasMipOption,
+    entries: &[AtlasEntry<I>],
+) -> Result<Atlas<I::Pixel>, AtlasError>
+where
+    I: image::GenericImage,
+    I::Pixel: 'static,
+{
+    let mut size = AUTO_MIN_SIZE;
+    loop {
+        let result = AtlasDescriptor::builder()
+            .max_page_count(max_page_count)
+            .size(size)
+            .mip(mip)
+            .entries(entries)
+            .build()
+            .and_then(|desc| create_atlas(&desc));
+
+        match result {
+            Err(
+                AtlasError::Packing(_)
+                | AtlasError::EntryTooLarge { .. }
+                | AtlasError::BlockLargerThanPage { .. },
+            ) if size < AUTO_MAX_SIZE => {
+                size <<= 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Returns the smallest power-of-two page size that fits all entries, without generation.
+///
+/// Page sizes from [AUTO_MIN_SIZE](AUTO_MIN_SIZE) to [AUTO_MAX_SIZE](AUTO_MAX_SIZE) are
+/// binary-searched by packing the layout of entries only (with the padding or block of `mip`),
+/// so no page is allocated and no pixel is written. The result is the size that
+/// [create_atlas_auto](create_atlas_auto) would use when packing succeeds for every larger size,
+/// which holds for practical inputs. It lets GPU resources be allocated before generation.
+///
+/// # Errors
+///
+/// Returns an error in the same condition as [create_atlas_auto](create_atlas_auto). The largest
+/// size is packed first, so its error is returned when no size fits.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let size = minimum_size(
+///     1,
+///     AtlasMipOption::NoMipWithPadding(2),
+///     &[AtlasEntry::new(image::RgbImage::new(300, 300))],
+/// )
+/// .unwrap();
+/// assert_eq!(size, 512);
+/// ```
+pub fn minimum_size<I: AtlasTexture>(
+    max_page_count: u32,
+    mip: AtlasMipOption,
+    entries: &[AtlasEntry<I>],
+) -> Result<u32, AtlasError> {
+    let extents = entries
+        .iter()
+        .map(|entry| {
+            let (_, _, width, height) = match entry.trim {
+                true => entry.texture.trim_bounds(),
+                false => (0, 0, entry.texture.extent().0, entry.texture.extent().1),
+            };
+            entry.size.unwrap_or((width, height))
+        })
+        .collect::<Vec<_>>();
+    let fits = |size: u32| {
+        let desc = AtlasDescriptor {
+            max_page_count,
+            size,
+            mip,
+            entries,
+            ..Default::default()
+        };
+        validate_layout(&desc)?;
+        pack_layout(&desc, &extents, &[])
+    };
+
+    fits(AUTO_MAX_SIZE)?;
+    let (mut low, mut high) = (AUTO_MIN_SIZE.ilog2(), AUTO_MAX_SIZE.ilog2());
+    while low < high {
+        let mid = (low + high) / 2;
+        match fits(1 << mid) {
+            Err(
+                AtlasError::Packing(_)
+                | AtlasError::EntryTooLarge { .. }
+                | AtlasError::BlockLargerThanPage { .. },
+            ) => low = mid + 1,
+            result => {
+                result?;
+                high = mid;
+            }
+        }
+    }
+    Ok(1 << low)
+}
+
+/// Creates a new texture atlas whose output pixel type differs from the entries.
+///
+/// Each entry (and `background`) is converted into the output pixel type by the color conversion
+/// of `image` before packing, e.g. RGB entries can be packed into RGBA pages.
+///
+/// # Errors
+///
+/// Returns an error in the same condition as [create_atlas](create_atlas).
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// let atlas = create_atlas_as::<image::Rgba<u8>, _>(&AtlasDescriptor {
+///     max_page_count: 8,
+///     size: 2048,
+///     mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 32),
+///     entries: &[AtlasEntry {
+///         texture: image::RgbImage::new(512, 512),
+///         mip: AtlasEntryMipOption::Clamp,
+///         ..Default::default()
+///     }],
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn create_atlas_as<Q, I>(desc: &AtlasDescriptor<'_, I>) -> Result<Atlas<Q>, AtlasError>
+where
+    Q: image::Pixel + 'static,
+    I: image::GenericImageView,
+    image::ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>:
+        image::buffer::ConvertBuffer<image::ImageBuffer<Q, Vec<Q::Subpixel>>>,
+{
+    use image::buffer::ConvertBuffer;
+
+    let entries = desc
+        .entries
+        .iter()
+        .map(|entry| {
+            entry.map_texture(|texture| {
+                let (width, height) = texture.dimensions();
+                image::ImageBuffer::from_fn(width, height, |x, y| texture.get_pixel(x, y)).convert()
+            })
+        })
+        .collect::<Vec<_>>();
+    let background = desc.background.map(|background| {
+        let converted: image::ImageBuffer<Q, _> =
+            image::ImageBuffer::from_pixel(1, 1, background).convert();
+        *converted.get_pixel(0, 0)
+    });
+    create_atlas(&desc.with_entries_and_background(&entries, background))
+}
+
+/// Returns a total byte length of texture atlas with the given layout.
+///
+/// This matches [Atlas::memory_usage](Atlas::memory_usage) of the generated atlas, so it can be
+/// used to choose `size` and `max_page_count` before generation.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_atlas::*;
+///
+/// // 4 pages of 2048x2048 RGBA8 with full mip chain.
+/// let bytes = estimate_memory(2048, 4, 12, 4);
+/// assert_eq!(bytes, 89478480);
+/// ```
+#[inline]
+pub fn estimate_memory(
+    size: u32,
+    page_count: u32,
+    mip_level_count: u32,
+    bytes_per_pixel: u32,
+) -> u64 {
+    let page_bytes = (0..mip_level_count)
+        .map(|mip_level| (size >> mip_level) as u64)
+        .map(|size| size * size * bytes_per_pixel as u64)
+        .sum::<u64>();
+    page_bytes * page_count as u64
+}
+
+/// Checks the invariants of texture atlas generation description.
+///
+/// This is the single place of the checks, called by [build](AtlasDescriptorBuilder::build) and
+/// before every generation, so the generation methods can assume a valid description. The checks
+/// of the pixel type follow the ones of the layout.
+#[cfg(feature = "std")]
+#[inline]
+fn validate<I>(desc: &AtlasDescriptor<'_, I>) -> Result<(), AtlasError>
+where
+    I: image::GenericImageView,
+{
+    validate_layout(desc)?;
+
+    let require_mip = !matches!(
+        desc.mip,
+        AtlasMipOption::NoMip | AtlasMipOption::NoMipWithPadding(_)
+    );
+    if require_mip && desc.premultiply_alpha && !I::Pixel::HAS_ALPHA {
+        return Err(AtlasError::NoAlphaChannel);
+    }
+
+    if !require_mip && desc.bleed_alpha && !I::Pixel::HAS_ALPHA {
+        return Err(AtlasError::NoAlphaChannel);
+    }
+
+    if let Some(swizzle) = desc.channel_swizzle {
+        validate_swizzle::<I::Pixel>(swizzle)?;
+    }
+
+    Ok(())
+}
+
+/// Checks the invariants of the description which do not depend on the pixel type.
+///
+/// The layout computation without pixels (e.g. [plan_atlas](plan_atlas)) checks only these. The
+/// gutter is checked against the mip filter only for `Mip` and `MipWithPadding`, since the block
+/// options resize each entry alone and only the page-wide resize reads neighbors.
+#[inline]
+fn validate_layout<I: AtlasTexture>(desc: &AtlasDescriptor<'_, I>) -> Result<(), AtlasError> {
+    let require_mip = !matches!(
+        desc.mip,
+        AtlasMipOption::NoMip | AtlasMipOption::NoMipWithPadding(_)
+    );
+    let relaxed = desc.relaxed_mip && desc.size > 0;
+    if require_mip && !desc.size.is_power_of_two() && !relaxed {
+        return Err(AtlasError::InvalidSize(desc.size));
+    }
+
+    let block_sizes = match desc.mip {
+        AtlasMipOption::MipWithBlock(_, block_size) => [block_size, block_size],
+        AtlasMipOption::MipWithBlockRect(_, block_width, block_height) => {
+            [block_width, block_height]
+        }
+        _ => [1, 1],
+    };
+    if let Some(&block_size) = block_sizes.iter().find(|size| !size.is_power_of_two()) {
+        return Err(AtlasError::InvalidBlockSize(block_size));
+    }
+
+    if !desc.origin_alignment.is_power_of_two() {
+        return Err(AtlasError::InvalidOriginAlignment(desc.origin_alignment));
+    }
+
+    if let Some(sub_block_size) = desc.sub_block_size {
+        let block_size = block_sizes[0].min(block_sizes[1]);
+        if !sub_block_size.is_power_of_two() || sub_block_size < 2 || sub_block_size >= block_size {
+            return Err(AtlasError::InvalidSubBlockSize(sub_block_size));
+        }
+    }
+
+    if let Some(&block_size) = block_sizes.iter().find(|&&size| size > desc.size) {
+        return Err(AtlasError::BlockLargerThanPage {
+            block_size,
+            size: desc.size,
+        });
+    }
+
+    if let Some(sdf) = desc
+        .sdf
+        .filter(|sdf| !(sdf.spread.is_finite() && sdf.spread > 0.0))
+    {
+        return Err(AtlasError::InvalidSdfSpread(sdf.spread));
+    }
+
+    if require_mip && desc.max_mip_level_count == Some(0) {
+        return Err(AtlasError::ZeroMipLevelCount);
+    }
+
+    if desc.max_entries_per_page == Some(0) {
+        return Err(AtlasError::ZeroMaxEntriesPerPage);
+    }
+
+    let enabled = match desc.pack_algorithm {
+        PackAlgorithm::RectanglePack => true,
+        PackAlgorithm::Skyline => cfg!(feature = "skyline"),
+        PackAlgorithm::Shelf => cfg!(feature = "shelf"),
+    };
+    if !enabled {
+        return Err(AtlasError::PackAlgorithmDisabled(desc.pack_algorithm));
+    }
+
+    if let Some(mip_range) = desc.mip_range.as_ref().filter(|_| require_mip) {
+        if mip_range.is_empty() {
+            return Err(AtlasError::EmptyMipRange(mip_range.clone()));
+        }
+    }
+
+    let filter = match desc.mip {
+        AtlasMipOption::Mip(filter) | AtlasMipOption::MipWithPadding(filter, _) => Some(filter),
+        _ => None,
+    };
+    if let Some(filter) = filter.filter(|_| desc.strict_gutter) {
+        let mip_level_count = capped_mip_level_count(desc, desc.size.trailing_zeros() + 1);
+        let needed = (filter_reach(filter) as u64) << (mip_level_count - 1);
+        let needed = u32::try_from(needed).unwrap_or(u32::MAX);
+        let (got, _) = mip_layout(desc.mip, desc.extrude);
+        if got < needed {
+            return Err(AtlasError::InsufficientGutter { needed, got });
+        }
+    }
+
+    if desc.max_page_count != 0 && desc.min_page_count > desc.max_page_count {
+        return Err(AtlasError::MinPageCountExceedsMax {
+            min_page_count: desc.min_page_count,
+            max_page_count: desc.max_page_count,
+        });
+    }
+
+    if desc.entries.is_empty() && desc.reserve.is_empty() {
+        return Err(AtlasError::ZeroEntry);
+    }
+
+    if let Some(&(index, _)) = desc
+        .preserve
+        .iter()
+        .find(|&&(i, _)| i >= desc.entries.len())
+    {
+        return Err(AtlasError::NoEntry { index });
+    }
+
+    let extents = desc
+        .entries
+        .iter()
+        .map(|entry| entry.size.unwrap_or(entry.texture.extent()))
+        .chain(desc.reserve.iter().copied());
+    for (i, (width, height)) in extents.enumerate() {
+        if width == 0 || height == 0 {
+            return Err(AtlasError::ZeroSizedEntry { index: i });
+        }
+    }
+
+    Ok(())
+}
+
+/// A layout of entries and reserved regions packed into pages, before any pixel is written.
+///
+/// - `page_count`: A page count (at least `min_page_count`).
+/// - `mip_level_count`: A mip map count of the pages.
+/// - `padding`: A padding of the pages (the half-block gutter with blocks).
+/// - `texcoords`: A texcoord of each entry followed by reserved regions. The texcoords of aliased
+///   entries are left default.
+/// - `locations`: A location of each packed rect, in cells of its unit.
+/// - `units`: A cell size in pixels of the location of each entry and reserved region.
+/// - `block_waste`: Pixels added by rounding each entry up to its unit.
+/// - `sub_blocks`: A sub-block size of each entry packed into a shared block.
+/// - `target_bins`: The target bins of the pages after packing.
+/// - `free_rects`, `packed_rects`: The free sections and placements when requested.
+struct PackedLayout {
+    page_count: u32,
+    mip_level_count: u32,
+    #[cfg(feature = "std")]
+    padding: u32,
+    texcoords: Vec<Texcoord>,
+    #[cfg(feature = "std")]
+    locations: PackedLocations,
+    #[cfg(feature = "std")]
+    units: Vec<(u32, u32)>,
+    #[cfg(feature = "std")]
+    block_waste: Vec<u32>,
+    #[cfg(feature = "std")]
+    sub_blocks: BTreeMap<usize, u32>,
+    #[cfg(feature = "std")]
+    target_bins: BTreeMap<u32, rectangle_pack::TargetBin>,
+    free_rects: Vec<Vec<Texcoord>>,
+    packed_rects: Vec<PackedRect>,
+}
+
+/// Packs entries and reserved regions of `extents` by the mip option of the description.
+///
+/// `extents` are the entries as laid out (rotated and trimmed) followed by reserved regions, and
+/// an entry aliasing another one by `aliases` is not packed (none without aliases).
+///
+/// The mip chain stops where the page side becomes odd, which is the full chain of power-of-two
+/// sizes and ends early for sizes allowed by `relaxed_mip`. A `mip_range` starting at or after
+/// the end of the chain fails with `MipLevelOutOfRange`.
+#[inline]
+fn pack_layout<I: AtlasTexture>(
+    desc: &AtlasDescriptor<'_, I>,
+    extents: &[(u32, u32)],
+    aliases: &[usize],
+) -> Result<PackedLayout, AtlasError> {
+    let mip_level_count = capped_mip_level_count(desc, desc.size.trailing_zeros() + 1);
+    let layout = match desc.mip {
+        AtlasMipOption::NoMip => pack_with_padding(desc, extents, aliases, desc.extrude, 1),
+        AtlasMipOption::NoMipWithPadding(padding) => {
+            let border = padding
+                .checked_add(desc.extrude)
+                .ok_or(AtlasError::Overflow)?;
+            pack_with_padding(desc, extents, aliases, border, 1)
+        }
+        AtlasMipOption::Mip(_) => pack_with_padding(desc, extents, aliases, 0, mip_level_count),
+        AtlasMipOption::MipWithPadding(_, padding) => {
+            pack_with_padding(desc, extents, aliases, padding, mip_level_count)
+        }
+        AtlasMipOption::MipWithBlock(_, block_size) => {
+            pack_with_block(desc, extents, aliases, (block_size, block_size))
+        }
+        AtlasMipOption::MipWithBlockRect(_, block_width, block_height) => {
+            pack_with_block(desc, extents, aliases, (block_width, block_height))
+        }
+    }?;
+
+    let mip_start = mip_start(desc);
+    if mip_start >= layout.mip_level_count {
+        return Err(AtlasError::MipLevelOutOfRange {
+            mip_level: mip_start,
+            mip_level_count: layout.mip_level_count,
+        });
+    }
+    Ok(layout)
+}
+
+/// Packs `sizes` by `pack` given whether each one is transposed, and returns the orientations
+/// with the layout.
+///
+/// With `allow_rotation`, the sizes are packed as they are, with every size taller than wide
+/// transposed, and with every size wider than tall transposed. In each of them, a size too large
+/// for a page (e.g. in non-square blocks) is transposed alone and packed again. The layout with
+/// the fewest pages, then the smallest bounding area on its last page, is kept, and the error of
+/// the sizes as they are is returned if none packs.
+///
+/// An entry aliasing another one by `aliases` takes its orientation, and an entry of `preserve`
+/// keeps the one of its previous texcoord.
+#[inline]
+fn pack_oriented<F>(
+    allow_rotation: bool,
+    sizes: &[(u32, u32)],
+    (aliases, preserve): (&[usize], &[(usize, Texcoord)]),
+    mut pack: F,
+) -> Result<(Vec<bool>, PackedLayout), AtlasError>
+where
+    F: FnMut(&[bool]) -> Result<PackedLayout, AtlasError>,
+{
+    if !allow_rotation {
+        let rotations = vec![false; sizes.len()];
+        let layout = pack(&rotations)?;
+        return Ok((rotations, layout));
+    }
+
+    let mut pack_with = |transposed: fn(u32, u32) -> bool| {
+        let mut rotations = sizes
+            .iter()
+            .map(|&(width, height)| transposed(width, height))
+            .collect::<Vec<_>>();
+        let mut fixed = vec![false; sizes.len()];
+        for &(i, previous) in preserve {
+            if let Some(rotated) = rotations.get_mut(i) {
+                *rotated = previous.rotated;
+                fixed[i] = true;
+            }
+        }
+        loop {
+            for (i, &alias) in aliases.iter().enumerate() {
+                rotations[i] = rotations[alias];
+            }
+            match pack(&rotations) {
+                Err(AtlasError::EntryTooLarge { index, .. })
+                    if fixed.get(index).is_some_and(|&fixed| !fixed) =>
+                {
+                    rotations[index] = !rotations[index];
+                    fixed[index] = true;
+                }
+                result => break result.map(|layout| (rotations, layout)),
+            }
+        }
+    };
+
+    let mut best = pack_with(|_, _| false);
+    for transposed in [
+        |width, height| height > width,
+        |width, height| width > height,
+    ] {
+        if let Ok(candidate) = pack_with(transposed) {
+            if best
+                .as_ref()
+                .map_or(true, |best| area(&candidate.1) < area(&best.1))
+            {
+                best = Ok(candidate);
+            }
+        }
+    }
+    best
+}
+
+/// Returns the page count and the bounding area of the texcoords on the last page of a layout.
+#[inline]
+fn area(layout: &PackedLayout) -> (u32, u64) {
+    let last = layout.texcoords.iter().map(|texcoord| texcoord.page).max();
+    let (width, height) = layout
+        .texcoords
+        .iter()
+        .filter(|texcoord| Some(texcoord.page) == last)
+        .fold((0, 0), |(width, height), texcoord| {
+            (
+                u32::max(width, texcoord.max_x),
+                u32::max(height, texcoord.max_y),
+            )
+        });
+    (layout.page_count, width as u64 * height as u64)
+}
+
+/// Returns the first mip level kept by `mip_range` of the description (0 without mip maps).
+#[inline]
+fn mip_start<I: AtlasTexture>(desc: &AtlasDescriptor<'_, I>) -> u32 {
+    match desc.mip {
+        AtlasMipOption::NoMip | AtlasMipOption::NoMipWithPadding(_) => 0,
+        _ => desc
+            .mip_range
+            .as_ref()
+            .map_or(0, |mip_range| mip_range.start),
+    }
+}
+
+/// Returns a mip map count capped by `max_mip_level_count` and `mip_range` of the description.
+#[inline]
+fn capped_mip_level_count<I: AtlasTexture>(
+    desc: &AtlasDescriptor<'_, I>,
+    mip_level_count: u32,
+) -> u32 {
+    let mip_level_count = desc
+        .max_mip_level_count
+        .map_or(mip_level_count, |max| mip_level_count.min(max));
+    desc.mip_range
+        .as_ref()
+        .map_or(mip_level_count, |mip_range| {
+            mip_level_count.min(mip_range.end)
+        })
+}
+
+/// Packs entries and reserved regions with `border` pixels (padding and extruded border) around
+/// each of them.
+#[inline]
+fn pack_with_padding<I: AtlasTexture>(
+    desc: &AtlasDescriptor<'_, I>,
+    extents: &[(u32, u32)],
+    aliases: &[usize],
+    border: u32,
+    mip_level_count: u32,
+) -> Result<PackedLayout, AtlasError> {
+    let size = desc.size;
+
+    let mut rects = Vec::new();
+    for (i, &(width, height)) in extents.iter().enumerate() {
+        if aliases.get(i).is_some_and(|&alias| alias != i) {
+            continue;
+        }
+
+        if width == 0 || height == 0 {
+            return Err(AtlasError::ZeroSizedEntry { index: i });
+        }
+
+        let slack = desc.origin_alignment - 1;
+        let width = padded(width, border).and_then(|width| width.checked_add(slack));
+        let height = padded(height, border).and_then(|height| height.checked_add(slack));
+        let (width, height) = width.zip(height).ok_or(AtlasError::Overflow)?;
+        if width > size || height > size {
+            return Err(AtlasError::EntryTooLarge {
+                index: i,
+                width,
+                height,
+                max: size,
+            });
+        }
+
+        let rect = rectangle_pack::RectToInsert::new(width, height, 1);
+        rects.push((i, rect));
+    }
+
+    desc.report(AtlasProgressStage::Packing, 0, 1);
+    let (target_bins, locations) = pack_pages(
+        desc,
+        &rects,
+        &BTreeMap::new(),
+        (size, size),
+        (1, 1),
+        (border, border),
+    )?;
+    let locations = aligned_locations(locations, border, desc.origin_alignment);
+    desc.report(AtlasProgressStage::Packing, 1, 1);
+
+    let mut page_count = desc.min_page_count;
+    let mut texcoords = vec![Texcoord::default(); extents.len()];
+    for (&i, &(page, location)) in &locations {
+        page_count = u32::max(page_count, page + 1);
+
+        let page = desc.page_base.checked_add(page);
+        let texcoord = page.and_then(|page| {
+            placed_texcoord(page, location, (1, 1), (border, border), extents[i], size)
+        });
+        texcoords[i] = texcoord.ok_or(AtlasError::Overflow)?;
+    }
+
+    Ok(PackedLayout {
+        page_count,
+        mip_level_count,
+        #[cfg(feature = "std")]
+        padding: border,
+        texcoords,
+        #[cfg(feature = "std")]
+        units: vec![(1, 1); extents.len()],
+        #[cfg(feature = "std")]
+        block_waste: vec![0; desc.entries.len()],
+        #[cfg(feature = "std")]
+        sub_blocks: BTreeMap::new(),
+        free_rects: free_rects(desc, &target_bins, page_count, (1, 1)),
+        packed_rects: packed_rects(desc, &locations),
+        #[cfg(feature = "std")]
+        locations,
+        #[cfg(feature = "std")]
+        target_bins,
+    })
+}
+
+/// Packs entries and reserved regions in blocks with the half-block gutter, and entries fitting
+/// in a block into shared blocks with `sub_block_size`.
+///
+/// The entries of shared blocks are packed in cells of sub-block size, and each shared block is
+/// packed as a rect of one block after the entries and reserved regions.
+///
+/// Each entry is resized alone, so downscale filters never read neighbor entries. The gutter is
+/// at least `block_size >> 1` on every side (right and bottom get the rounding remainder), which
+/// is `(block_size >> 1) >> mip_level` texels at each mip level. This stays at least half a texel
+/// (the bilinear footprint at the texcoord edge) up to mip level `log2(block_size)`, so the mip
+/// chain is capped there by the smaller block side, or by the sub-block size when any entry is
+/// packed into a shared block. It also stops where the page side becomes odd.
+#[inline]
+fn pack_with_block<I: AtlasTexture>(
+    desc: &AtlasDescriptor<'_, I>,
+    extents: &[(u32, u32)],
+    aliases: &[usize],
+    (block_width, block_height): (u32, u32),
+) -> Result<PackedLayout, AtlasError> {
+    let AtlasDescriptor { size, entries, .. } = *desc;
+
+    let block = (block_width, block_height);
+    let bin_size = (size / block_width, size / block_height);
+
+    let mut units = vec![block; extents.len()];
+    let mut rects = Vec::new();
+    let mut sub_rects = Vec::new();
+    for (i, &(width, height)) in extents.iter().enumerate() {
+        if aliases.get(i).is_some_and(|&alias| alias != i) {
+            continue;
+        }
+
+        if width == 0 || height == 0 {
+            return Err(AtlasError::ZeroSizedEntry { index: i });
+        }
+
+        let preserved = desc.preserve.iter().any(|&(index, _)| index == i);
+        let shared = i < entries.len() && entries[i].pin_page.is_none() && !preserved;
+        if let Some(sub) = desc.sub_block_size.filter(|_| shared) {
+            let rect = entry_rect((width, height), 0, (sub, sub)).ok_or(AtlasError::Overflow)?;
+            if rect.width() * sub <= block_width && rect.height() * sub <= block_height {
+                units[i] = (sub, sub);
+                sub_rects.push((i, rect));
+                continue;
+            }
+        }
+
+        let blocks = |extent: u32, block_size: u32| {
+            extent
+                .checked_add(block_size)
+                .map(|extent| extent.div_ceil(block_size))
+        };
+        let rect = rectangle_pack::RectToInsert::new(
+            blocks(width, block_width).ok_or(AtlasError::Overflow)?,
+            blocks(height, block_height).ok_or(AtlasError::Overflow)?,
             1,
         );
-        rects.push_rect(i, None, rect);
+        if rect.width() > bin_size.0 || rect.height() > bin_size.1 {
+            return Err(AtlasError::EntryTooLarge {
+                index: i,
+                width: rect.width() * block_width,
+                height: rect.height() * block_height,
+                max: size,
+            });
+        }
+
+        rects.push((i, rect));
+    }
+
+    desc.report(AtlasProgressStage::Packing, 0, 1);
+    let sub = desc.sub_block_size.unwrap_or(1);
+    let cells = pack_sub_blocks(desc, &sub_rects, (block_width / sub, block_height / sub))?;
+    let mut entry_counts = BTreeMap::new();
+    for &(shared, _) in cells.values() {
+        *entry_counts
+            .entry(extents.len() + shared as usize)
+            .or_insert(0) += 1;
+    }
+    for &i in entry_counts.keys() {
+        rects.push((i, rectangle_pack::RectToInsert::new(1, 1, 1)));
+    }
+
+    let (target_bins, mut locations) = pack_pages(
+        desc,
+        &rects,
+        &entry_counts,
+        bin_size,
+        block,
+        (block_width >> 1, block_height >> 1),
+    )?;
+    desc.report(AtlasProgressStage::Packing, 1, 1);
+
+    let packed_rects = packed_rects(desc, &locations)
+        .into_iter()
+        .filter(|packed_rect| packed_rect.index < extents.len())
+        .collect();
+    for (&i, &(shared, cell)) in &cells {
+        let (page, location) = locations[&(extents.len() + shared as usize)];
+        let x = location.x() * (block_width / sub) + cell.x();
+        let y = location.y() * (block_height / sub) + cell.y();
+        let rect = rectangle_pack::RectToInsert::new(cell.width(), cell.height(), 1);
+        locations.insert(i, (page, packed_location(x, y, rect)));
+    }
+    locations.retain(|&i, _| i < extents.len());
+
+    let mut page_count = desc.min_page_count;
+    let mut texcoords = vec![Texcoord::default(); extents.len()];
+    for (&i, &(page, location)) in &locations {
+        page_count = u32::max(page_count, page + 1);
+
+        let (width, height) = extents[i];
+        let anchor = entries
+            .get(i)
+            .map_or(Default::default(), |entry| entry.anchor);
+        let shift = anchor_shift(anchor, (width, height), location, units[i]);
+        let page = desc.page_base.checked_add(page);
+        let texcoord = page.and_then(|page| {
+            placed_texcoord(page, location, units[i], shift, (width, height), size)
+        });
+        texcoords[i] = texcoord.ok_or(AtlasError::Overflow)?;
+    }
+    #[cfg(feature = "std")]
+    let block_waste = extents[..entries.len()]
+        .iter()
+        .zip(&units)
+        .map(|(&(width, height), &unit)| block_waste(width, height, unit))
+        .collect::<Option<Vec<_>>>()
+        .ok_or(AtlasError::Overflow)?;
+
+    let block_size = block_width.min(block_height);
+    let block_size = if cells.is_empty() { block_size } else { sub };
+    let mip_level_count = u32::min(block_size.ilog2(), size.trailing_zeros()) + 1;
+    let mip_level_count = capped_mip_level_count(desc, mip_level_count);
+
+    Ok(PackedLayout {
+        page_count,
+        mip_level_count,
+        #[cfg(feature = "std")]
+        padding: block_size >> 1,
+        texcoords,
+        #[cfg(feature = "std")]
+        locations,
+        #[cfg(feature = "std")]
+        units,
+        #[cfg(feature = "std")]
+        block_waste,
+        #[cfg(feature = "std")]
+        sub_blocks: cells.keys().map(|&i| (i, sub)).collect(),
+        free_rects: free_rects(desc, &target_bins, page_count, block),
+        packed_rects,
+        #[cfg(feature = "std")]
+        target_bins,
+    })
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn create_atlas_with_padding<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    buffers: &mut Vec<Texture<I::Pixel>>,
+    padding: u32,
+) -> Result<Atlas<I::Pixel>, AtlasError>
+where
+    I: image::GenericImageView,
+    I::Pixel: 'static,
+{
+    let AtlasDescriptor { size, entries, .. } = *desc;
+
+    let views = views(entries);
+    let aliases = aliases(desc.deduplicate, entries, &views, desc.preserve);
+    let (rotations, layout) = pack_views(desc, &views, &aliases)?;
+
+    let mut texcoords = layout.texcoords;
+    resolve_texcoords(&mut texcoords, &aliases, entries, &views, &rotations);
+    let reserved = texcoords.split_off(entries.len());
+
+    let mut textures = allocate_textures(
+        buffers,
+        layout.page_count,
+        size,
+        (layout.mip_level_count, mip_start(desc)),
+        desc.background,
+        layout.padding,
+    );
+    let locations = &layout.locations;
+    let blit_count = locations.keys().filter(|&&i| i < entries.len()).count() as u32;
+    for (done, (&i, &(page, location))) in (1..).zip(locations) {
+        let Some(entry) = entries.get(i) else {
+            continue;
+        };
+
+        let target = &mut textures[page as usize].mip_maps[0];
+        blit_with_padding(
+            target,
+            &*views[i],
+            entry.wraps().0,
+            rotations[i],
+            padding,
+            desc.extrude,
+            location,
+        )?;
+        if desc.bleed_alpha {
+            bleed_alpha(target, location);
+        }
+        desc.report(AtlasProgressStage::Blitting, done, blit_count);
+    }
+
+    Ok(Atlas {
+        page_count: layout.page_count,
+        size,
+        mip_level_count: layout.mip_level_count,
+        mip_start: 0,
+        textures,
+        texcoords,
+        reserved,
+        page_base: desc.page_base,
+        block_waste: layout.block_waste,
+        scale_factors: vec![1.0; entries.len()],
+        alpha_coverage: vec![Vec::new(); entries.len()],
+        free_rects: layout.free_rects,
+        packed_rects: layout.packed_rects,
+        sources: Vec::new(),
+        baked_rotations: vec![None; entries.len()],
+        packer: Some(AtlasPacker::new(desc, layout.target_bins)),
+    })
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn create_atlas_mip_with_padding<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    buffers: &mut Vec<Texture<I::Pixel>>,
+    filter: AtlasMipFilter,
+    padding: u32,
+) -> Result<Atlas<I::Pixel>, AtlasError>
+where
+    I: image::GenericImageView,
+    I::Pixel: 'static,
+{
+    let AtlasDescriptor { size, entries, .. } = *desc;
+
+    let views = views(entries);
+    let aliases = aliases(desc.deduplicate, entries, &views, desc.preserve);
+    let (rotations, layout) = pack_views(desc, &views, &aliases)?;
+
+    let mut texcoords = layout.texcoords;
+    resolve_texcoords(&mut texcoords, &aliases, entries, &views, &rotations);
+    let reserved = texcoords.split_off(entries.len());
+
+    let mut textures = allocate_textures(
+        buffers,
+        layout.page_count,
+        size,
+        (layout.mip_level_count, mip_start(desc)),
+        desc.background,
+        layout.padding,
+    );
+    let locations = &layout.locations;
+    let blit_count = locations.keys().filter(|&&i| i < entries.len()).count() as u32;
+    for (done, (&i, &(page, location))) in (1..).zip(locations) {
+        let Some(entry) = entries.get(i) else {
+            continue;
+        };
+
+        blit_with_padding(
+            &mut textures[page as usize].mip_maps[0],
+            &*views[i],
+            entry.wraps().0,
+            rotations[i],
+            padding,
+            0,
+            location,
+        )?;
+        desc.report(AtlasProgressStage::Blitting, done, blit_count);
+    }
+
+    for (done, texture) in (1..).zip(&mut textures) {
+        generate_mip_maps(
+            texture,
+            filter,
+            desc.mip_color_space,
+            desc.premultiply_alpha,
+            desc.mip_sharpen,
+        );
+        desc.report(AtlasProgressStage::MipGeneration, done, layout.page_count);
+    }
+
+    Ok(Atlas {
+        page_count: layout.page_count,
+        size,
+        mip_level_count: layout.mip_level_count,
+        mip_start: 0,
+        textures,
+        texcoords,
+        reserved,
+        page_base: desc.page_base,
+        block_waste: layout.block_waste,
+        scale_factors: vec![1.0; entries.len()],
+        alpha_coverage: vec![Vec::new(); entries.len()],
+        free_rects: layout.free_rects,
+        packed_rects: layout.packed_rects,
+        sources: Vec::new(),
+        baked_rotations: vec![None; entries.len()],
+        packer: Some(AtlasPacker::new(desc, layout.target_bins)),
+    })
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn create_atlas_mip_with_block<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    buffers: &mut Vec<Texture<I::Pixel>>,
+    filter: AtlasMipFilter,
+) -> Result<Atlas<I::Pixel>, AtlasError>
+where
+    I: image::GenericImageView,
+    I::Pixel: 'static,
+{
+    let AtlasDescriptor { size, entries, .. } = *desc;
+
+    let views = views(entries);
+    let aliases = aliases(desc.deduplicate, entries, &views, desc.preserve);
+    let (rotations, layout) = pack_views(desc, &views, &aliases)?;
+    let extents = extents(&views, &rotations, desc.reserve);
+    let units = &layout.units;
+
+    let mut texcoords = layout.texcoords;
+    resolve_texcoords(&mut texcoords, &aliases, entries, &views, &rotations);
+    let reserved = texcoords.split_off(entries.len());
+
+    let (mip_level_count, mip_start) = (layout.mip_level_count, mip_start(desc));
+    let mut textures = allocate_textures(
+        buffers,
+        layout.page_count,
+        size,
+        (mip_level_count, mip_start),
+        desc.background,
+        layout.padding,
+    );
+    let locations = &layout.locations;
+    let blit_count = locations.keys().filter(|&&i| i < entries.len()).count() as u32;
+    for (done, (&i, &(page, location))) in (1..).zip(locations) {
+        let Some(entry) = entries.get(i) else {
+            continue;
+        };
+
+        let shift = anchor_shift(entry.anchor, extents[i], location, units[i]);
+        let src = resample(
+            &*views[i],
+            entry.mip,
+            rotations[i],
+            (0, 0),
+            extents[i],
+            extents[i],
+            AtlasSampling::Nearest,
+        )?;
+        desc.report(AtlasProgressStage::Blitting, done, blit_count);
+
+        blit_with_block(
+            &mut textures[page as usize],
+            &src,
+            entry.wraps(),
+            block_cell(location, units[i]),
+            shift,
+            (entry.generate_mips).then(|| entry.mip_filter.unwrap_or(filter)),
+            (
+                desc.mip_color_space,
+                desc.premultiply_alpha,
+                desc.mip_sharpen,
+            ),
+        )?;
+        desc.report(AtlasProgressStage::MipGeneration, done, blit_count);
+    }
+
+    let alpha_coverage = entries
+        .iter()
+        .zip(&texcoords)
+        .map(|(entry, texcoord)| {
+            let texture = &textures[(texcoord.page - desc.page_base) as usize];
+            let mip_level_count = if entry.generate_mips {
+                mip_level_count
+            } else {
+                1
+            };
+            alpha_coverage(texture, texcoord, mip_start..mip_level_count.max(mip_start))
+        })
+        .collect();
+
+    Ok(Atlas {
+        page_count: layout.page_count,
+        size,
+        mip_level_count,
+        mip_start: 0,
+        textures,
+        texcoords,
+        reserved,
+        page_base: desc.page_base,
+        block_waste: layout.block_waste,
+        scale_factors: vec![1.0; entries.len()],
+        alpha_coverage,
+        free_rects: layout.free_rects,
+        packed_rects: layout.packed_rects,
+        sources: Vec::new(),
+        baked_rotations: vec![None; entries.len()],
+        packer: Some(AtlasPacker {
+            sub_blocks: layout.sub_blocks,
+            ..AtlasPacker::new(desc, layout.target_bins)
+        }),
+    })
+}
+
+/// Returns an offset of entry from the top-left of its block-rounded location by the anchor.
+#[inline]
+fn anchor_shift(
+    anchor: AtlasEntryAnchor,
+    (width, height): (u32, u32),
+    location: rectangle_pack::PackedLocation,
+    (block_width, block_height): (u32, u32),
+) -> (u32, u32) {
+    match anchor {
+        AtlasEntryAnchor::TopLeft => (block_width >> 1, block_height >> 1),
+        AtlasEntryAnchor::Center => (
+            (location.width() * block_width - width) / 2,
+            (location.height() * block_height - height) / 2,
+        ),
+    }
+}
+
+/// Packed locations of rects as (bin index, location) keyed by rect id.
+type PackedLocations = BTreeMap<usize, (u32, rectangle_pack::PackedLocation)>;
+
+/// An owned texture of the pixel type.
+#[cfg(feature = "std")]
+type OwnedTexture<P> = image::ImageBuffer<P, Vec<<P as image::Pixel>::Subpixel>>;
+
+/// Packs rects into target bins of the pages, and returns the bins with the locations.
+///
+/// With `max_page_count` of zero, the page count is unbounded. Packing starts from the pages
+/// covering the total area of rects, and is retried from fresh bins with twice the pages until
+/// every rect fits. Rects are placed into the first bin with room, so extra empty bins change
+/// neither the layout nor the page count. Each rect fits alone in a page, so enough pages are
+/// reached once every rect can have an empty page besides the occupied ones. There are at least
+/// `min_page_count` bins, so the padded pages have bins as well, and every pinned and preserved
+/// page has a bin from the first try.
+///
+/// With `max_entries_per_page`, each rect counts as one entry, except the rects listed in
+/// `entry_counts` (e.g. shared blocks), and distinct `occupied` texcoords count on their pages.
+///
+/// The rects of entries in `preserve` are not packed but placed back at their previous texcoords,
+/// and their locations are marked in every bin before the other rects are packed.
+#[inline]
+fn pack_pages<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    rects: &[(usize, rectangle_pack::RectToInsert)],
+    entry_counts: &BTreeMap<usize, u32>,
+    bin_size: (u32, u32),
+    unit: (u32, u32),
+    border: (u32, u32),
+) -> Result<(BTreeMap<u32, rectangle_pack::TargetBin>, PackedLocations), AtlasError>
+where
+    I: AtlasTexture,
+{
+    let groups = desc
+        .entries
+        .iter()
+        .map(|entry| entry.group)
+        .collect::<Vec<_>>();
+    let fixed = preserved_locations(desc, rects, bin_size, unit, border)?;
+    let rects = &rects
+        .iter()
+        .filter(|(i, _)| !fixed.contains_key(i))
+        .copied()
+        .collect::<Vec<_>>();
+    let mut occupied = desc.occupied.to_vec();
+    occupied.sort_unstable();
+    occupied.dedup();
+    let occupied = occupied
+        .iter()
+        .filter_map(|texcoord| Some((texcoord.page.checked_sub(desc.page_base)?, 1)));
+    let preserved = fixed
+        .iter()
+        .map(|(i, &(page, _))| (page, entry_counts.get(i).copied().unwrap_or(1)));
+    let mut filled = Vec::new();
+    for (page, count) in occupied.chain(preserved) {
+        if filled.len() <= page as usize {
+            filled.resize(page as usize + 1, 0);
+        }
+        filled[page as usize] += count;
+    }
+    let pin_page = |i: usize| desc.entries.get(i).and_then(|entry| entry.pin_page);
+    let (pinned, unpinned) = rects
+        .iter()
+        .partition::<Vec<_>, _>(|&&(i, _)| pin_page(i).is_some());
+    let pack = |page_count| -> Result<_, AtlasError> {
+        let mut target_bins = target_bins(desc, page_count, bin_size, unit, border, &fixed);
+        let (mut locations, filled) = pack_pinned(
+            desc,
+            &pinned,
+            &mut target_bins,
+            bin_size,
+            entry_counts,
+            &filled,
+        )?;
+        locations.extend(&fixed);
+        locations.extend(match desc.max_entries_per_page {
+            Some(cap) => pack_capped(
+                desc,
+                &unpinned,
+                &mut target_bins,
+                bin_size,
+                entry_counts,
+                (cap, &filled),
+            )?,
+            None => pack_rects(
+                &unpinned,
+                &groups,
+                &mut target_bins,
+                bin_size,
+                desc.pack_heuristic,
+                desc.pack_algorithm,
+                desc.stable,
+            )?,
+        });
+        Ok((target_bins, locations))
+    };
+
+    let (bins, locations) = if desc.max_page_count != 0 {
+        pack(desc.max_page_count)?
+    } else {
+        let area = rects
+            .iter()
+            .map(|(_, rect)| rect.width() as u64 * rect.height() as u64)
+            .sum::<u64>();
+        let bin_area = bin_size.0 as u64 * bin_size.1 as u64;
+        let enough = rects
+            .len()
+            .saturating_add(desc.occupied.len())
+            .saturating_add(fixed.len());
+        let mut page_count = u32::try_from(area.div_ceil(bin_area)).unwrap_or(u32::MAX);
+        if let Some(cap) = desc.max_entries_per_page {
+            let count = rects
+                .iter()
+                .map(|(i, _)| entry_counts.get(i).copied().unwrap_or(1) as u64)
+                .sum::<u64>();
+            let pages = u32::try_from(count.div_ceil(cap as u64)).unwrap_or(u32::MAX);
+            page_count = page_count.max(pages);
+        }
+        let pinned_pages = pinned
+            .iter()
+            .filter_map(|&(i, _)| pin_page(i)?.checked_sub(desc.page_base)?.checked_add(1))
+            .max();
+        let preserved_pages = fixed
+            .values()
+            .map(|&(page, _)| page.saturating_add(1))
+            .max();
+        page_count = page_count.max(pinned_pages.unwrap_or(0));
+        page_count = page_count.max(preserved_pages.unwrap_or(0));
+        loop {
+            match pack(page_count.max(desc.min_page_count).max(1)) {
+                Err(AtlasError::Packing(_)) if (page_count as usize) < enough => {
+                    page_count = page_count.saturating_mul(2)
+                }
+                result => break result?,
+            }
+        }
+    };
+
+    let capped = desc.max_entries_per_page.is_some();
+    let grouped = groups.iter().any(Option::is_some);
+    if desc.balance_pages && !capped && !grouped && pinned.is_empty() && fixed.is_empty() {
+        let page_count = bins.len() as u32;
+        let balanced = balance_bins(
+            rects,
+            target_bins(desc, page_count, bin_size, unit, border, &fixed),
+            &locations,
+            bin_size,
+            (desc.pack_heuristic, desc.pack_algorithm),
+        );
+        if let Some(balanced) = balanced {
+            return Ok(balanced);
+        }
+    }
+    Ok((bins, locations))
+}
+
+/// Returns the locations of the rects of entries in `preserve` placed back at their previous
+/// texcoords, in cells of `unit` pixels.
+///
+/// The texcoord is shifted from its location by `border` (or by the anchor within blocks), as the
+/// generation places it, so the location is recovered from the texcoord. A rect widened by
+/// `origin_alignment` is wider and taller than the padded entry by the alignment slack, and it is
+/// placed so that its aligned position is the texcoord. A region out of the
+/// bins of the pages, or overlapping another preserved one, fails with `PreservedMismatch`, and
+/// the texcoord generated from the location is checked against the previous one after
+/// generation.
+#[inline]
+fn preserved_locations<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    rects: &[(usize, rectangle_pack::RectToInsert)],
+    (bin_width, bin_height): (u32, u32),
+    unit: (u32, u32),
+    (border_x, border_y): (u32, u32),
+) -> Result<PackedLocations, AtlasError>
+where
+    I: AtlasTexture,
+{
+    let previous = desc.preserve.iter().copied().collect::<BTreeMap<_, _>>();
+    let mut locations = BTreeMap::new();
+    let mut regions = Vec::new();
+    for &(i, rect) in rects {
+        let Some(texcoord) = previous.get(&i) else {
+            continue;
+        };
+        let mismatch = || AtlasError::PreservedMismatch { index: i };
+
+        let width = texcoord
+            .max_x
+            .checked_sub(texcoord.min_x)
+            .ok_or_else(mismatch)?;
+        let height = texcoord
+            .max_y
+            .checked_sub(texcoord.min_y)
+            .ok_or_else(mismatch)?;
+        let (x, y, region) = if unit == (1, 1) {
+            let slack =
+                |cells: u32, extent: u32, border: u32| cells.checked_sub(padded(extent, border)?);
+            let slack_x = slack(rect.width(), width, border_x).ok_or_else(mismatch)?;
+            let slack_y = slack(rect.height(), height, border_y).ok_or_else(mismatch)?;
+            let min_x = texcoord.min_x.checked_sub(border_x).ok_or_else(mismatch)?;
+            let min_y = texcoord.min_y.checked_sub(border_y).ok_or_else(mismatch)?;
+            let max_x = texcoord.max_x.checked_add(border_x).ok_or_else(mismatch)?;
+            let max_y = texcoord.max_y.checked_add(border_y).ok_or_else(mismatch)?;
+            let x = min_x.saturating_sub(slack_x);
+            let y = min_y.saturating_sub(slack_y);
+            (x, y, (min_x, min_y, max_x, max_y))
+        } else {
+            let anchor = desc
+                .entries
+                .get(i)
+                .map_or(Default::default(), |entry| entry.anchor);
+            let fits = |extent: u32, cells: u32, unit: u32| {
+                cells.checked_mul(unit).is_some_and(|cells| extent <= cells)
+            };
+            if !fits(width, rect.width(), unit.0) || !fits(height, rect.height(), unit.1) {
+                return Err(mismatch());
+            }
+            let location = packed_location(0, 0, rect);
+            let (shift_x, shift_y) = anchor_shift(anchor, (width, height), location, unit);
+            let x = texcoord.min_x.checked_sub(shift_x).ok_or_else(mismatch)? / unit.0;
+            let y = texcoord.min_y.checked_sub(shift_y).ok_or_else(mismatch)? / unit.1;
+            let max_x = x.checked_add(rect.width()).ok_or_else(mismatch)?;
+            let max_y = y.checked_add(rect.height()).ok_or_else(mismatch)?;
+            (x, y, (x, y, max_x, max_y))
+        };
+        let page = texcoord
+            .page
+            .checked_sub(desc.page_base)
+            .filter(|&page| desc.max_page_count == 0 || page < desc.max_page_count)
+            .ok_or_else(mismatch)?;
+
+        let (min_x, min_y, max_x, max_y) = region;
+        if max_x > bin_width || max_y > bin_height {
+            return Err(mismatch());
+        }
+        for &(other_page, (other_min_x, other_min_y, other_max_x, other_max_y)) in &regions {
+            let overlaps_x = min_x < other_max_x && other_min_x < max_x;
+            let overlaps_y = min_y < other_max_y && other_min_y < max_y;
+            if other_page == page && overlaps_x && overlaps_y {
+                return Err(mismatch());
+            }
+        }
+        regions.push((page, region));
+        locations.insert(i, (page, packed_location(x, y, rect)));
+    }
+    Ok(locations)
+}
+
+/// Packs the rects of entries with `pin_page` into the bins of their pages, before any other rect
+/// is packed, and returns their locations with the entry counts of the bins.
+///
+/// The entry counts start from `filled` and add the pinned rects (counted as in
+/// [pack_capped](pack_capped)), so the other rects are packed around them within
+/// `max_entries_per_page`. The pinned rects of a page which is out of the bins, or which they do
+/// not fit in, fail with `PinnedPageOverflow`.
+#[inline]
+fn pack_pinned<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    rects: &[(usize, rectangle_pack::RectToInsert)],
+    target_bins: &mut BTreeMap<u32, rectangle_pack::TargetBin>,
+    bin_size: (u32, u32),
+    entry_counts: &BTreeMap<usize, u32>,
+    filled: &[u32],
+) -> Result<(PackedLocations, Vec<u32>), AtlasError>
+where
+    I: AtlasTexture,
+{
+    let mut pages = BTreeMap::<u32, Vec<_>>::new();
+    for &(i, rect) in rects {
+        if let Some(page) = desc.entries.get(i).and_then(|entry| entry.pin_page) {
+            pages.entry(page).or_default().push((i, rect));
+        }
+    }
+
+    let mut filled = filled.to_vec();
+    let mut locations = BTreeMap::new();
+    for (page, rects) in pages {
+        let overflow = AtlasError::PinnedPageOverflow { page };
+        let (bin_page, bin) = page
+            .checked_sub(desc.page_base)
+            .and_then(|bin_page| Some((bin_page, target_bins.remove(&bin_page)?)))
+            .ok_or(overflow)?;
+        let mut bins = BTreeMap::from([(bin_page, bin)]);
+        let placed = pack_rects(
+            &rects,
+            &[],
+            &mut bins,
+            bin_size,
+            desc.pack_heuristic,
+            desc.pack_algorithm,
+            desc.stable,
+        );
+        target_bins.extend(bins);
+        locations.extend(placed.map_err(|_| AtlasError::PinnedPageOverflow { page })?);
+
+        if let Some(cap) = desc.max_entries_per_page {
+            if filled.len() <= bin_page as usize {
+                filled.resize(bin_page as usize + 1, 0);
+            }
+            let count = &mut filled[bin_page as usize];
+            for &(i, _) in &rects {
+                *count = count.saturating_add(entry_counts.get(&i).copied().unwrap_or(1));
+            }
+            if *count > cap {
+                return Err(AtlasError::PinnedPageOverflow { page });
+            }
+        }
+    }
+    Ok((locations, filled))
+}
+
+/// Packs rects one by one into the bins in order, moving to the next bin once the entries in the
+/// current one reach `cap`, and returns locations keyed by rect id.
+///
+/// Rects are tried from the largest one (then by id), and a rect which does not fit spatially or
+/// would exceed the cap is left for the next bin. A rect counts as its `entry_counts` (1 if not
+/// listed), and a bin starts from its `filled` count (0 if out of range). Groups are ignored.
+#[inline]
+fn pack_capped<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    rects: &[(usize, rectangle_pack::RectToInsert)],
+    target_bins: &mut BTreeMap<u32, rectangle_pack::TargetBin>,
+    bin_size: (u32, u32),
+    entry_counts: &BTreeMap<usize, u32>,
+    (cap, filled): (u32, &[u32]),
+) -> Result<PackedLocations, AtlasError>
+where
+    I: AtlasTexture,
+{
+    let area = |rect: &rectangle_pack::RectToInsert| rect.width() as u64 * rect.height() as u64;
+    let mut order = rects.iter().collect::<Vec<_>>();
+    order.sort_by_key(|&(i, rect)| (core::cmp::Reverse(area(rect)), *i));
+
+    let mut locations = BTreeMap::new();
+    let pages = target_bins.keys().copied().collect::<Vec<_>>();
+    for page in pages {
+        let Some(bin) = target_bins.remove(&page) else {
+            continue;
+        };
+        let mut bins = BTreeMap::from([(page, bin)]);
+        let mut count = filled.get(page as usize).copied().unwrap_or(0);
+        order.retain(|&&(i, rect)| {
+            let entry_count = entry_counts.get(&i).copied().unwrap_or(1);
+            if count.saturating_add(entry_count) > cap {
+                return true;
+            }
+            let result = pack_rects(
+                &[(i, rect)],
+                &[],
+                &mut bins,
+                bin_size,
+                desc.pack_heuristic,
+                desc.pack_algorithm,
+                false,
+            );
+            match result {
+                Ok(placed) => {
+                    count += entry_count;
+                    locations.extend(placed);
+                    false
+                }
+                Err(_) => true,
+            }
+        });
+        target_bins.extend(bins);
+    }
+
+    if !order.is_empty() {
+        return Err(rectangle_pack::RectanglePackError::NotEnoughBinSpace.into());
+    }
+    Ok(locations)
+}
+
+/// Packs rects in sub-block units into as few shared blocks of `bin_size` as possible.
+///
+/// Returns the locations keyed by rect id with the shared block index (counted from 0 without
+/// gaps) in place of the page.
+///
+/// A shared block holds at most `max_entries_per_page` entries, so it fits in a page. Each rect
+/// fits in a block alone, so the blocks are doubled until one block per rect at most.
+#[inline]
+fn pack_sub_blocks<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    rects: &[(usize, rectangle_pack::RectToInsert)],
+    bin_size: (u32, u32),
+) -> Result<PackedLocations, AtlasError>
+where
+    I: AtlasTexture,
+{
+    if rects.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    let area = rects
+        .iter()
+        .map(|(_, rect)| rect.width() as u64 * rect.height() as u64)
+        .sum::<u64>();
+    let bin_area = bin_size.0 as u64 * bin_size.1 as u64;
+    let mut bin_count = u32::try_from(area.div_ceil(bin_area)).unwrap_or(u32::MAX);
+    let locations = loop {
+        let mut bins = (0..bin_count)
+            .map(|b| (b, rectangle_pack::TargetBin::new(bin_size.0, bin_size.1, 1)))
+            .collect();
+        let result = match desc.max_entries_per_page {
+            Some(cap) => pack_capped(
+                desc,
+                rects,
+                &mut bins,
+                bin_size,
+                &BTreeMap::new(),
+                (cap, &[]),
+            ),
+            None => pack_rects(
+                rects,
+                &[],
+                &mut bins,
+                bin_size,
+                desc.pack_heuristic,
+                desc.pack_algorithm,
+                desc.stable,
+            )
+            .map_err(AtlasError::from),
+        };
+        match result {
+            Err(_) if (bin_count as usize) < rects.len() => bin_count = bin_count.saturating_mul(2),
+            result => break result?,
+        }
+    };
+
+    let mut used = locations.values().map(|&(b, _)| b).collect::<Vec<_>>();
+    used.sort_unstable();
+    used.dedup();
+    Ok(locations
+        .into_iter()
+        .map(|(i, (b, location))| (i, (used.partition_point(|&used| used < b) as u32, location)))
+        .collect())
+}
+
+/// Packs rects one by one into fresh target bins, from the largest rect, each into the bin with
+/// the least area of rects that has room among the bins used by `locations`.
+///
+/// Returns the bins with the locations, or `None` when a rect fits in none of the bins.
+#[inline]
+fn balance_bins(
+    rects: &[(usize, rectangle_pack::RectToInsert)],
+    mut target_bins: BTreeMap<u32, rectangle_pack::TargetBin>,
+    locations: &PackedLocations,
+    bin_size: (u32, u32),
+    (pack_heuristic, pack_algorithm): (PackHeuristic, PackAlgorithm),
+) -> Option<(BTreeMap<u32, rectangle_pack::TargetBin>, PackedLocations)> {
+    let area = |rect: &rectangle_pack::RectToInsert| rect.width() as u64 * rect.height() as u64;
+
+    let page_count = locations.values().map(|&(page, _)| page + 1).max()?;
+    let mut areas = vec![0; page_count as usize];
+    let mut order = rects.iter().collect::<Vec<_>>();
+    order.sort_by_key(|&(i, rect)| (core::cmp::Reverse(area(rect)), *i));
+
+    let mut balanced = BTreeMap::new();
+    for &(i, rect) in order {
+        let mut pages = (0..page_count).collect::<Vec<_>>();
+        pages.sort_by_key(|&page| (areas[page as usize], page));
+        let (page, location) = pages.into_iter().find_map(|page| {
+            let mut bins = BTreeMap::from([(page, target_bins.remove(&page)?)]);
+            let result = pack_rects(
+                &[(i, rect)],
+                &[],
+                &mut bins,
+                bin_size,
+                pack_heuristic,
+                pack_algorithm,
+                false,
+            );
+            target_bins.extend(bins);
+            result.ok().map(|locations| locations[&i])
+        })?;
+        areas[page as usize] += area(&rect);
+        balanced.insert(i, (page, location));
+    }
+    Some((target_bins, balanced))
+}
+
+/// Returns target bins of the pages where `occupied` regions of the description and the `fixed`
+/// locations are marked.
+///
+/// Each occupied region is expanded by `border` and rounded out to `unit` (block size or 1) per
+/// axis, and the free space of its bin is split into disjoint sections along the edges of the
+/// regions.
+#[inline]
+fn target_bins<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    page_count: u32,
+    (bin_width, bin_height): (u32, u32),
+    (unit_x, unit_y): (u32, u32),
+    (border_x, border_y): (u32, u32),
+    fixed: &PackedLocations,
+) -> BTreeMap<u32, rectangle_pack::TargetBin>
+where
+    I: AtlasTexture,
+{
+    let mut target_bins = BTreeMap::new();
+    for i in 0..page_count {
+        let mut target_bin = rectangle_pack::TargetBin::new(bin_width, bin_height, 1);
+
+        let page = desc.page_base.checked_add(i);
+        let mut regions = occupied_regions(
+            desc.occupied,
+            page,
+            (bin_width, bin_height),
+            (unit_x, unit_y),
+            (border_x, border_y),
+        );
+        regions.extend(
+            fixed
+                .values()
+                .filter(|&&(bin_page, _)| bin_page == i)
+                .map(|(_, location)| {
+                    let max_x = location.x().saturating_add(location.width());
+                    let max_y = location.y().saturating_add(location.height());
+                    (
+                        location.x(),
+                        location.y(),
+                        max_x.min(bin_width),
+                        max_y.min(bin_height),
+                    )
+                })
+                .filter(|&(min_x, min_y, max_x, max_y)| min_x < max_x && min_y < max_y),
+        );
+
+        if !regions.is_empty() {
+            mark_regions(&mut target_bin, (bin_width, bin_height), &regions);
+        }
+
+        target_bins.insert(i, target_bin);
+    }
+    target_bins
+}
+
+/// Returns the regions (min_x, min_y, max_x, max_y) of the bin of the page covered by `occupied`
+/// texcoords, in cells of `unit`.
+///
+/// Each texcoord is expanded by `border` and rounded out to `unit` per axis, and clipped to the
+/// bin.
+#[inline]
+fn occupied_regions(
+    occupied: &[Texcoord],
+    page: Option<u32>,
+    (bin_width, bin_height): (u32, u32),
+    (unit_x, unit_y): (u32, u32),
+    (border_x, border_y): (u32, u32),
+) -> Vec<(u32, u32, u32, u32)> {
+    occupied
+        .iter()
+        .filter(|texcoord| Some(texcoord.page) == page)
+        .map(|texcoord| {
+            let min_x = texcoord.min_x.saturating_sub(border_x) / unit_x;
+            let min_y = texcoord.min_y.saturating_sub(border_y) / unit_y;
+            let max_x = texcoord.max_x.saturating_add(border_x).div_ceil(unit_x);
+            let max_y = texcoord.max_y.saturating_add(border_y).div_ceil(unit_y);
+            (min_x, min_y, max_x.min(bin_width), max_y.min(bin_height))
+        })
+        .filter(|&(min_x, min_y, max_x, max_y)| min_x < max_x && min_y < max_y)
+        .collect()
+}
+
+/// Replaces the sections of a target bin by the free space except the regions.
+#[inline]
+fn mark_regions(
+    target_bin: &mut rectangle_pack::TargetBin,
+    bin_size: (u32, u32),
+    regions: &[(u32, u32, u32, u32)],
+) {
+    while !target_bin.available_bin_sections().is_empty() {
+        target_bin.remove_filled_section(0);
+    }
+    for (x, y, width, height) in free_sections(bin_size, regions) {
+        let section = bin_section(x, y, width, height);
+        target_bin.push_available_bin_section_unchecked(section);
+    }
+}
+
+/// Returns disjoint rects (x, y, width, height) covering the bin of the size except the regions.
+///
+/// The bin is swept from top to bottom over the horizontal edges of the regions. Between two
+/// edges, the free runs are the gaps of the x intervals of the active regions, and a run of the
+/// same span as in the band above extends its rect downward. Each band costs `O(n log n)` for `n`
+/// regions, so the sweep is `O(n^2 log n)`, bounded by the count of rects it can return.
+#[inline]
+fn free_sections(
+    (width, height): (u32, u32),
+    regions: &[(u32, u32, u32, u32)],
+) -> Vec<(u32, u32, u32, u32)> {
+    let mut edges = regions
+        .iter()
+        .flat_map(|&(min_x, min_y, max_x, max_y)| {
+            [
+                (min_y, true, (min_x, max_x)),
+                (max_y, false, (min_x, max_x)),
+            ]
+        })
+        .collect::<Vec<_>>();
+    edges.sort_unstable();
+
+    let mut sections = Vec::new();
+    let mut active = BTreeMap::<(u32, u32), usize>::new();
+    let mut open = BTreeMap::<(u32, u32), u32>::new();
+    let mut edges = edges.into_iter().peekable();
+    let mut y0 = 0;
+    while y0 < height {
+        while let Some((_, enter, span)) = edges.next_if(|&(y, _, _)| y <= y0) {
+            let count = active.entry(span).or_default();
+            match enter {
+                true => *count += 1,
+                false => {
+                    *count -= 1;
+                    if *count == 0 {
+                        active.remove(&span);
+                    }
+                }
+            }
+        }
+        let y1 = edges.peek().map_or(height, |&(y, _, _)| y.min(height));
+
+        let mut runs = Vec::new();
+        let mut cursor = 0;
+        for &(min_x, max_x) in active.keys() {
+            if cursor < min_x {
+                runs.push((cursor, min_x));
+            }
+            cursor = cursor.max(max_x);
+        }
+        if cursor < width {
+            runs.push((cursor, width));
+        }
+
+        let mut next = BTreeMap::new();
+        for span in runs {
+            next.insert(span, open.remove(&span).unwrap_or(y0));
+        }
+        for ((x0, x1), y) in core::mem::replace(&mut open, next) {
+            sections.push((x0, y, x1 - x0, y0 - y));
+        }
+        y0 = y1;
+    }
+    for ((x0, x1), y) in open {
+        sections.push((x0, y, x1 - x0, height - y));
+    }
+    sections
+}
+
+/// Returns a bin section of the extent at the position.
+///
+/// `WidthHeightDepth` is not exported by `rectangle_pack`, so the extent is taken from the
+/// sections split off by placing an empty rect into a bin of the extent. Every split section with
+/// non-zero volume spans the whole extent.
+#[inline]
+fn bin_section(x: u32, y: u32, width: u32, height: u32) -> rectangle_pack::BinSection {
+    let target_bin = rectangle_pack::TargetBin::new(width, height, 1);
+    split_sections(
+        &target_bin.available_bin_sections()[0],
+        &rectangle_pack::RectToInsert::new(0, 0, 0),
+        (x, y),
+    )
+    .into_iter()
+    .find_map(|(volume, section)| (volume > 0).then_some(section))
+    .expect("split sections have the extent of the bin")
+}
+
+/// Returns each section which placing `incoming` at the origin of `section` may split off, with
+/// its volume, moved to the position.
+///
+/// The split sections are only passed to the comparator of `try_place`, which must be `'static`,
+/// so they are collected through a shared cell moved into it. This is the only way to read the
+/// extent of a section, as the fields of `BinSection` are private. Nothing is split off when
+/// `incoming` does not fit.
+#[inline]
+fn split_sections(
+    section: &rectangle_pack::BinSection,
+    incoming: &rectangle_pack::RectToInsert,
+    (x, y): (u32, u32),
+) -> Vec<(u128, rectangle_pack::BinSection)> {
+    let splits = Rc::new(RefCell::new(Vec::new()));
+    let _ = section.try_place(
+        incoming,
+        &{
+            let splits = Rc::clone(&splits);
+            move |container1, container2, heuristic| {
+                for whd in container1.into_iter().chain(container2) {
+                    let split = rectangle_pack::BinSection::new(x, y, 0, whd);
+                    splits.borrow_mut().push((heuristic(whd), split));
+                }
+                core::cmp::Ordering::Equal
+            }
+        },
+        &rectangle_pack::volume_heuristic,
+    );
+    splits.take()
+}
+
+/// Returns the free sections of used pages as texcoords when requested by the description.
+///
+/// A section of `unit` (block size or 1 per axis) is scaled to pixels.
+#[inline]
+fn free_rects<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    target_bins: &BTreeMap<u32, rectangle_pack::TargetBin>,
+    page_count: u32,
+    (unit_x, unit_y): (u32, u32),
+) -> Vec<Vec<Texcoord>>
+where
+    I: AtlasTexture,
+{
+    if !desc.collect_free_rects {
+        return Vec::new();
+    }
+
+    (0..page_count)
+        .map(|page| {
+            let sections = target_bins[&page].available_bin_sections();
+            let mut free_rects = sections
+                .iter()
+                .map(|section| {
+                    let (x, y, width, height) = section_rect(section);
+                    Texcoord {
+                        page: desc.page_base + page,
+                        min_x: x * unit_x,
+                        min_y: y * unit_y,
+                        max_x: (x + width) * unit_x,
+                        max_y: (y + height) * unit_y,
+                        size: desc.size,
+                        ..Default::default()
+                    }
+                })
+                .collect::<Vec<_>>();
+            free_rects.sort_by_key(|texcoord| (texcoord.min_y, texcoord.min_x));
+            free_rects
+        })
+        .collect()
+}
+
+/// Returns the placements of the packer in packer units, ordered by index.
+#[inline]
+fn packed_rects<I>(desc: &AtlasDescriptor<'_, I>, locations: &PackedLocations) -> Vec<PackedRect>
+where
+    I: AtlasTexture,
+{
+    if !desc.collect_packed_rects {
+        return Vec::new();
+    }
+
+    locations
+        .iter()
+        .map(|(&index, &(page, location))| PackedRect {
+            index,
+            page: desc.page_base + page,
+            x: location.x(),
+            y: location.y(),
+            w: location.width(),
+            h: location.height(),
+        })
+        .collect()
+}
+
+/// Returns the position and extent (x, y, width, height) of a bin section.
+///
+/// The fields of `BinSection` are private, so the extent is read from the sections split off by
+/// [split_sections](split_sections) as in [bin_section](bin_section). A section of a 2D bin has
+/// depth 1, and placing a rect of width 0 and height 1 splits off sections whose smallest non-zero
+/// volume is the section width (the space directly right of the rect), and likewise for the
+/// height.
+#[inline]
+fn section_rect(section: &rectangle_pack::BinSection) -> (u32, u32, u32, u32) {
+    let (location, _) = section
+        .try_place(
+            &rectangle_pack::RectToInsert::new(0, 0, 0),
+            &rectangle_pack::contains_smallest_box,
+            &rectangle_pack::volume_heuristic,
+        )
+        .expect("empty rect fits any section");
+    let extent = |width, height| {
+        let incoming = rectangle_pack::RectToInsert::new(width, height, 1);
+        split_sections(section, &incoming, (0, 0))
+            .into_iter()
+            .map(|(volume, _)| volume)
+            .filter(|&volume| volume > 0)
+            .min()
+            .map_or(0, |volume| volume as u32)
+    };
+    (location.x(), location.y(), extent(0, 1), extent(1, 0))
+}
+
+/// Returns pixels added by rounding up an entry with half-block gutter to block multiples.
+#[cfg(feature = "std")]
+#[inline]
+fn block_waste(width: u32, height: u32, (block_width, block_height): (u32, u32)) -> Option<u32> {
+    let (block_width, block_height) = (block_width as u64, block_height as u64);
+    let (width, height) = (width as u64 + block_width, height as u64 + block_height);
+    let waste = width.next_multiple_of(block_width) * height.next_multiple_of(block_height)
+        - width * height;
+    u32::try_from(waste).ok()
+}
+
+/// Returns the mean alpha of the texcoord region at each mip level of `mip_levels`.
+///
+/// The region is scaled down at each mip level, keeping at least one pixel. Pixels without alpha
+/// channel are opaque.
+#[cfg(feature = "std")]
+#[inline]
+fn alpha_coverage<P>(texture: &Texture<P>, texcoord: &Texcoord, mip_levels: Range<u32>) -> Vec<f32>
+where
+    P: image::Pixel,
+{
+    if !P::HAS_ALPHA {
+        return vec![1.0; mip_levels.len()];
+    }
+
+    let alpha_index = P::CHANNEL_COUNT as usize - 1;
+    mip_levels
+        .map(|mip_level| (mip_level, &texture.mip_maps[mip_level as usize]))
+        .map(|(mip_level, mip_map)| {
+            let min_x = (texcoord.min_x >> mip_level).min(mip_map.width() - 1);
+            let min_y = (texcoord.min_y >> mip_level).min(mip_map.height() - 1);
+            let max_x = (texcoord.max_x >> mip_level).clamp(min_x + 1, mip_map.width());
+            let max_y = (texcoord.max_y >> mip_level).clamp(min_y + 1, mip_map.height());
+
+            let mut sum = 0.0;
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    sum += to_unit(mip_map.get_pixel(x, y).channels()[alpha_index]);
+                }
+            }
+            sum / ((max_x - min_x) * (max_y - min_y)) as f32
+        })
+        .collect()
+}
+
+/// Returns an extent with padding on both sides, or `None` on overflow.
+#[inline]
+fn padded(extent: u32, padding: u32) -> Option<u32> {
+    extent.checked_add(padding.checked_mul(2)?)
+}
+
+/// Returns the padding (including extruded border) or half-block gutter of a mip option, and the
+/// block width and height (1 without block).
+#[inline]
+#[rustfmt::skip]
+fn mip_layout(mip: AtlasMipOption, extrude: u32) -> (u32, (u32, u32)) {
+    match mip {
+        AtlasMipOption::NoMip => (extrude, (1, 1)),
+        AtlasMipOption::NoMipWithPadding(padding) => (padding.saturating_add(extrude), (1, 1)),
+        AtlasMipOption::Mip(_) => (0, (1, 1)),
+        AtlasMipOption::MipWithPadding(_, padding) => (padding, (1, 1)),
+        AtlasMipOption::MipWithBlock(_, block_size) => (block_size >> 1, (block_size, block_size)),
+        AtlasMipOption::MipWithBlockRect(_, block_width, block_height) => {
+            (block_width.min(block_height) >> 1, (block_width, block_height))
+        }
+    }
+}
+
+/// Returns texels beyond an entry edge read by the filter when the page is downscaled, in texels
+/// of the destination mip level.
+///
+/// The support of the filter of `image` is 0, 1, 2 and 3 texels, and a texel at the edge is
+/// centered half a texel inside, so the reach is the support less half a texel rounded up.
+#[inline]
+fn filter_reach(filter: AtlasMipFilter) -> u32 {
+    match filter {
+        AtlasMipFilter::Nearest => 0,
+        AtlasMipFilter::Linear => 1,
+        AtlasMipFilter::Cubic => 2,
+        AtlasMipFilter::Gaussian | AtlasMipFilter::Lanczos3 => 3,
+    }
+}
+
+/// Returns a rect of an entry extent with the padding (or half-block gutter) rounded up to block
+/// multiples, or `None` on overflow.
+///
+/// The gutter of a block is half of the block on each axis, which is `border` only when square,
+/// so blocks are rounded with their own gutter.
+#[inline]
+fn entry_rect(
+    (width, height): (u32, u32),
+    border: u32,
+    block: (u32, u32),
+) -> Option<rectangle_pack::RectToInsert> {
+    let is_block = block != (1, 1);
+    let blocks = |extent: u32, block_size: u32| {
+        let gutter = if is_block { block_size >> 1 } else { border };
+        padded(extent, gutter).map(|extent| extent.div_ceil(block_size))
+    };
+    Some(rectangle_pack::RectToInsert::new(
+        blocks(width, block.0)?,
+        blocks(height, block.1)?,
+        1,
+    ))
+}
+
+/// Returns the locations moved within their rects so that the positions after `border` are
+/// multiples of `alignment`, shrinking the rects by the `alignment - 1` pixels they are widened by.
+#[inline]
+fn aligned_locations(locations: PackedLocations, border: u32, alignment: u32) -> PackedLocations {
+    locations
+        .into_iter()
+        .map(|(i, (page, location))| (i, (page, aligned_location(location, border, alignment))))
+        .collect()
+}
+
+/// Returns a location moved within its rect so that the position after `border` is a multiple of
+/// `alignment`, shrinking the rect by the `alignment - 1` pixels it is widened by.
+#[inline]
+fn aligned_location(
+    location: rectangle_pack::PackedLocation,
+    border: u32,
+    alignment: u32,
+) -> rectangle_pack::PackedLocation {
+    let slack = alignment - 1;
+    let align = |position: u32| (position + border).next_multiple_of(alignment) - border;
+    let rect =
+        rectangle_pack::RectToInsert::new(location.width() - slack, location.height() - slack, 1);
+    packed_location(align(location.x()), align(location.y()), rect)
+}
+
+/// Returns a packed location of a rect at the position.
+///
+/// `PackedLocation` can not be constructed directly, so the rect is placed at the origin of a
+/// section of the same extent.
+#[inline]
+fn packed_location(
+    x: u32,
+    y: u32,
+    rect: rectangle_pack::RectToInsert,
+) -> rectangle_pack::PackedLocation {
+    let (location, _) = bin_section(x, y, rect.width(), rect.height())
+        .try_place(
+            &rect,
+            &rectangle_pack::contains_smallest_box,
+            &rectangle_pack::volume_heuristic,
+        )
+        .expect("rect fits in the section of its extent");
+    location
+}
+
+/// Returns a texcoord of an extent shifted from a packed location in cells of `unit` pixels, or
+/// `None` when a coordinate can not be represented in `u32`.
+#[inline]
+fn placed_texcoord(
+    page: u32,
+    location: rectangle_pack::PackedLocation,
+    (unit_x, unit_y): (u32, u32),
+    (shift_x, shift_y): (u32, u32),
+    (width, height): (u32, u32),
+    size: u32,
+) -> Option<Texcoord> {
+    let min_x = location.x().checked_mul(unit_x)?.checked_add(shift_x)?;
+    let min_y = location.y().checked_mul(unit_y)?.checked_add(shift_y)?;
+    Some(Texcoord {
+        page,
+        min_x,
+        min_y,
+        max_x: min_x.checked_add(width)?,
+        max_y: min_y.checked_add(height)?,
+        size,
+        ..Default::default()
+    })
+}
+
+/// Returns a block location in pixels (x, y, width, height).
+#[cfg(feature = "std")]
+#[inline]
+fn block_cell(
+    location: rectangle_pack::PackedLocation,
+    (block_width, block_height): (u32, u32),
+) -> (u32, u32, u32, u32) {
+    (
+        location.x() * block_width,
+        location.y() * block_height,
+        location.width() * block_width,
+        location.height() * block_height,
+    )
+}
+
+/// Returns cleared textures of the pages, reusing buffers whose dimensions match.
+///
+/// The mip maps between the base mip map and `mip_start` are empty (0x0), since they are dropped
+/// after generation. The base mip map is kept as the source of the others.
+#[cfg(feature = "std")]
+#[inline]
+fn allocate_textures<P: image::Pixel>(
+    buffers: &mut Vec<Texture<P>>,
+    page_count: u32,
+    size: u32,
+    (mip_level_count, mip_start): (u32, u32),
+    background: Option<P>,
+    padding: u32,
+) -> Vec<Texture<P>> {
+    let skipped = 1..mip_start.max(1) as usize;
+    let mut textures = std::mem::take(buffers);
+    textures.retain(|texture| texture.size == size && texture.mip_level_count == mip_level_count);
+    textures.truncate(page_count as usize);
+    for texture in &mut textures {
+        texture.padding = padding;
+        for mip_map in &mut texture.mip_maps[skipped.clone()] {
+            *mip_map = image::ImageBuffer::new(0, 0);
+        }
+    }
+
+    for mip_map in textures
+        .iter_mut()
+        .flat_map(|texture| &mut texture.mip_maps)
+    {
+        match background {
+            Some(background) => mip_map.pixels_mut().for_each(|pixel| *pixel = background),
+            None => mip_map.fill(num_traits::Zero::zero()),
+        }
+    }
+
+    while textures.len() < page_count as usize {
+        let mip_maps = (0..mip_level_count as usize)
+            .map(|mip_level| match skipped.contains(&mip_level) {
+                true => 0,
+                false => size >> mip_level,
+            })
+            .map(|size| match background {
+                Some(background) => image::ImageBuffer::from_pixel(size, size, background),
+                None => image::ImageBuffer::new(size, size),
+            })
+            .collect();
+        textures.push(Texture {
+            size,
+            mip_level_count,
+            mip_maps,
+            padding,
+        });
+    }
+    textures
+}
+
+/// Writes an entry with padding (and extruded border) into the base mip map.
+#[cfg(feature = "std")]
+#[inline]
+fn blit_with_padding<I>(
+    target: &mut image::ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>,
+    view: &I,
+    mip: AtlasEntryMipOption,
+    rotated: bool,
+    padding: u32,
+    extrude: u32,
+    location: rectangle_pack::PackedLocation,
+) -> Result<(), AtlasError>
+where
+    I: image::GenericImageView,
+{
+    let (width, height) = dimensions(view, rotated);
+    let extent = (location.width(), location.height());
+    let src = match extrude {
+        0 => resample(
+            view,
+            mip,
+            rotated,
+            (padding, padding),
+            extent,
+            (width, height),
+            AtlasSampling::Nearest,
+        )?,
+        extrude => {
+            let extruded = (width + extrude * 2, height + extrude * 2);
+            let extruded = resample(
+                view,
+                AtlasEntryMipOption::Clamp,
+                rotated,
+                (extrude, extrude),
+                extruded,
+                (width, height),
+                AtlasSampling::Nearest,
+            )?;
+            resample(
+                &extruded,
+                mip,
+                false,
+                (padding, padding),
+                extent,
+                extruded.dimensions(),
+                AtlasSampling::Nearest,
+            )?
+        }
+    };
+
+    image::imageops::replace(target, &src, location.x() as i64, location.y() as i64);
+    Ok(())
+}
+
+/// Spreads the color of the pixels that are not fully transparent into the fully transparent
+/// pixels of the location, keeping their alpha.
+///
+/// Each transparent pixel takes the color of the nearest one by 4-connected steps, found by a
+/// breadth-first search from all of them at once. A location without such pixel is left as is.
+#[cfg(feature = "std")]
+#[inline]
+fn bleed_alpha<P>(
+    target: &mut image::ImageBuffer<P, Vec<P::Subpixel>>,
+    location: rectangle_pack::PackedLocation,
+) where
+    P: image::Pixel,
+{
+    if !P::HAS_ALPHA {
+        return;
+    }
+
+    let alpha_index = P::CHANNEL_COUNT as usize - 1;
+    let (x, y, width, height) = (
+        location.x(),
+        location.y(),
+        location.width(),
+        location.height(),
+    );
+    let mut visited = vec![false; width as usize * height as usize];
+    let mut queue = VecDeque::new();
+    for dy in 0..height {
+        for dx in 0..width {
+            let alpha = target.get_pixel(x + dx, y + dy).channels()[alpha_index];
+            if alpha != Primitive::DEFAULT_MIN_VALUE {
+                visited[(dy * width + dx) as usize] = true;
+                queue.push_back((dx, dy));
+            }
+        }
+    }
+
+    while let Some((dx, dy)) = queue.pop_front() {
+        let color = *target.get_pixel(x + dx, y + dy);
+        let neighbors = [
+            (dx.wrapping_sub(1), dy),
+            (dx + 1, dy),
+            (dx, dy.wrapping_sub(1)),
+            (dx, dy + 1),
+        ];
+        for (nx, ny) in neighbors {
+            if nx >= width || ny >= height || visited[(ny * width + nx) as usize] {
+                continue;
+            }
+            visited[(ny * width + nx) as usize] = true;
+
+            let pixel = target.get_pixel_mut(x + nx, y + ny);
+            let alpha = pixel.channels()[alpha_index];
+            *pixel = color;
+            pixel.channels_mut()[alpha_index] = alpha;
+            queue.push_back((nx, ny));
+        }
+    }
+}
+
+/// Writes an entry with its half-block gutter into every mip map of the block location.
+///
+/// `cell` is the block-rounded location in pixels (x, y, width, height), and `src` is placed at
+/// `shift` in it. At each mip level, the entry is resized from `src` to the span between its
+/// scaled-down edges (at least one pixel) with `mip_wrap` beyond its edges, and the gutter is
+/// filled from the resized entry by `gutter_wrap`, so the tiling period matches the entry at every
+/// mip level.
+///
+/// Without `filter`, only the base mip map is written and the coarser ones are left as is. Empty
+/// (0x0) mip maps are skipped.
+#[cfg(feature = "std")]
+#[inline]
+fn blit_with_block<P>(
+    texture: &mut Texture<P>,
+    src: &image::ImageBuffer<P, Vec<P::Subpixel>>,
+    (gutter_wrap, mip_wrap): (AtlasEntryMipOption, AtlasEntryMipOption),
+    (x, y, width, height): (u32, u32, u32, u32),
+    (shift_x, shift_y): (u32, u32),
+    filter: Option<AtlasMipFilter>,
+    mip_options: (AtlasMipColorSpace, bool, Option<SharpenParams>),
+) -> Result<(), AtlasError>
+where
+    P: image::Pixel + 'static,
+{
+    let mip_level_count = if filter.is_some() {
+        texture.mip_level_count
+    } else {
+        1
+    };
+    for mip_level in 0..mip_level_count {
+        if texture.mip_maps[mip_level as usize].width() == 0 {
+            continue;
+        }
+        let shift = (shift_x >> mip_level, shift_y >> mip_level);
+        let max_x = ((shift_x + src.width()) >> mip_level).max(shift.0 + 1);
+        let max_y = ((shift_y + src.height()) >> mip_level).max(shift.1 + 1);
+        let footprint = (max_x - shift.0, max_y - shift.1);
+        let extent = (width >> mip_level, height >> mip_level);
+
+        let cell = match filter.filter(|_| mip_level > 0) {
+            Some(filter) => {
+                let entry = resize_wrapped(src, footprint, filter, mip_wrap, mip_options)?;
+                resample(
+                    &entry,
+                    gutter_wrap,
+                    false,
+                    shift,
+                    extent,
+                    footprint,
+                    AtlasSampling::Nearest,
+                )?
+            }
+            None => resample(
+                src,
+                gutter_wrap,
+                false,
+                shift,
+                extent,
+                footprint,
+                AtlasSampling::Nearest,
+            )?,
+        };
+
+        let target = &mut texture.mip_maps[mip_level as usize];
+        let (x, y) = (x >> mip_level, y >> mip_level);
+        image::imageops::replace(target, &cell, x as i64, y as i64);
+    }
+    Ok(())
+}
+
+/// Regenerates every mip map of the texture from the base mip map, skipping empty (0x0) ones.
+#[cfg(feature = "std")]
+#[inline]
+fn generate_mip_maps<P>(
+    texture: &mut Texture<P>,
+    filter: AtlasMipFilter,
+    mip_color_space: AtlasMipColorSpace,
+    premultiply_alpha: bool,
+    mip_sharpen: Option<SharpenParams>,
+) where
+    P: image::Pixel + 'static,
+{
+    for mip_level in 1..texture.mip_level_count {
+        if texture.mip_maps[mip_level as usize].width() == 0 {
+            continue;
+        }
+        let size = texture.size >> mip_level;
+
+        let mip_map = resize(
+            &texture.mip_maps[0],
+            size,
+            size,
+            filter,
+            (mip_color_space, premultiply_alpha, mip_sharpen),
+        );
+
+        let target = &mut texture.mip_maps[mip_level as usize];
+        image::imageops::replace(target, &mip_map, 0, 0);
+    }
+}
+
+/// Places a single rect covering a whole bin of `bin_size` (width, height) at the origin of the
+/// first empty bin, or returns `None` for any other rects.
+///
+/// Such a rect fits only in an empty bin, so it is placed directly without sorting and trying
+/// every section by the packer. This leaves the bins in the same state as `rectangle_pack`.
+#[inline]
+fn place_single(
+    rects: &[(usize, rectangle_pack::RectToInsert)],
+    target_bins: &mut BTreeMap<u32, rectangle_pack::TargetBin>,
+    bin_size: (u32, u32),
+) -> Option<PackedLocations> {
+    let [(i, rect)] = *rects else {
+        return None;
+    };
+    if (rect.width(), rect.height()) != bin_size {
+        return None;
+    }
+    for (&page, bin) in target_bins.iter_mut() {
+        let [section] = bin.available_bin_sections()[..] else {
+            continue;
+        };
+        let placement = section.try_place(
+            &rect,
+            &rectangle_pack::contains_smallest_box,
+            &rectangle_pack::volume_heuristic,
+        );
+        if let Ok((location, _)) = placement {
+            bin.remove_filled_section(0);
+            return Some(BTreeMap::from([(i, (page, location))]));
+        }
+    }
+    None
+}
+
+/// Packs rects into target bins with the packing strategy and returns locations keyed by rect id.
+///
+/// A single rect covering a whole bin is placed by [place_single](place_single) without the
+/// packer.
+///
+/// `rectangle_pack` places rects of equal volume in the order of their id, so rects are given
+/// their rank as id. With `stable`, the rank is the canonical order (area descending, then width,
+/// height and id), otherwise it is the input order.
+///
+/// `ContainsLargestBox` selects the container that has the largest box, and the second largest
+/// box on tie. `WidthHeightDepth` is not exported by `rectangle_pack`, so it is written as a
+/// closure.
+///
+/// `groups` is indexed by rect id (`None` out of range), and rects of the same group are packed
+/// into one bin. `rectangle_pack` fails when a group does not fit in any single bin, so each
+/// group which does not fit alone into the original bins is packed ungrouped, and the rects are
+/// packed again with the other groups kept.
+///
+/// With `Skyline` or `Shelf`, rects are packed by [pack_skyline](pack_skyline) or
+/// [pack_shelf](pack_shelf) instead, ignoring groups.
+#[inline]
+fn pack_rects(
+    rects: &[(usize, rectangle_pack::RectToInsert)],
+    groups: &[Option<u32>],
+    target_bins: &mut BTreeMap<u32, rectangle_pack::TargetBin>,
+    bin_size: (u32, u32),
+    pack_heuristic: PackHeuristic,
+    pack_algorithm: PackAlgorithm,
+    stable: bool,
+) -> Result<PackedLocations, rectangle_pack::RectanglePackError> {
+    if let Some(locations) = place_single(rects, target_bins, bin_size) {
+        return Ok(locations);
+    }
+
+    match pack_algorithm {
+        PackAlgorithm::RectanglePack => {}
+        #[cfg(feature = "skyline")]
+        PackAlgorithm::Skyline => return pack_skyline(rects, target_bins),
+        #[cfg(not(feature = "skyline"))]
+        PackAlgorithm::Skyline => {}
+        #[cfg(feature = "shelf")]
+        PackAlgorithm::Shelf => return pack_shelf(rects, target_bins),
+        #[cfg(not(feature = "shelf"))]
+        PackAlgorithm::Shelf => {}
+    }
+
+    let mut order = (0..rects.len()).collect::<Vec<_>>();
+    if stable {
+        order.sort_by_key(|&rank| {
+            let (i, rect) = rects[rank];
+            let area = rect.width() as u64 * rect.height() as u64;
+            (
+                core::cmp::Reverse(area),
+                core::cmp::Reverse(rect.width()),
+                core::cmp::Reverse(rect.height()),
+                i,
+            )
+        });
+    }
+
+    let pack = |groups: &[Option<u32>], only: Option<u32>, target_bins: &mut BTreeMap<_, _>| {
+        let mut grouped = rectangle_pack::GroupedRectsToPlace::new();
+        for (rank, &k) in order.iter().enumerate() {
+            let (i, rect) = rects[k];
+            let group = groups.get(i).copied().flatten();
+            if only.is_some_and(|only| group != Some(only)) {
+                continue;
+            }
+            grouped.push_rect(rank, group.map(|group| vec![group]), rect);
+        }
+
+        match pack_heuristic {
+            PackHeuristic::ContainsSmallestBox => rectangle_pack::pack_rects(
+                &grouped,
+                target_bins,
+                &rectangle_pack::volume_heuristic,
+                &rectangle_pack::contains_smallest_box,
+            ),
+            PackHeuristic::ContainsLargestBox => rectangle_pack::pack_rects(
+                &grouped,
+                target_bins,
+                &rectangle_pack::volume_heuristic,
+                &|mut container1, mut container2, heuristic| {
+                    container1.sort_by_key(|&whd| core::cmp::Reverse(heuristic(whd)));
+                    container2.sort_by_key(|&whd| core::cmp::Reverse(heuristic(whd)));
+
+                    match heuristic(container1[0]).cmp(&heuristic(container2[0])) {
+                        core::cmp::Ordering::Equal => {
+                            heuristic(container1[1]).cmp(&heuristic(container2[1]))
+                        }
+                        ordering => ordering,
+                    }
+                },
+            ),
+        }
+    };
+
+    let original = groups
+        .iter()
+        .any(Option::is_some)
+        .then(|| target_bins.clone());
+    let locations = match (pack(groups, None, target_bins), original) {
+        (Err(_), Some(original)) => {
+            let mut ids = groups.iter().flatten().copied().collect::<Vec<_>>();
+            ids.sort_unstable();
+            ids.dedup();
+            ids.retain(|&id| pack(groups, Some(id), &mut original.clone()).is_err());
+
+            let groups = groups
+                .iter()
+                .map(|group| group.filter(|id| ids.binary_search(id).is_err()))
+                .collect::<Vec<_>>();
+            *target_bins = original;
+            pack(&groups, None, target_bins)
+        }
+        (result, _) => result,
+    }?;
+
+    let locations = locations
+        .packed_locations()
+        .iter()
+        .map(|(&rank, &location)| (rects[order[rank]].0, location))
+        .collect();
+    Ok(locations)
+}
+
+/// Packs rects into target bins by the skyline bottom-left heuristic.
+///
+/// A skyline is raised from the top of each free section of a bin, so the space already used in
+/// the bin is never touched. A rect goes to the lowest (then leftmost) position over the skylines
+/// of the first bin that fits it. After packing, the sections of every bin that received rects
+/// are replaced by the space below each segment of its skylines, which is disjoint, so no grid
+/// over the placed rects is needed.
+#[cfg(feature = "skyline")]
+#[inline]
+fn pack_skyline(
+    rects: &[(usize, rectangle_pack::RectToInsert)],
+    target_bins: &mut BTreeMap<u32, rectangle_pack::TargetBin>,
+) -> Result<PackedLocations, rectangle_pack::RectanglePackError> {
+    let mut order = (0..rects.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&k| {
+        let (i, rect) = rects[k];
+        (
+            core::cmp::Reverse(rect.height()),
+            core::cmp::Reverse(rect.width()),
+            i,
+        )
+    });
+
+    let mut pages = target_bins
+        .iter()
+        .map(|(&page, target_bin)| {
+            let skylines = target_bin
+                .available_bin_sections()
+                .iter()
+                .map(|section| Skyline::new(section_rect(section)))
+                .collect::<Vec<_>>();
+            (page, skylines)
+        })
+        .collect::<Vec<_>>();
+
+    let mut locations = BTreeMap::new();
+    for k in order {
+        let (i, rect) = rects[k];
+        let (width, height) = (rect.width(), rect.height());
+
+        let (page, x, y) = pages
+            .iter_mut()
+            .find_map(|(page, skylines)| {
+                let (skyline, (x, y)) = skylines
+                    .iter_mut()
+                    .filter_map(|skyline| Some((skyline.find(width, height)?, skyline)))
+                    .min_by_key(|&((x, y), _)| (y, x))
+                    .map(|(position, skyline)| (skyline, position))?;
+                skyline.place(x, y, width, height);
+                Some((*page, x, y))
+            })
+            .ok_or(rectangle_pack::RectanglePackError::NotEnoughBinSpace)?;
+
+        locations.insert(i, (page, packed_location(x, y, rect)));
+    }
+
+    for (page, skylines) in pages {
+        if skylines.iter().all(|skyline| !skyline.placed) {
+            continue;
+        }
+
+        let target_bin = target_bins.get_mut(&page).expect("skylines of the bin");
+        while !target_bin.available_bin_sections().is_empty() {
+            target_bin.remove_filled_section(0);
+        }
+        for (x, y, width, height) in skylines.iter().flat_map(Skyline::free) {
+            let section = bin_section(x, y, width, height);
+            target_bin.push_available_bin_section_unchecked(section);
+        }
+    }
+
+    Ok(locations)
+}
+
+/// A skyline over a free section of a bin packed bottom-left.
+///
+/// - `rect`: A (x, y, width, height) of the section.
+/// - `segments`: A vec of (x, y, width) of the skyline ordered by x, covering the section width.
+/// - `placed`: Whether any rect is placed.
+#[cfg(feature = "skyline")]
+struct Skyline {
+    rect: (u32, u32, u32, u32),
+    segments: Vec<(u32, u32, u32)>,
+    placed: bool,
+}
+
+#[cfg(feature = "skyline")]
+impl Skyline {
+    #[inline]
+    fn new(rect: (u32, u32, u32, u32)) -> Self {
+        let (x, y, width, _) = rect;
+        Self {
+            rect,
+            segments: vec![(x, y, width)],
+            placed: false,
+        }
+    }
+
+    /// Returns the lowest (then leftmost) position where the rect fits.
+    #[inline]
+    fn find(&self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let (section_x, section_y, section_width, section_height) = self.rect;
+        let mut best = None::<(u32, u32)>;
+        for (i, &(x, _, _)) in self.segments.iter().enumerate() {
+            if x + width > section_x + section_width {
+                break;
+            }
+
+            let y = self.segments[i..]
+                .iter()
+                .take_while(|&&(segment_x, _, _)| segment_x < x + width)
+                .map(|&(_, segment_y, _)| segment_y)
+                .max()
+                .unwrap_or(section_y);
+
+            let lower = best.is_none_or(|(best_x, best_y)| (y, x) < (best_y, best_x));
+            if y + height <= section_y + section_height && lower {
+                best = Some((x, y));
+            }
+        }
+        best
+    }
+
+    /// Raises the skyline over the placed rect.
+    #[inline]
+    fn place(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        let (start, end) = (x, x + width);
+
+        let mut segments = Vec::with_capacity(self.segments.len() + 2);
+        for &(segment_x, segment_y, segment_width) in &self.segments {
+            if segment_x < start {
+                segments.push((segment_x, segment_y, segment_width.min(start - segment_x)));
+            }
+        }
+        segments.push((start, y + height, width));
+        for &(segment_x, segment_y, segment_width) in &self.segments {
+            if segment_x + segment_width > end {
+                let x = segment_x.max(end);
+                segments.push((x, segment_y, segment_x + segment_width - x));
+            }
+        }
+        segments.retain(|&(_, _, width)| width > 0);
+        segments.dedup_by(|next, prev| {
+            let merge = prev.1 == next.1;
+            if merge {
+                prev.2 += next.2;
+            }
+            merge
+        });
+
+        self.segments = segments;
+        self.placed = true;
+    }
+
+    /// Returns the free rects (x, y, width, height) below each segment of the skyline.
+    #[inline]
+    fn free(&self) -> Vec<(u32, u32, u32, u32)> {
+        let bottom = self.rect.1 + self.rect.3;
+        self.segments
+            .iter()
+            .filter(|&&(_, y, _)| y < bottom)
+            .map(|&(x, y, width)| (x, y, width, bottom - y))
+            .collect()
+    }
+}
+
+/// Packs rects into target bins in shelves (rows) sorted by height.
+///
+/// Shelves are stacked from the top of each free section of a bin, so the space already used in
+/// the bin is never touched. A rect goes into the shortest shelf (the upper one on tie) of the
+/// first section that is tall enough and has room on its right, or opens a new shelf below the last
+/// one. After packing, the sections of every bin that received rects are replaced by the right of
+/// each shelf and the space below the last shelf, which are disjoint, so no grid over the placed
+/// rects is needed.
+#[cfg(feature = "shelf")]
+#[inline]
+fn pack_shelf(
+    rects: &[(usize, rectangle_pack::RectToInsert)],
+    target_bins: &mut BTreeMap<u32, rectangle_pack::TargetBin>,
+) -> Result<PackedLocations, rectangle_pack::RectanglePackError> {
+    let mut order = (0..rects.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&k| {
+        let (i, rect) = rects[k];
+        (
+            core::cmp::Reverse(rect.height()),
+            core::cmp::Reverse(rect.width()),
+            i,
+        )
+    });
+
+    let mut pages = target_bins
+        .iter()
+        .map(|(&page, target_bin)| {
+            let sections = target_bin
+                .available_bin_sections()
+                .iter()
+                .map(|section| ShelfSection::new(section_rect(section)))
+                .collect::<Vec<_>>();
+            (page, sections)
+        })
+        .collect::<Vec<_>>();
+
+    let mut locations = BTreeMap::new();
+    for k in order {
+        let (i, rect) = rects[k];
+        let (width, height) = (rect.width(), rect.height());
+
+        let (page, x, y) = pages
+            .iter_mut()
+            .find_map(|(page, sections)| {
+                let (x, y) = sections
+                    .iter_mut()
+                    .find_map(|section| section.place(width, height))?;
+                Some((*page, x, y))
+            })
+            .ok_or(rectangle_pack::RectanglePackError::NotEnoughBinSpace)?;
+
+        locations.insert(i, (page, packed_location(x, y, rect)));
+    }
+
+    for (page, sections) in pages {
+        if sections.iter().all(|section| section.shelves.is_empty()) {
+            continue;
+        }
+
+        let target_bin = target_bins.get_mut(&page).expect("shelves of the bin");
+        while !target_bin.available_bin_sections().is_empty() {
+            target_bin.remove_filled_section(0);
+        }
+        for (x, y, width, height) in sections.iter().flat_map(ShelfSection::free) {
+            let section = bin_section(x, y, width, height);
+            target_bin.push_available_bin_section_unchecked(section);
+        }
+    }
+
+    Ok(locations)
+}
+
+/// A free section of a bin packed in shelves.
+///
+/// - `rect`: A (x, y, width, height) of the section.
+/// - `shelves`: A vec of (y, height, used width) of the shelves from the top.
+#[cfg(feature = "shelf")]
+struct ShelfSection {
+    rect: (u32, u32, u32, u32),
+    shelves: Vec<(u32, u32, u32)>,
+}
+
+#[cfg(feature = "shelf")]
+impl ShelfSection {
+    #[inline]
+    fn new(rect: (u32, u32, u32, u32)) -> Self {
+        Self {
+            rect,
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Places the rect on the shortest shelf with room, or on a new shelf, and returns its
+    /// position.
+    #[inline]
+    fn place(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let (x, y, section_width, section_height) = self.rect;
+        if width > section_width {
+            return None;
+        }
+
+        let shelf = self
+            .shelves
+            .iter_mut()
+            .filter(|&&mut (_, shelf_height, used)| {
+                height <= shelf_height && width <= section_width - used
+            })
+            .min_by_key(|&&mut (_, shelf_height, _)| shelf_height);
+        if let Some((shelf_y, _, used)) = shelf {
+            let position = (x + *used, *shelf_y);
+            *used += width;
+            return Some(position);
+        }
+
+        let top = self
+            .shelves
+            .last()
+            .map_or(y, |&(shelf_y, shelf_height, _)| shelf_y + shelf_height);
+        if top + height > y + section_height {
+            return None;
+        }
+        self.shelves.push((top, height, width));
+        Some((x, top))
+    }
+
+    /// Returns the free rects (x, y, width, height) right of each shelf and below the last one.
+    #[inline]
+    fn free(&self) -> Vec<(u32, u32, u32, u32)> {
+        let (x, y, width, height) = self.rect;
+        let mut free = self
+            .shelves
+            .iter()
+            .filter(|&&(_, _, used)| used < width)
+            .map(|&(shelf_y, shelf_height, used)| (x + used, shelf_y, width - used, shelf_height))
+            .collect::<Vec<_>>();
+        let top = self
+            .shelves
+            .last()
+            .map_or(y, |&(shelf_y, shelf_height, _)| shelf_y + shelf_height);
+        if top < y + height {
+            free.push((x, top, width, y + height - top));
+        }
+        free
+    }
+}
+
+/// Returns a view of each entry texture which is stored in the atlas.
+#[cfg(feature = "std")]
+#[inline]
+fn views<I>(entries: &[AtlasEntry<I>]) -> Vec<image::SubImage<&I>>
+where
+    I: image::GenericImageView,
+{
+    entries
+        .iter()
+        .map(|entry| {
+            let (x, y, width, height) = match entry.trim {
+                true => trim_bounds(&entry.texture),
+                false => (0, 0, entry.texture.width(), entry.texture.height()),
+            };
+            image::imageops::crop_imm(&entry.texture, x, y, width, height)
+        })
+        .collect()
+}
+
+/// Returns the tight bounding box of pixels whose alpha is not zero.
+#[cfg(feature = "std")]
+#[inline]
+fn trim_bounds<I>(texture: &I) -> (u32, u32, u32, u32)
+where
+    I: image::GenericImageView,
+{
+    let (width, height) = texture.dimensions();
+    if !I::Pixel::HAS_ALPHA {
+        return (0, 0, width, height);
+    }
+
+    let alpha_index = I::Pixel::CHANNEL_COUNT as usize - 1;
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (width, height, 0, 0);
+    for (x, y, pixel) in texture.pixels() {
+        if pixel.channels()[alpha_index] != Primitive::DEFAULT_MIN_VALUE {
+            min_x = u32::min(min_x, x);
+            min_y = u32::min(min_y, y);
+            max_x = u32::max(max_x, x + 1);
+            max_y = u32::max(max_y, y + 1);
+        }
+    }
+
+    if min_x >= max_x || min_y >= max_y {
+        return (0, 0, width, height);
+    }
+
+    (min_x, min_y, max_x - min_x, max_y - min_y)
+}
+
+/// Returns the index of entry whose stored pixels are shared by each entry.
+///
+/// Entries are shared only if their tiling options, groups, pinned pages, preserved texcoords and
+/// stored pixels are identical. Candidates are grouped by a content hash over the subpixels and
+/// then compared exactly.
+#[cfg(feature = "std")]
+#[inline]
+fn aliases<I>(
+    deduplicate: bool,
+    entries: &[AtlasEntry<I>],
+    views: &[image::SubImage<&I>],
+    preserve: &[(usize, Texcoord)],
+) -> Vec<usize>
+where
+    I: image::GenericImageView,
+{
+    if !deduplicate {
+        return (0..entries.len()).collect();
+    }
+
+    let mut buckets = HashMap::<_, Vec<usize>>::new();
+    let mut aliases = Vec::with_capacity(entries.len());
+    for (i, view) in views.iter().enumerate() {
+        let mut hasher = DefaultHasher::new();
+        for (_, _, pixel) in view.pixels() {
+            for subpixel in pixel.channels() {
+                subpixel
+                    .to_f64()
+                    .unwrap_or_default()
+                    .to_bits()
+                    .hash(&mut hasher);
+            }
+        }
+        let preserved = preserve
+            .iter()
+            .find(|&&(index, _)| index == i)
+            .map(|&(_, t)| (t.page, t.min_x, t.min_y, t.max_x, t.max_y));
+        let key = (
+            view.dimensions(),
+            entries[i].mip,
+            entries[i].mip_filter,
+            entries[i].anchor,
+            entries[i].generate_mips,
+            (entries[i].group, entries[i].pin_page, preserved),
+            hasher.finish(),
+        );
+
+        let bucket = buckets.entry(key).or_default();
+        let alias = bucket.iter().copied().find(|&j| {
+            view.pixels()
+                .zip(views[j].pixels())
+                .all(|((_, _, a), (_, _, b))| a.channels() == b.channels())
+        });
+        match alias {
+            Some(j) => aliases.push(j),
+            None => {
+                bucket.push(i);
+                aliases.push(i);
+            }
+        }
+    }
+    aliases
+}
+
+/// Fills the per-entry fields of texcoords after packing.
+///
+/// Aliased entries share the location of the entry they alias, but trim offsets and source size
+/// are kept per entry since they may differ between aliases. Reserved regions (after entries)
+/// have their own size as source size.
+#[cfg(feature = "std")]
+#[inline]
+fn resolve_texcoords<I>(
+    texcoords: &mut [Texcoord],
+    aliases: &[usize],
+    entries: &[AtlasEntry<I>],
+    views: &[image::SubImage<&I>],
+    rotations: &[bool],
+) where
+    I: image::GenericImageView,
+{
+    for i in 0..texcoords.len() {
+        texcoords[i] = match entries.get(i) {
+            Some(entry) => Texcoord {
+                rotated: rotations[i],
+                trim_offset_x: views[i].offsets().0,
+                trim_offset_y: views[i].offsets().1,
+                source_width: entry.texture.width(),
+                source_height: entry.texture.height(),
+                ..texcoords[aliases[i]]
+            },
+            None => Texcoord {
+                source_width: texcoords[i].max_x - texcoords[i].min_x,
+                source_height: texcoords[i].max_y - texcoords[i].min_y,
+                ..texcoords[i]
+            },
+        };
+    }
+}
+
+/// Packs the views of entries followed by reserved regions, and returns whether each entry is
+/// stored transposed with the layout.
+#[cfg(feature = "std")]
+#[inline]
+fn pack_views<I>(
+    desc: &AtlasDescriptor<'_, I>,
+    views: &[image::SubImage<&I>],
+    aliases: &[usize],
+) -> Result<(Vec<bool>, PackedLayout), AtlasError>
+where
+    I: image::GenericImageView,
+{
+    let sizes = views
+        .iter()
+        .map(|view| view.dimensions())
+        .collect::<Vec<_>>();
+    pack_oriented(
+        desc.allow_rotation,
+        &sizes,
+        (aliases, desc.preserve),
+        |rotations| pack_layout(desc, &extents(views, rotations, desc.reserve), aliases),
+    )
+}
+
+/// Returns the width and height of a texture as it is laid out in the atlas.
+#[cfg(feature = "std")]
+#[inline]
+fn dimensions<I>(texture: &I, rotated: bool) -> (u32, u32)
+where
+    I: image::GenericImageView,
+{
+    match rotated {
+        true => (texture.height(), texture.width()),
+        false => (texture.width(), texture.height()),
+    }
+}
+
+/// Returns the width and height of each entry as laid out in the atlas, followed by reserved
+/// regions.
+#[cfg(feature = "std")]
+#[inline]
+fn extents<I>(
+    views: &[image::SubImage<&I>],
+    rotations: &[bool],
+    reserve: &[(u32, u32)],
+) -> Vec<(u32, u32)>
+where
+    I: image::GenericImageView,
+{
+    views
+        .iter()
+        .zip(rotations)
+        .map(|(view, &rotated)| dimensions(&**view, rotated))
+        .chain(reserve.iter().copied())
+        .collect()
+}
+
+/// Checks that every used index of a channel swizzle is a channel of the pixel.
+#[cfg(feature = "std")]
+#[inline]
+fn validate_swizzle<P: image::Pixel>(swizzle: [usize; 4]) -> Result<(), AtlasError> {
+    let channel_count = P::CHANNEL_COUNT as usize;
+    match swizzle[..channel_count.min(4)]
+        .iter()
+        .all(|&i| i < channel_count)
+    {
+        true => Ok(()),
+        false => Err(AtlasError::InvalidSwizzle(swizzle)),
+    }
+}
+
+/// Returns a copy of texture as an image buffer.
+#[cfg(feature = "std")]
+#[inline]
+fn owned_texture<I>(texture: &I) -> image::ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>
+where
+    I: image::GenericImageView,
+{
+    image::ImageBuffer::from_fn(texture.width(), texture.height(), |x, y| {
+        texture.get_pixel(x, y)
+    })
+}
+
+/// A texture borrowed from an entry, or owned by a rotated copy of `bake_rotations`.
+#[cfg(feature = "std")]
+enum BakedTexture<'a, I: image::GenericImageView> {
+    Entry(&'a I),
+    Rotated(OwnedTexture<I::Pixel>),
+}
+
+#[cfg(feature = "std")]
+impl<I: image::GenericImageView> image::GenericImageView for BakedTexture<'_, I> {
+    type Pixel = I::Pixel;
+
+    #[inline]
+    fn dimensions(&self) -> (u32, u32) {
+        match self {
+            BakedTexture::Entry(texture) => texture.dimensions(),
+            BakedTexture::Rotated(texture) => texture.dimensions(),
+        }
+    }
+
+    #[inline]
+    fn get_pixel(&self, x: u32, y: u32) -> Self::Pixel {
+        match self {
+            BakedTexture::Entry(texture) => texture.get_pixel(x, y),
+            BakedTexture::Rotated(texture) => *texture.get_pixel(x, y),
+        }
+    }
+}
+
+/// Returns a copy of entry whose texture is rotated clockwise by `turns` quarter turns.
+#[cfg(feature = "std")]
+#[inline]
+fn rotate_entry<'a, I>(entry: &AtlasEntry<I>, turns: u32) -> AtlasEntry<BakedTexture<'a, I>>
+where
+    I: image::GenericImageView,
+    I::Pixel: 'static,
+{
+    let texture = match turns % 4 {
+        1 => image::imageops::rotate90(&entry.texture),
+        2 => image::imageops::rotate180(&entry.texture),
+        3 => image::imageops::rotate270(&entry.texture),
+        _ => owned_texture(&entry.texture),
+    };
+    let size = match turns % 2 {
+        1 => entry.size.map(|(width, height)| (height, width)),
+        _ => entry.size,
+    };
+    AtlasEntry {
+        size,
+        ..entry.map_texture(|_| BakedTexture::Rotated(texture))
+    }
+}
+
+/// Moves the texcoords of rotated copies appended after the entries into `baked_rotations`.
+///
+/// `baked` lists the entries with `bake_rotations`, whose three copies are appended in order.
+/// The placements of the copies are given the index of their entry, and the reserved regions
+/// after them are indexed after the entries again.
+#[cfg(feature = "std")]
+#[inline]
+fn split_baked_rotations<P: image::Pixel>(atlas: &mut Atlas<P>, baked: &[usize]) {
+    let copy_count = baked.len() * 3;
+    let count = atlas.texcoords.len() - copy_count;
+    let copies = atlas.texcoords.split_off(count);
+    atlas.block_waste.truncate(count);
+    atlas.scale_factors.truncate(count);
+    atlas.alpha_coverage.truncate(count);
+    atlas.baked_rotations = vec![None; count];
+    for (&i, copies) in baked.iter().zip(copies.chunks_exact(3)) {
+        atlas.baked_rotations[i] = Some([copies[0], copies[1], copies[2]]);
+    }
+
+    for rect in &mut atlas.packed_rects {
+        rect.index = match rect.index.checked_sub(count) {
+            None => rect.index,
+            Some(i) if i < copy_count => baked[i / 3],
+            Some(i) => count + i - copy_count,
+        };
+    }
+    if let Some(packer) = &mut atlas.packer {
+        packer.sub_blocks.retain(|&i, _| i < count);
+    }
+}
+
+/// Returns a copy of texture whose channel `i` is channel `swizzle[i]` of the source.
+#[cfg(feature = "std")]
+#[inline]
+fn swizzle_channels<I>(
+    texture: &I,
+    swizzle: [usize; 4],
+) -> image::ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>
+where
+    I: image::GenericImageView,
+{
+    image::ImageBuffer::from_fn(texture.width(), texture.height(), |x, y| {
+        let src = texture.get_pixel(x, y);
+        let mut pixel = src;
+        for (channel, &i) in pixel.channels_mut().iter_mut().zip(&swizzle) {
+            *channel = src.channels()[i];
+        }
+        pixel
+    })
+}
+
+/// Returns the extent downscaled to fit in `limit` keeping the aspect ratio, and the scale factor
+/// (1 when it already fits, or when nothing fits).
+#[cfg(feature = "std")]
+#[inline]
+fn downscale((width, height): (u32, u32), limit: u32) -> ((u32, u32), f32) {
+    if (width <= limit && height <= limit) || limit == 0 {
+        return ((width, height), 1.0);
+    }
+
+    let scale = f64::min(limit as f64 / width as f64, limit as f64 / height as f64);
+    let scaled = |extent: u32| ((extent as f64 * scale).floor() as u32).clamp(1, limit);
+    ((scaled(width), scaled(height)), scale as f32)
+}
+
+/// Returns the `size` of an entry when it differs from the texture size.
+#[cfg(feature = "std")]
+#[inline]
+fn resized<I>(entry: &AtlasEntry<I>) -> Option<(u32, u32)>
+where
+    I: image::GenericImageView,
+{
+    entry
+        .size
+        .filter(|&size| size != entry.texture.dimensions())
+}
+
+/// Returns a copy of entry whose texture is resized to its `size` by the sampling method.
+#[cfg(feature = "std")]
+#[inline]
+fn resize_entry<I, P>(
+    entry: &AtlasEntry<I>,
+    sampling: AtlasSampling,
+) -> Result<AtlasEntry<OwnedTexture<P>>, AtlasError>
+where
+    I: image::GenericImageView<Pixel = P>,
+    P: image::Pixel,
+{
+    let size = entry.size.unwrap_or(entry.texture.dimensions());
+    let texture = resample(
+        &entry.texture,
+        entry.mip,
+        false,
+        (0, 0),
+        size,
+        size,
+        sampling,
+    )?;
+    Ok(AtlasEntry {
+        size: None,
+        ..entry.map_texture(|_| texture)
+    })
+}
+
+/// Returns a texture of the extent where the source is placed at the shift, and the rest is
+/// filled by the tiling option.
+///
+/// When `footprint` matches the (rotated) source, source pixels are copied as is. Otherwise the
+/// source is scaled to `footprint` by `sampling`: positions are wrapped around the footprint by
+/// the tiling option, and mapped to pixel centers of the source, whose neighbors are clamped.
+///
+/// Every source position is checked against the source bounds before it is read, so a
+/// miscomputed position (or an empty source or footprint, or a shift out of `i32`) is returned as
+/// an internal error instead of panicking in `image`.
+#[cfg(feature = "std")]
+#[inline]
+#[rustfmt::skip]
+fn resample<I>(
+    src: &I,
+    mip: AtlasEntryMipOption,
+    rotated: bool,
+    (shift_x, shift_y): (u32, u32),
+    (width, height): (u32, u32),
+    footprint: (u32, u32),
+    sampling: AtlasSampling,
+) -> Result<OwnedTexture<I::Pixel>, AtlasError>
+where
+    I: image::GenericImageView,
+{
+    let mut target = image::ImageBuffer::new(width, height);
+    if width == 0 || height == 0 {
+        return Ok(target);
+    }
+    if src.width() == 0 || src.height() == 0 || footprint.0 == 0 || footprint.1 == 0 {
+        return Err(AtlasError::Internal("resample from an empty source or footprint"));
+    }
+    let (Ok(shift_x), Ok(shift_y)) = (i32::try_from(shift_x), i32::try_from(shift_y)) else {
+        return Err(AtlasError::Internal("resample shift out of range"));
+    };
+    let fetch = |x: i32, y: i32| match x >= 0 && y >= 0 && src.in_bounds(x as u32, y as u32) {
+        true => Ok(src.get_pixel(x as u32, y as u32)),
+        false => Err(AtlasError::Internal("resample position out of the source bounds")),
+    };
+
+    if footprint == dimensions(src, rotated) {
+        for x in 0..width {
+            for y in 0..height {
+                let dx = x as i32 - shift_x;
+                let dy = y as i32 - shift_y;
+                let (dx, dy) = if rotated { (dy, dx) } else { (dx, dy) };
+                let sx = wrap(mip, dx, src.width() as i32);
+                let sy = wrap(mip, dy, src.height() as i32);
+                *target.get_pixel_mut(x, y) = fetch(sx, sy)?;
+            }
+        }
+        return Ok(target);
+    }
+
+    let (src_width, src_height) = dimensions(src, rotated);
+    let (footprint_width, footprint_height) = footprint;
+    let scale_x = src_width as f32 / footprint_width as f32;
+    let scale_y = src_height as f32 / footprint_height as f32;
+    let pixel = |x: i32, y: i32| {
+        let x = x.clamp(0, src_width as i32 - 1);
+        let y = y.clamp(0, src_height as i32 - 1);
+        let (x, y) = if rotated { (y, x) } else { (x, y) };
+        fetch(x, y)
+    };
+    for x in 0..width {
+        for y in 0..height {
+            let fx = wrap(mip, x as i32 - shift_x, footprint_width as i32);
+            let fy = wrap(mip, y as i32 - shift_y, footprint_height as i32);
+            let u = (fx as f32 + 0.5) * scale_x - 0.5;
+            let v = (fy as f32 + 0.5) * scale_y - 0.5;
+
+            *target.get_pixel_mut(x, y) = match sampling {
+                AtlasSampling::Nearest => pixel(u.round() as i32, v.round() as i32)?,
+                AtlasSampling::Bilinear => {
+                    let (x0, y0) = (u.floor(), v.floor());
+                    let (tx, ty) = (u - x0, v - y0);
+                    let (x0, y0) = (x0 as i32, y0 as i32);
+                    let taps = [
+                        (pixel(x0, y0)?, (1.0 - tx) * (1.0 - ty)),
+                        (pixel(x0 + 1, y0)?, tx * (1.0 - ty)),
+                        (pixel(x0, y0 + 1)?, (1.0 - tx) * ty),
+                        (pixel(x0 + 1, y0 + 1)?, tx * ty),
+                    ];
+                    let mut value = taps[0].0;
+                    for (i, channel) in value.channels_mut().iter_mut().enumerate() {
+                        let sum = taps.iter().map(|(tap, weight)| to_unit(tap.channels()[i]) * weight).sum::<f32>();
+                        *channel = from_unit(sum);
+                    }
+                    value
+                }
+            };
+        }
+    }
+    Ok(target)
+}
+
+/// Maps a position relative to the texture origin into the texture bounds.
+#[cfg(feature = "std")]
+#[inline]
+fn wrap(mip: AtlasEntryMipOption, d: i32, len: i32) -> i32 {
+    match mip {
+        AtlasEntryMipOption::Clamp => d.max(0).min(len - 1),
+        AtlasEntryMipOption::Repeat => d.rem_euclid(len),
+        AtlasEntryMipOption::Mirror => {
+            let s = d.rem_euclid(len);
+            if d.div_euclid(len) & 1 == 0 {
+                len - 1 - s
+            } else {
+                s
+            }
+        }
+    }
+}
+
+/// Resizes an entry for mip map generation, wrapping its edges by the tiling option.
+///
+/// With `Repeat` or `Mirror`, the entry is extended by a tiled margin on each side, resized with
+/// the margin scaled down alike, and the margin is cropped, so the filter reads the tiled
+/// neighbors at the edges while the scale is exactly the one of the entry alone. The margin covers
+/// the filter support (three texels of the resized entry), rounded up to a length that scales to
+/// whole texels, and is at most the entry itself. The margins of the source and of the resized
+/// extent keep the ratio of the scale.
+#[cfg(feature = "std")]
+#[inline]
+fn resize_wrapped<P>(
+    src: &OwnedTexture<P>,
+    (width, height): (u32, u32),
+    filter: AtlasMipFilter,
+    mip_wrap: AtlasEntryMipOption,
+    mip_options: (AtlasMipColorSpace, bool, Option<SharpenParams>),
+) -> Result<OwnedTexture<P>, AtlasError>
+where
+    P: image::Pixel + 'static,
+{
+    if mip_wrap == AtlasEntryMipOption::Clamp {
+        return Ok(resize(src, width, height, filter, mip_options));
+    }
+
+    let margin = |src_len: u32, len: u32| {
+        let (mut a, mut b) = (src_len, len);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        let unit = src_len / a;
+        let margin = (3 * src_len)
+            .div_ceil(len)
+            .next_multiple_of(unit)
+            .min(src_len);
+        (margin, margin / unit * (len / a))
+    };
+    let (src_width, src_height) = src.dimensions();
+    let (src_margin_x, margin_x) = margin(src_width, width);
+    let (src_margin_y, margin_y) = margin(src_height, height);
+    let tiled = resample(
+        src,
+        mip_wrap,
+        false,
+        (src_margin_x, src_margin_y),
+        (src_width + src_margin_x * 2, src_height + src_margin_y * 2),
+        (src_width, src_height),
+        AtlasSampling::Nearest,
+    )?;
+    let resized = resize(
+        &tiled,
+        width + margin_x * 2,
+        height + margin_y * 2,
+        filter,
+        mip_options,
+    );
+    Ok(image::imageops::crop_imm(&resized, margin_x, margin_y, width, height).to_image())
+}
+
+/// Resizes a texture for mip map generation.
+///
+/// When sRGB color space or premultiplied alpha is requested, or the subpixel is float, the
+/// texture is resized through a linear float representation, otherwise it is resized as is.
+///
+/// `image::imageops::resize` clamps every value into `0.0..=1.0`, so the color channels of the
+/// linear representation are mapped from their value range into `0.0..=1.0` before resizing and
+/// mapped back after that.
+///
+/// With `sharpen`, the resized texture is sharpened before it is converted back, so the mask
+/// works in the same representation as the resize.
+#[cfg(feature = "std")]
+#[inline]
+fn resize<I>(
+    src: &I,
+    width: u32,
+    height: u32,
+    filter: AtlasMipFilter,
+    (color_space, premultiply_alpha, sharpen): (AtlasMipColorSpace, bool, Option<SharpenParams>),
+) -> OwnedTexture<I::Pixel>
+where
+    I: image::GenericImageView,
+    I::Pixel: 'static,
+{
+    let is_float = is_float::<<I::Pixel as Pixel>::Subpixel>();
+    if color_space == AtlasMipColorSpace::Linear && !premultiply_alpha && !is_float {
+        let mut resized = image::imageops::resize(src, width, height, filter.into());
+        if let Some(sharpen) = sharpen {
+            unsharp_mask(&mut resized, sharpen);
+        }
+        return resized;
+    }
+
+    let channel_count = I::Pixel::CHANNEL_COUNT as usize;
+    let color_count = channel_count - I::Pixel::HAS_ALPHA as usize;
+    let premultiply_alpha = premultiply_alpha && I::Pixel::HAS_ALPHA;
+
+    let mut linear = image::Rgba32FImage::from_fn(src.width(), src.height(), |x, y| {
+        let mut value = [0.0; 4];
+        for (i, &subpixel) in src.get_pixel(x, y).channels().iter().enumerate() {
+            value[i] = to_unit(subpixel);
+        }
+        for i in 0..color_count {
+            if color_space == AtlasMipColorSpace::Srgb {
+                value[i] = srgb_to_linear(value[i]);
+            }
+            if premultiply_alpha {
+                value[i] *= value[color_count];
+            }
+        }
+        image::Rgba(value)
+    });
+
+    let (mut min, mut max) = (0.0f32, 1.0f32);
+    for pixel in linear.pixels() {
+        for &value in &pixel.0[..color_count] {
+            min = min.min(value);
+            max = max.max(value);
+        }
+    }
+    let range = max - min;
+    if range != 1.0 {
+        for pixel in linear.pixels_mut() {
+            for value in &mut pixel.0[..color_count] {
+                *value = (*value - min) / range;
+            }
+        }
+    }
+
+    let mut linear = image::imageops::resize(&linear, width, height, filter.into());
+    if let Some(sharpen) = sharpen {
+        unsharp_mask(&mut linear, sharpen);
+    }
+
+    image::ImageBuffer::from_fn(width, height, |x, y| {
+        let mut value = linear.get_pixel(x, y).0;
+        for i in 0..color_count {
+            value[i] = value[i] * range + min;
+            if premultiply_alpha && value[color_count] > 0.0 {
+                value[i] /= value[color_count];
+            }
+            if color_space == AtlasMipColorSpace::Srgb {
+                value[i] = linear_to_srgb(value[i]);
+            }
+        }
+        let mut channels = [Primitive::DEFAULT_MIN_VALUE; 4];
+        for i in 0..channel_count {
+            channels[i] = from_unit(value[i]);
+        }
+        *I::Pixel::from_slice(&channels[..channel_count])
+    })
+}
+
+/// Applies an unsharp mask to the color channels of the texture.
+///
+/// The blur is clamped at the texture edges, and the sharpened values of integer subpixels are
+/// clamped into their value range.
+#[cfg(feature = "std")]
+#[inline]
+fn unsharp_mask<P>(texture: &mut image::ImageBuffer<P, Vec<P::Subpixel>>, sharpen: SharpenParams)
+where
+    P: image::Pixel + 'static,
+{
+    let blurred = image::imageops::blur(texture, 1.0);
+    let color_count = P::CHANNEL_COUNT as usize - P::HAS_ALPHA as usize;
+    for (pixel, blurred) in texture.pixels_mut().zip(blurred.pixels()) {
+        let channels = pixel.channels_mut().iter_mut().zip(blurred.channels());
+        for (value, &blurred) in channels.take(color_count) {
+            let (unit, blurred) = (to_unit(*value), to_unit(blurred));
+            *value = from_unit(unit + sharpen.amount * (unit - blurred));
+        }
+    }
+}
+
+/// Returns a signed distance field of the texture computed by 8SSEDT.
+///
+/// The mask is the alpha channel (or the first channel without alpha) of at least half, and each
+/// pixel is the distance to the nearest pixel outside the mask minus the one to the nearest pixel
+/// inside it, mapped by `spread` around 0.5.
+#[cfg(feature = "std")]
+#[inline]
+fn signed_distance_field<I>(
+    texture: &I,
+    sdf: SdfParams,
+) -> image::ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>
+where
+    I: image::GenericImageView,
+{
+    let (width, height) = texture.dimensions();
+    let channel = match I::Pixel::HAS_ALPHA {
+        true => I::Pixel::CHANNEL_COUNT as usize - 1,
+        false => 0,
+    };
+    let mask = texture
+        .pixels()
+        .map(|(_, _, pixel)| to_unit(pixel.channels()[channel]) >= 0.5)
+        .collect::<Vec<_>>();
+
+    let outside = distance_field(width, height, |i| mask[i]);
+    let inside = distance_field(width, height, |i| !mask[i]);
+
+    image::ImageBuffer::from_fn(width, height, |x, y| {
+        let i = (y * width + x) as usize;
+        let distance = inside[i] - outside[i];
+        let value = (0.5 + distance / (2.0 * sdf.spread)).clamp(0.0, 1.0);
+        let channels = [from_unit(value); 4];
+        *I::Pixel::from_slice(&channels[..I::Pixel::CHANNEL_COUNT as usize])
+    })
+}
+
+/// Returns a euclidean distance from each pixel to the nearest seed pixel by 8SSEDT.
+#[cfg(feature = "std")]
+#[inline]
+fn distance_field<F>(width: u32, height: u32, is_seed: F) -> Vec<f32>
+where
+    F: Fn(usize) -> bool,
+{
+    const FAR: (i64, i64) = (1 << 20, 1 << 20);
+
+    let (width, height) = (width as i64, height as i64);
+    let mut grid = (0..(width * height) as usize)
+        .map(|i| if is_seed(i) { (0, 0) } else { FAR })
+        .collect::<Vec<_>>();
+
+    let compare = |grid: &mut Vec<(i64, i64)>, x: i64, y: i64, ox: i64, oy: i64| {
+        let (nx, ny) = (x + ox, y + oy);
+        if nx < 0 || ny < 0 || nx >= width || ny >= height {
+            return;
+        }
+        let other = grid[(ny * width + nx) as usize];
+        let other = (other.0 + ox, other.1 + oy);
+        let current = &mut grid[(y * width + x) as usize];
+        if other.0 * other.0 + other.1 * other.1 < current.0 * current.0 + current.1 * current.1 {
+            *current = other;
+        }
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            compare(&mut grid, x, y, -1, 0);
+            compare(&mut grid, x, y, 0, -1);
+            compare(&mut grid, x, y, -1, -1);
+            compare(&mut grid, x, y, 1, -1);
+        }
+        for x in (0..width).rev() {
+            compare(&mut grid, x, y, 1, 0);
+        }
+    }
+
+    for y in (0..height).rev() {
+        for x in (0..width).rev() {
+            compare(&mut grid, x, y, 1, 0);
+            compare(&mut grid, x, y, 0, 1);
+            compare(&mut grid, x, y, -1, 1);
+            compare(&mut grid, x, y, 1, 1);
+        }
+        for x in 0..width {
+            compare(&mut grid, x, y, -1, 0);
+        }
+    }
+
+    grid.into_iter()
+        .map(|(dx, dy)| ((dx * dx + dy * dy) as f32).sqrt())
+        .collect()
+}
+
+/// Converts a subpixel into a value where `DEFAULT_MAX_VALUE` maps to `1.0`.
+#[cfg(feature = "std")]
+#[inline]
+fn to_unit<S: Primitive>(value: S) -> f32 {
+    value.to_f32().unwrap_or_default() / S::DEFAULT_MAX_VALUE.to_f32().unwrap_or(1.0)
+}
+
+/// Returns whether the subpixel is float, where `DEFAULT_MAX_VALUE` is `1.0`.
+#[cfg(feature = "std")]
+#[inline]
+fn is_float<S: Primitive>() -> bool {
+    S::DEFAULT_MAX_VALUE.to_f32() == Some(1.0)
+}
+
+/// Returns the index of the first entry which has a non-finite float subpixel.
+#[cfg(feature = "std")]
+#[inline]
+fn non_finite_entry<I>(entries: &[&I]) -> Option<usize>
+where
+    I: image::GenericImageView,
+{
+    if !is_float::<<I::Pixel as Pixel>::Subpixel>() {
+        return None;
+    }
+
+    entries.iter().position(|texture| {
+        texture.pixels().any(|(_, _, pixel)| {
+            let channels = pixel.channels();
+            !channels
+                .iter()
+                .all(|subpixel| subpixel.to_f32().is_some_and(f32::is_finite))
+        })
+    })
+}
+
+/// Converts a value where `1.0` maps to `DEFAULT_MAX_VALUE` into a subpixel.
+///
+/// Integer subpixels are rounded and saturated, float subpixels are kept as is.
+#[cfg(feature = "std")]
+#[inline]
+fn from_unit<S: Primitive>(value: f32) -> S {
+    let max = S::DEFAULT_MAX_VALUE.to_f32().unwrap_or(1.0);
+    let value = match max > 1.0 {
+        true => (value * max).round().clamp(0.0, max),
+        false => value * max,
+    };
+    NumCast::from(value).unwrap_or(S::DEFAULT_MIN_VALUE)
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn srgb_to_linear(value: f32) -> f32 {
+    match value <= 0.04045 {
+        true => value / 12.92,
+        false => ((value + 0.055) / 1.055).powf(2.4),
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn linear_to_srgb(value: f32) -> f32 {
+    match value <= 0.0031308 {
+        true => value * 12.92,
+        false => 1.055 * value.powf(1.0 / 2.4) - 0.055,
     }
+}
+
+/// A result of texture atlas generation.
+///
+/// - `page_count`: A output texture count.
+/// - `size`: A output texture width and height (same width and height, an upper bound of each
+///   page with `shrink_pages`).
+/// - `mip_level_count`: A mip map count of output texture (1 is no mip map).
+/// - `mip_start`: A mip level of the first mip map of each texture (the start of `mip_range`, 0
+///   otherwise).
+/// - `textures`: A vec of output texture.
+/// - `texcoord`: A vec of texcoord in output texture (same order as `entries`).
+/// - `reserved`: A vec of texcoord of blank region in output texture (same order as `reserve`).
+/// - `page_base`: A page index of the first output texture (`textures[page - page_base]`).
+/// - `block_waste`: A vec of pixels added by rounding up each entry to block multiples with
+///   `MipWithBlock` or `MipWithBlockRect` (same order as `entries`, 0 with other options).
+/// - `scale_factors`: A vec of scale applied to each entry by `downscale_oversized` (same order
+///   as `entries`, 1 for entries kept as is).
+/// - `alpha_coverage`: A vec of mean alpha of each entry per mip level with `MipWithBlock` or
+///   `MipWithBlockRect` (same order as `entries`, empty with other options).
+/// - `free_rects`: A vec of free regions per page (`free_rects[page - page_base]`), collected
+///   only with `collect_free_rects`.
+/// - `packed_rects`: A vec of raw placements of the packer, collected only with
+///   `collect_packed_rects`.
+/// - `sources`: A vec of copies of entries (same order as `entries`), kept only with
+///   `retain_sources`.
+/// - `baked_rotations`: A vec of texcoords of the copies rotated clockwise by 90, 180 and 270
+///   degrees (same order as `entries`, `None` for entries without `bake_rotations`).
+///
+/// `block_waste` excludes the half-block gutter, so it is 0 for entries whose width and height
+/// are multiples of block size. It guides resizing source textures to efficient dimensions.
+///
+/// `alpha_coverage[i][mip_level]` is averaged over the texcoord region of entry `i` scaled down to
+/// the mip level, and is 1 for pixels without alpha channel. It helps to scale the alpha-test
+/// threshold per mip level so alpha-tested entries keep their coverage at distance. An entry
+/// without `generate_mips` has only the base mip level. With `mip_start`, `alpha_coverage[i][k]`
+/// is of mip level `mip_start + k` (and empty for entries without `generate_mips`).
+///
+/// `free_rects` are the disjoint free sections of the packer after generation. A region covers
+/// raw space, so an entry placed into it at runtime needs room for its own padding (or gutter),
+/// and all of its pixels are blank in every mip map. It is not updated by [insert](Atlas::insert).
+///
+/// `packed_rects` are the placements before the padding (or block) adjustment of texcoords, in
+/// blocks with `MipWithBlock` and `MipWithBlockRect` and in pixels otherwise. A placement covers
+/// the padding (or gutter) of its entry, and entries sharing a location by `deduplicate` have a
+/// placement only for the first one. The placements of rotated copies of `bake_rotations` have
+/// the index of their entry. [insert](Atlas::insert) appends the placement of each new
+/// entry.
+///
+/// The atlas retains the packing state of its generation, so more entries can be appended later
+/// by [insert](Atlas::insert).
+///
+/// With `serde` feature, the atlas including pixel data of all mip maps can be serialized. The
+/// packing state and `sources` are not serialized, so a deserialized atlas does not support
+/// insertion.
+#[cfg(feature = "std")]
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "P::Subpixel: serde::Serialize",
+        deserialize = "P::Subpixel: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Atlas<P: image::Pixel> {
+    pub page_count: u32,
+    pub size: u32,
+    pub mip_level_count: u32,
+    pub mip_start: u32,
+    pub textures: Vec<Texture<P>>,
+    pub texcoords: Vec<Texcoord>,
+    pub reserved: Vec<Texcoord>,
+    pub page_base: u32,
+    pub block_waste: Vec<u32>,
+    pub scale_factors: Vec<f32>,
+    pub alpha_coverage: Vec<Vec<f32>>,
+    pub free_rects: Vec<Vec<Texcoord>>,
+    pub packed_rects: Vec<PackedRect>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub sources: Vec<AtlasEntry<image::ImageBuffer<P, Vec<P::Subpixel>>>>,
+    pub baked_rotations: Vec<Option<[Texcoord; 3]>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    packer: Option<AtlasPacker<P>>,
+}
 
-    let mut target_bins = BTreeMap::new();
-    for i in 0..max_page_count {
-        target_bins.insert(i, rectangle_pack::TargetBin::new(size, size, 1));
+#[cfg(feature = "std")]
+impl<P: image::Pixel> Atlas<P> {
+    /// Appends an entry into free space of existing pages, or a new page up to `max_page_count`
+    /// (without limit when it is zero). A new page keeps the `occupied` regions of the description
+    /// free as the generation does.
+    ///
+    /// The entry is laid out with the same options as the generation (padding, block and extrude)
+    /// and its texcoord is appended to `texcoords`. With `allow_rotation`, it is transposed only
+    /// when it fits in a page only transposed. Deduplication is not applied. With
+    /// `retain_sources`, a copy of the entry is appended to `sources`.
+    ///
+    /// With `Mip` and `MipWithPadding`, all mip maps of the page are regenerated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The atlas has no packing state (e.g. deserialized atlas).
+    /// - The entry has zero width or height.
+    /// - The entry is larger than `size`.
+    /// - A position or extent of the layout overflows `u32`.
+    /// - An internal invariant is violated (a bug of this crate).
+    /// - Packing error occurred.
+    pub fn insert<I>(&mut self, entry: &AtlasEntry<I>) -> Result<Texcoord, AtlasError>
+    where
+        I: image::GenericImageView<Pixel = P>,
+        P: 'static,
+    {
+        let packer = self.packer.as_ref().ok_or(AtlasError::NoPackingState)?;
+        let retain_sources = packer.retain_sources;
+        let texcoord = match packer.prepare(entry)? {
+            Some(entry) => self.insert_prepared(&entry),
+            None => self.insert_prepared(entry),
+        }?;
+        if retain_sources {
+            self.sources.push(entry.map_texture(owned_texture));
+        }
+        Ok(texcoord)
     }
 
-    let locations = rectangle_pack::pack_rects(
-        &rects,
-        &mut target_bins,
-        &rectangle_pack::volume_heuristic,
-        &rectangle_pack::contains_smallest_box,
-    )?;
+    /// Packs and writes an entry prepared by the packing state.
+    ///
+    /// Pages whose distinct texcoords reach `max_entries_per_page` are set aside while packing.
+    /// When the pages are unbounded, new pages are marked by `occupied` as the generation does,
+    /// and a page without occupied regions always has room for the entry.
+    #[inline]
+    fn insert_prepared<I>(&mut self, entry: &AtlasEntry<I>) -> Result<Texcoord, AtlasError>
+    where
+        I: image::GenericImageView<Pixel = P>,
+        P: 'static,
+    {
+        let packer = self.packer.as_mut().ok_or(AtlasError::NoPackingState)?;
+        let index = self.texcoords.len();
 
-    let mut page_count = 0;
-    let mut texcoords = vec![Texcoord::default(); entries.len()];
-    for (&i, &(page, location)) in locations.packed_locations() {
-        page_count = u32::max(page_count, page + 1);
+        let has_mip = !matches!(
+            packer.mip,
+            AtlasMipOption::NoMip | AtlasMipOption::NoMipWithPadding(_)
+        );
+        if has_mip && non_finite_entry(&[&entry.texture]).is_some() {
+            return Err(AtlasError::NonFiniteSubpixel { index });
+        }
+
+        let view = &views(std::slice::from_ref(entry))[0];
+        if view.width() == 0 || view.height() == 0 {
+            return Err(AtlasError::ZeroSizedEntry { index });
+        }
+
+        let (border, block) = packer.layout();
+        let (block_width, block_height) = block;
+        let is_block = block != (1, 1);
+
+        let slack = if is_block {
+            0
+        } else {
+            packer.origin_alignment - 1
+        };
+        let rect_of = |rotated: bool| {
+            entry_rect(dimensions(&**view, rotated), border, block)
+                .and_then(|rect| {
+                    let width = rect.width().checked_add(slack)?;
+                    let height = rect.height().checked_add(slack)?;
+                    Some(rectangle_pack::RectToInsert::new(width, height, 1))
+                })
+                .ok_or(AtlasError::Overflow)
+        };
+        let fits = |rect: &rectangle_pack::RectToInsert| {
+            rect.width() * block_width <= self.size && rect.height() * block_height <= self.size
+        };
+        let (mut rotated, mut rect) = (false, rect_of(false)?);
+        if packer.allow_rotation && !fits(&rect) {
+            let transposed = rect_of(true)?;
+            if fits(&transposed) {
+                (rotated, rect) = (true, transposed);
+            }
+        }
+        let (width, height) = dimensions(&**view, rotated);
+        if !fits(&rect) {
+            return Err(AtlasError::EntryTooLarge {
+                index,
+                width: rect.width() * block_width,
+                height: rect.height() * block_height,
+                max: self.size,
+            });
+        }
+
+        let bin_size = (self.size / block_width, self.size / block_height);
+        let pack = |bins: &mut BTreeMap<_, _>| {
+            pack_rects(
+                &[(index, rect)],
+                &[],
+                bins,
+                bin_size,
+                packer.pack_heuristic,
+                packer.pack_algorithm,
+                false,
+            )
+        };
+        let full = match packer.max_entries_per_page {
+            Some(cap) => {
+                let mut texcoords = self
+                    .texcoords
+                    .iter()
+                    .chain(&self.reserved)
+                    .chain(self.baked_rotations.iter().flatten().flatten())
+                    .collect::<Vec<_>>();
+                texcoords.sort_unstable();
+                texcoords.dedup();
+                let mut counts = BTreeMap::new();
+                for texcoord in texcoords {
+                    *counts.entry(texcoord.page - self.page_base).or_insert(0) += 1;
+                }
+                counts
+                    .into_iter()
+                    .filter(|&(_, count)| count >= cap)
+                    .filter_map(|(page, _)| Some((page, packer.bins.remove(&page)?)))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        let gutter = if is_block {
+            (block_width >> 1, block_height >> 1)
+        } else {
+            (border, border)
+        };
+        let locations = match pack(&mut packer.bins) {
+            Err(_) if packer.unbounded => loop {
+                let page = (packer.bins.len() + full.len()) as u32;
+                let regions = occupied_regions(
+                    &packer.occupied,
+                    self.page_base.checked_add(page),
+                    bin_size,
+                    block,
+                    gutter,
+                );
+                let mut bin = rectangle_pack::TargetBin::new(bin_size.0, bin_size.1, 1);
+                if !regions.is_empty() {
+                    mark_regions(&mut bin, bin_size, &regions);
+                }
+                packer.bins.insert(page, bin);
+                match pack(&mut packer.bins) {
+                    Err(_) if !regions.is_empty() => {}
+                    result => break result,
+                }
+            },
+            result => result,
+        };
+        packer.bins.extend(full);
+        let locations = locations?;
+        let (page, location) = locations[&index];
+        let (location, (shift_x, shift_y)) = if is_block {
+            let shift = anchor_shift(entry.anchor, (width, height), location, block);
+            (location, shift)
+        } else {
+            let location = aligned_location(location, border, packer.origin_alignment);
+            (location, (border, border))
+        };
+        let page_index = self.page_base.checked_add(page);
+        let texcoord = page_index.and_then(|page| {
+            placed_texcoord(
+                page,
+                location,
+                block,
+                (shift_x, shift_y),
+                (width, height),
+                self.size,
+            )
+        });
+        let waste = if is_block {
+            block_waste(width, height, block)
+        } else {
+            Some(0)
+        };
+        let (Some(texcoord), Some(waste)) = (texcoord, waste) else {
+            return Err(AtlasError::Overflow);
+        };
+
+        while self.textures.len() <= page as usize {
+            let texture = match packer.background {
+                Some(background) => {
+                    Texture::from_pixel(self.size, self.mip_level_count, background)
+                }
+                None => Texture::new(self.size, self.mip_level_count),
+            };
+            self.textures.push(Texture {
+                padding: border,
+                ..texture
+            });
+        }
+        self.page_count = self.textures.len() as u32;
+
+        let texture = &mut self.textures[page as usize];
+        packer.blit(
+            texture,
+            entry,
+            view,
+            rotated,
+            (location, block),
+            (shift_x, shift_y),
+        )?;
+        let coverage = if is_block {
+            let mip_level_count = if entry.generate_mips {
+                self.mip_level_count
+            } else {
+                1
+            };
+            alpha_coverage(texture, &texcoord, 0..mip_level_count)
+        } else {
+            Vec::new()
+        };
 
         let texcoord = Texcoord {
-            page,
-            min_x: location.x() + padding,
-            min_y: location.y() + padding,
-            max_x: location.x() + location.width() - padding,
-            max_y: location.y() + location.height() - padding,
-            size,
+            rotated,
+            trim_offset_x: view.offsets().0,
+            trim_offset_y: view.offsets().1,
+            source_width: entry.texture.width(),
+            source_height: entry.texture.height(),
+            ..texcoord
         };
-        texcoords[i] = texcoord;
+        self.texcoords.push(texcoord);
+        self.block_waste.push(waste);
+        self.scale_factors.push(1.0);
+        self.alpha_coverage.push(coverage);
+        self.baked_rotations.push(None);
+        if packer.collect_packed_rects {
+            self.packed_rects.push(PackedRect {
+                index,
+                page: texcoord.page,
+                x: location.x(),
+                y: location.y(),
+                w: location.width(),
+                h: location.height(),
+            });
+        }
+        Ok(texcoord)
     }
 
-    let mip_level_count = size.ilog2() + 1;
-    let mut textures = vec![Texture::new(size, mip_level_count); page_count as usize];
-    for (&i, &(page, location)) in locations.packed_locations() {
-        let entry = &entries[i];
+    /// Replaces the pixels of an entry in its existing region, without packing.
+    ///
+    /// The entry is laid out with the same options and rotation as the replaced one, and its
+    /// texcoord, `block_waste` and `alpha_coverage` are updated. The whole region of the originally
+    /// packed entry (including padding or block rounding) is cleared to `background` first, so a
+    /// smaller entry leaves no stale pixels, and the region is kept for later updates. With `Mip`
+    /// and `MipWithPadding`, all mip maps of the page are regenerated. Entries sharing the region
+    /// by `deduplicate` keep their texcoords, so they are not expected to be updated. With
+    /// `retain_sources`, the copy in `sources` is replaced by the entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The atlas has no packing state (e.g. deserialized atlas).
+    /// - `index` is out of `texcoords`.
+    /// - The entry has zero width or height.
+    /// - The entry with its padding does not fit in the region of the replaced entry.
+    /// - The entry has a non-finite float subpixel with mip map generation.
+    pub fn update_entry<I>(&mut self, index: usize, entry: &AtlasEntry<I>) -> Result<(), AtlasError>
+    where
+        I: image::GenericImageView<Pixel = P>,
+        P: 'static,
+    {
+        let packer = self.packer.as_ref().ok_or(AtlasError::NoPackingState)?;
+        let retain_sources = packer.retain_sources;
+        match packer.prepare(entry)? {
+            Some(entry) => self.update_prepared(index, &entry),
+            None => self.update_prepared(index, entry),
+        }?;
+        if let Some(source) = self.sources.get_mut(index).filter(|_| retain_sources) {
+            *source = entry.map_texture(owned_texture);
+        }
+        Ok(())
+    }
 
-        let src = resample(
-            &entry.texture,
-            entry.mip,
-            padding,
-            padding,
-            location.width(),
-            location.height(),
+    /// Writes an entry again from its copy in `sources`, e.g. after its texture is modified in
+    /// place.
+    ///
+    /// The entry is written in the same way as [update_entry](Atlas::update_entry).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `index` is out of `sources` (e.g. the atlas is generated without
+    /// `retain_sources`), or in the same condition as [update_entry](Atlas::update_entry).
+    pub fn rebake_entry(&mut self, index: usize) -> Result<(), AtlasError>
+    where
+        P: 'static,
+    {
+        let packer = self.packer.as_ref().ok_or(AtlasError::NoPackingState)?;
+        let source = self
+            .sources
+            .get(index)
+            .ok_or(AtlasError::NoEntry { index })?;
+        match packer.prepare(source)? {
+            Some(entry) => self.update_prepared(index, &entry),
+            None => {
+                let source = std::mem::take(&mut self.sources[index]);
+                let result = self.update_prepared(index, &source);
+                self.sources[index] = source;
+                result
+            }
+        }
+    }
+
+    /// Writes an entry into its region laid out by [create_atlas_planned](create_atlas_planned).
+    ///
+    /// The entry is written in the same way as [update_entry](Atlas::update_entry), so it is
+    /// rotated as planned and may be smaller than the planned size. Baking an entry again replaces
+    /// its pixels.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the same condition as [update_entry](Atlas::update_entry).
+    #[inline]
+    pub fn bake_entry<I>(&mut self, index: usize, entry: &AtlasEntry<I>) -> Result<(), AtlasError>
+    where
+        I: image::GenericImageView<Pixel = P>,
+        P: 'static,
+    {
+        self.update_entry(index, entry)
+    }
+
+    /// Writes an entry prepared by the packing state into the region of the entry at `index`.
+    ///
+    /// An entry is shifted from its location by the padding, or by less than a block. The region
+    /// keeps the extent of the originally packed entry over updates, and an entry packed into a
+    /// shared block keeps its cells of sub-block size.
+    #[inline]
+    fn update_prepared<I>(&mut self, index: usize, entry: &AtlasEntry<I>) -> Result<(), AtlasError>
+    where
+        I: image::GenericImageView<Pixel = P>,
+        P: 'static,
+    {
+        let packer = self.packer.as_mut().ok_or(AtlasError::NoPackingState)?;
+        let old = *self
+            .texcoords
+            .get(index)
+            .ok_or(AtlasError::NoEntry { index })?;
+
+        let has_mip = !matches!(
+            packer.mip,
+            AtlasMipOption::NoMip | AtlasMipOption::NoMipWithPadding(_)
         );
+        if has_mip && non_finite_entry(&[&entry.texture]).is_some() {
+            return Err(AtlasError::NonFiniteSubpixel { index });
+        }
 
-        let target = &mut textures[page as usize].mip_maps[0];
-        image::imageops::replace(target, &src, location.x() as i64, location.y() as i64);
+        let view = &views(std::slice::from_ref(entry))[0];
+        let rotated = old.rotated;
+        let (width, height) = dimensions(&**view, rotated);
+        if width == 0 || height == 0 {
+            return Err(AtlasError::ZeroSizedEntry { index });
+        }
+
+        let (border, block) = packer.layout();
+        let block = packer
+            .sub_blocks
+            .get(&index)
+            .map_or(block, |&sub_block_size| (sub_block_size, sub_block_size));
+        let (block_width, block_height) = block;
+        let is_block = block != (1, 1);
+        let (x, y) = if is_block {
+            (old.min_x / block_width, old.min_y / block_height)
+        } else {
+            (old.min_x - border, old.min_y - border)
+        };
+        let slot = match packer.slots.get(&index) {
+            Some(&slot) => slot,
+            None => {
+                let old_extent = (old.max_x - old.min_x, old.max_y - old.min_y);
+                let slot = entry_rect(old_extent, border, block).ok_or(AtlasError::Overflow)?;
+                (slot.width(), slot.height())
+            }
+        };
+        let rect = entry_rect((width, height), border, block).ok_or(AtlasError::Overflow)?;
+        if rect.width() > slot.0 || rect.height() > slot.1 {
+            return Err(AtlasError::SlotTooSmall {
+                index,
+                width: rect.width() * block_width,
+                height: rect.height() * block_height,
+                slot_width: slot.0 * block_width,
+                slot_height: slot.1 * block_height,
+            });
+        }
+        packer.slots.insert(index, slot);
+
+        let location = packed_location(x, y, rect);
+        let shift = if is_block {
+            anchor_shift(entry.anchor, (width, height), location, block)
+        } else {
+            (border, border)
+        };
+        let texcoord = placed_texcoord(old.page, location, block, shift, (width, height), old.size);
+        let waste = if is_block {
+            block_waste(width, height, block)
+        } else {
+            Some(0)
+        };
+        let (Some(texcoord), Some(waste)) = (texcoord, waste) else {
+            return Err(AtlasError::Overflow);
+        };
+
+        let texture = &mut self.textures[(old.page - self.page_base) as usize];
+        for (mip_level, mip_map) in texture.mip_maps.iter_mut().enumerate() {
+            let width = (slot.0 * block_width) >> mip_level;
+            let height = (slot.1 * block_height) >> mip_level;
+            let blank = match packer.background {
+                Some(background) => image::ImageBuffer::from_pixel(width, height, background),
+                None => image::ImageBuffer::new(width, height),
+            };
+            let x = (x * block_width) >> mip_level;
+            let y = (y * block_height) >> mip_level;
+            image::imageops::replace(mip_map, &blank, x as i64, y as i64);
+        }
+        packer.blit(texture, entry, view, rotated, (location, block), shift)?;
+        if let Some(coverage) = self.alpha_coverage.get_mut(index).filter(|_| is_block) {
+            let mip_level_count = if entry.generate_mips {
+                self.mip_level_count
+            } else {
+                1
+            };
+            *coverage = alpha_coverage(texture, &texcoord, 0..mip_level_count);
+        }
+
+        self.texcoords[index] = Texcoord {
+            rotated,
+            trim_offset_x: view.offsets().0,
+            trim_offset_y: view.offsets().1,
+            source_width: entry.texture.width(),
+            source_height: entry.texture.height(),
+            ..texcoord
+        };
+        if let Some(block_waste) = self.block_waste.get_mut(index) {
+            *block_waste = waste;
+        }
+        Ok(())
+    }
+
+    /// Combines atlases into one atlas whose pages are the pages of each atlas in order.
+    ///
+    /// The pages of the combined atlas start from `page_base` of the first atlas, and `page` of
+    /// every texcoord is offset by the pages of the preceding atlases. `texcoords`, `reserved`,
+    /// `block_waste`, `scale_factors`, `alpha_coverage` and `baked_rotations` are concatenated in
+    /// order, so an entry of a later atlas is indexed after all entries of the former ones.
+    /// `free_rects` and `sources` are kept only when every atlas collected them, and
+    /// `packed_rects` are dropped.
+    ///
+    /// Atlases are packed independently, so the combined atlas has no packing state and does not
+    /// support [insert](Atlas::insert). An empty vec returns an empty atlas.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - An atlas has a different `size`, `mip_level_count` or `mip_start` from the first one.
+    /// - A page index of the combined atlas overflows `u32`.
+    pub fn concat(atlases: Vec<Atlas<P>>) -> Result<Atlas<P>, AtlasError> {
+        let (size, mip_level_count, mip_start, page_base) =
+            atlases.first().map_or((0, 0, 0, 0), |atlas| {
+                (
+                    atlas.size,
+                    atlas.mip_level_count,
+                    atlas.mip_start,
+                    atlas.page_base,
+                )
+            });
+        if let Some(index) = atlases.iter().position(|atlas| {
+            (atlas.size, atlas.mip_level_count, atlas.mip_start)
+                != (size, mip_level_count, mip_start)
+        }) {
+            return Err(AtlasError::IncompatibleAtlas { index });
+        }
+        let keep_free_rects = atlases
+            .iter()
+            .all(|atlas| atlas.free_rects.len() == atlas.textures.len());
+        let keep_sources = atlases
+            .iter()
+            .all(|atlas| atlas.sources.len() == atlas.texcoords.len());
+
+        let mut concat = Atlas {
+            page_count: 0,
+            size,
+            mip_level_count,
+            mip_start,
+            textures: Vec::new(),
+            texcoords: Vec::new(),
+            reserved: Vec::new(),
+            page_base,
+            block_waste: Vec::new(),
+            scale_factors: Vec::new(),
+            alpha_coverage: Vec::new(),
+            free_rects: Vec::new(),
+            packed_rects: Vec::new(),
+            sources: Vec::new(),
+            baked_rotations: Vec::new(),
+            packer: None,
+        };
+        for atlas in atlases {
+            let offset = concat.textures.len() as u32;
+            let renumber = |texcoord: Texcoord| {
+                let page = (texcoord.page - atlas.page_base)
+                    .checked_add(offset)?
+                    .checked_add(page_base)?;
+                Some(Texcoord { page, ..texcoord })
+            };
+            let renumber_all = |texcoords: &[Texcoord]| {
+                texcoords
+                    .iter()
+                    .map(|&texcoord| renumber(texcoord))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(AtlasError::Overflow)
+            };
+
+            concat.texcoords.extend(renumber_all(&atlas.texcoords)?);
+            concat.reserved.extend(renumber_all(&atlas.reserved)?);
+            if keep_free_rects {
+                for free_rects in &atlas.free_rects {
+                    concat.free_rects.push(renumber_all(free_rects)?);
+                }
+            }
+            if keep_sources {
+                concat.sources.extend(atlas.sources);
+            }
+            concat.block_waste.extend(atlas.block_waste);
+            concat.scale_factors.extend(atlas.scale_factors);
+            concat.alpha_coverage.extend(atlas.alpha_coverage);
+            let renumber_baked = |texcoords: [Texcoord; 3]| match texcoords.map(renumber) {
+                [Some(a), Some(b), Some(c)] => Ok([a, b, c]),
+                _ => Err(AtlasError::Overflow),
+            };
+            for &baked in &atlas.baked_rotations {
+                concat
+                    .baked_rotations
+                    .push(baked.map(renumber_baked).transpose()?);
+            }
+            concat.textures.extend(atlas.textures);
+            concat.page_count = u32::try_from(concat.textures.len())
+                .ok()
+                .filter(|&page_count| page_base.checked_add(page_count).is_some())
+                .ok_or(AtlasError::Overflow)?;
+        }
+        Ok(concat)
+    }
+
+    /// Returns a total byte length of all mip maps across all textures.
+    #[inline]
+    pub fn memory_usage(&self) -> u64 {
+        let subpixel_size = std::mem::size_of::<P::Subpixel>() as u64;
+        self.textures
+            .iter()
+            .flat_map(|texture| &texture.mip_maps)
+            .map(|mip_map| mip_map.as_raw().len() as u64 * subpixel_size)
+            .sum()
     }
 
-    for mip_level in 1..mip_level_count {
-        let size = size >> mip_level;
+    /// Returns packing statistics computed from texcoords.
+    ///
+    /// Entries sharing the same region (e.g. deduplicated entries) are counted once, and rotated
+    /// copies of `bake_rotations` are counted as entries. Pages may be cropped by
+    /// `shrink_pages`, so the area of each page is taken from its texture.
+    pub fn stats(&self) -> AtlasStats {
+        let mut regions = self
+            .texcoords
+            .iter()
+            .chain(&self.reserved)
+            .chain(self.baked_rotations.iter().flatten().flatten())
+            .map(|t| (t.page, t.min_x, t.min_y, t.max_x, t.max_y))
+            .collect::<Vec<_>>();
+        regions.sort_unstable();
+        regions.dedup();
 
-        for page in 0..page_count {
-            let src = &textures[page as usize].mip_maps[0];
+        let page_areas = (0..self.page_count as usize)
+            .map(|i| {
+                let size = self
+                    .textures
+                    .get(i)
+                    .map_or(self.size, |texture| texture.size << self.mip_start);
+                size as u64 * size as u64
+            })
+            .collect::<Vec<_>>();
+        let mut per_page_area = vec![0u64; self.page_count as usize];
+        for &(page, min_x, min_y, max_x, max_y) in &regions {
+            per_page_area[(page - self.page_base) as usize] +=
+                (max_x - min_x) as u64 * (max_y - min_y) as u64;
+        }
 
-            let mip_map = image::imageops::resize(src, size, size, filter.into());
+        let used_area = per_page_area.iter().sum::<u64>();
+        let total_area = page_areas.iter().sum::<u64>();
+        let occupancy = if total_area == 0 {
+            0.0
+        } else {
+            (used_area as f64 / total_area as f64) as f32
+        };
+        let per_page = per_page_area
+            .iter()
+            .zip(&page_areas)
+            .map(|(&area, &page_area)| (area as f64 / page_area as f64) as f32)
+            .collect();
 
-            let target = &mut textures[page as usize].mip_maps[mip_level as usize];
-            image::imageops::replace(target, &mip_map, 0, 0);
+        AtlasStats {
+            used_area,
+            total_area,
+            occupancy,
+            per_page,
         }
     }
 
-    Ok(Atlas {
-        page_count,
-        size,
-        mip_level_count,
-        textures,
-        texcoords,
-    })
-}
+    /// Returns a ratio of used area to total area of all pages.
+    #[inline]
+    pub fn occupancy(&self) -> f32 {
+        self.stats().occupancy
+    }
 
-#[inline]
-fn create_atlas_mip_with_block<I>(
-    max_page_count: u32,
-    size: u32,
-    filter: AtlasMipFilter,
-    block_size: u32,
-    entries: &[AtlasEntry<I>],
-) -> Result<Atlas<I::Pixel>, AtlasError>
-where
-    I: image::GenericImage,
-    I::Pixel: 'static,
-{
-    if max_page_count == 0 {
-        return Err(AtlasError::ZeroMaxPageCount);
+    /// Returns normalized texcoords as `[min_x, min_y, max_x, max_y]` and their page indices.
+    ///
+    /// Both vecs are in the same order as `texcoords`, which is the shape for GPU storage buffers.
+    #[inline]
+    pub fn texcoord_table_f32(&self) -> (Vec<[f32; 4]>, Vec<u32>) {
+        self.texcoords
+            .iter()
+            .map(|texcoord| {
+                let texcoord = texcoord.to_f32();
+                let rect = [
+                    texcoord.min_x,
+                    texcoord.min_y,
+                    texcoord.max_x,
+                    texcoord.max_y,
+                ];
+                (rect, texcoord.page)
+            })
+            .unzip()
     }
 
-    if !size.is_power_of_two() {
-        return Err(AtlasError::InvalidSize(size));
+    /// Returns entry indices paired with their texcoords, sorted by the order of
+    /// [Texcoord](Texcoord) and then by index.
+    ///
+    /// The order depends only on the layout, so dumps of the metadata are deterministic and
+    /// diffable between builds.
+    #[inline]
+    pub fn sorted_texcoords(&self) -> Vec<(usize, Texcoord)> {
+        let mut texcoords = self
+            .texcoords
+            .iter()
+            .copied()
+            .enumerate()
+            .collect::<Vec<_>>();
+        texcoords.sort_unstable_by_key(|&(index, texcoord)| (texcoord, index));
+        texcoords
     }
 
-    if !block_size.is_power_of_two() {
-        return Err(AtlasError::InvalidBlockSize(block_size));
+    /// Returns an iterator of entry index and texcoord placed on the page.
+    #[inline]
+    pub fn texcoords_on_page(&self, page: u32) -> impl Iterator<Item = (usize, &Texcoord)> {
+        self.texcoords
+            .iter()
+            .enumerate()
+            .filter(move |(_, texcoord)| texcoord.page == page)
     }
 
-    if entries.is_empty() {
-        return Err(AtlasError::ZeroEntry);
+    /// Returns the page of each entry (same order as `texcoords`).
+    #[inline]
+    pub fn page_indices(&self) -> Vec<u32> {
+        self.texcoords
+            .iter()
+            .map(|texcoord| texcoord.page)
+            .collect()
     }
 
-    let padding = block_size >> 1;
+    /// Returns entry indices grouped by page, in ascending order within each page.
+    ///
+    /// The outer vec has `page_count` elements, and element `i` holds the entries on page
+    /// `page_base + i`, so draws can be batched per page without sorting `texcoords`.
+    #[inline]
+    pub fn entries_by_page(&self) -> Vec<Vec<usize>> {
+        let mut entries = vec![Vec::new(); self.page_count as usize];
+        for (index, texcoord) in self.texcoords.iter().enumerate() {
+            entries[(texcoord.page - self.page_base) as usize].push(index);
+        }
+        entries
+    }
 
-    let mut rects = rectangle_pack::GroupedRectsToPlace::<_, ()>::new();
-    for (i, entry) in entries.iter().enumerate() {
-        let rect = rectangle_pack::RectToInsert::new(
-            ((entry.texture.width() + block_size) as f32 / block_size as f32).ceil() as u32,
-            ((entry.texture.height() + block_size) as f32 / block_size as f32).ceil() as u32,
-            1,
-        );
-        rects.push_rect(i, None, rect);
+    /// Returns the texcoords of the entry rotated clockwise by 0, 90, 180 and 270 degrees, or
+    /// `None` if the entry does not exist or is packed without `bake_rotations`.
+    ///
+    /// Element `i` samples the entry turned by `i` quarter turns, so a sprite drawn at a fixed
+    /// rotation picks its texcoord by the turn count.
+    #[inline]
+    pub fn rotation_texcoords(&self, index: usize) -> Option<[Texcoord; 4]> {
+        let texcoord = *self.texcoords.get(index)?;
+        let [a, b, c] = (*self.baked_rotations.get(index)?)?;
+        Some([texcoord, a, b, c])
     }
 
-    let bin_size = size / block_size;
-    let mut target_bins = BTreeMap::new();
-    for i in 0..max_page_count {
-        target_bins.insert(i, rectangle_pack::TargetBin::new(bin_size, bin_size, 1));
+    /// Returns an iterator of entry index and texcoord of every rotated copy of `bake_rotations`.
+    #[inline]
+    fn baked_texcoords(&self) -> impl Iterator<Item = (usize, &Texcoord)> {
+        self.baked_rotations
+            .iter()
+            .enumerate()
+            .flat_map(|(index, baked)| {
+                baked
+                    .iter()
+                    .flatten()
+                    .map(move |texcoord| (index, texcoord))
+            })
     }
 
-    let locations = rectangle_pack::pack_rects(
-        &rects,
-        &mut target_bins,
-        &rectangle_pack::volume_heuristic,
-        &rectangle_pack::contains_smallest_box,
-    )?;
+    /// Returns the entry index whose texcoord contains the pixel on the page.
+    ///
+    /// Only the texcoord rect is hit, so padding, extruded border and block gutter do not belong
+    /// to any entry. Deduplicated entries share one rect, and the smallest index is returned.
+    /// This is a linear scan over `texcoords`.
+    #[inline]
+    pub fn entry_at(&self, page: u32, x: u32, y: u32) -> Option<usize> {
+        self.texcoords_on_page(page)
+            .find(|(_, texcoord)| {
+                (texcoord.min_x..texcoord.max_x).contains(&x)
+                    && (texcoord.min_y..texcoord.max_y).contains(&y)
+            })
+            .map(|(i, _)| i)
+    }
 
-    let mut page_count = 0;
-    let mut texcoords = vec![Texcoord::default(); entries.len()];
-    for (&i, &(page, location)) in locations.packed_locations() {
-        page_count = u32::max(page_count, page + 1);
+    /// Checks that the texcoords of entries and reserved regions neither overlap nor leave their
+    /// pages.
+    ///
+    /// Each texcoord is expanded by `padding` of its page (the gutter at mip 0), so it also checks
+    /// that neighbors do not intrude into padding, extruded border or block gutter. Reserved
+    /// regions are indexed after entries (`texcoords.len() + i`), rotated copies of
+    /// `bake_rotations` have the index of their entry, and deduplicated entries sharing one rect
+    /// are not reported. It is meant for tests and debug builds. Each page is swept from left to
+    /// right, so only rects overlapping on x are compared.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A texcoord with its padding is out of its page.
+    /// - Two texcoords with their padding overlap.
+    pub fn verify_layout(&self) -> Result<(), AtlasError> {
+        let mut rects = Vec::new();
+        let texcoords = self.texcoords.iter().chain(&self.reserved).enumerate();
+        for (index, texcoord) in texcoords.chain(self.baked_texcoords()) {
+            let texture = texcoord
+                .page
+                .checked_sub(self.page_base)
+                .and_then(|page| self.textures.get(page as usize));
+            let rect = texture.and_then(|texture| {
+                let size = u32::min(texcoord.size, texture.size);
+                let min_x = texcoord.min_x.checked_sub(texture.padding)?;
+                let min_y = texcoord.min_y.checked_sub(texture.padding)?;
+                let max_x = texcoord.max_x.checked_add(texture.padding)?;
+                let max_y = texcoord.max_y.checked_add(texture.padding)?;
+                (max_x <= size && max_y <= size).then_some((min_x, min_y, max_x, max_y))
+            });
+            let rect = rect.ok_or(AtlasError::OutOfPage { index })?;
+            rects.push((texcoord.page, rect, index, texcoord));
+        }
 
-        let texcoord = Texcoord {
-            page,
-            min_x: location.x() * block_size + padding,
-            min_y: location.y() * block_size + padding,
-            max_x: location.x() * block_size + padding + entries[i].texture.width(),
-            max_y: location.y() * block_size + padding + entries[i].texture.height(),
-            size,
-        };
-        texcoords[i] = texcoord;
+        rects.sort_by_key(|&(page, (min_x, ..), index, _)| (page, min_x, index));
+        for (i, &(page, (_, min_y, max_x, max_y), a, texcoord)) in rects.iter().enumerate() {
+            let others =
+                rects[i + 1..]
+                    .iter()
+                    .take_while(|&&(other_page, (other_min_x, ..), ..)| {
+                        other_page == page && other_min_x < max_x
+                    });
+            for &(_, (_, other_min_y, _, other_max_y), b, other) in others {
+                let shared = (
+                    texcoord.min_x,
+                    texcoord.min_y,
+                    texcoord.max_x,
+                    texcoord.max_y,
+                ) == (other.min_x, other.min_y, other.max_x, other.max_y);
+                if other_min_y < max_y && min_y < other_max_y && !shared {
+                    return Err(AtlasError::Overlap {
+                        a: a.min(b),
+                        b: a.max(b),
+                    });
+                }
+            }
+        }
+        Ok(())
     }
 
-    let mip_level_count = block_size.ilog2() + 1;
-    let mut textures = vec![Texture::new(size, mip_level_count); page_count as usize];
-    for (&i, &(page, location)) in locations.packed_locations() {
-        let entry = &entries[i];
+    /// Returns an iterator of page index, texture and entries placed on the page.
+    #[inline]
+    pub fn pages(&self) -> impl Iterator<Item = (u32, &Texture<P>, Vec<(usize, &Texcoord)>)> {
+        (self.page_base..)
+            .zip(&self.textures)
+            .map(|(page, texture)| (page, texture, self.texcoords_on_page(page).collect()))
+    }
 
-        let src = resample(
-            &entry.texture,
-            entry.mip,
-            padding,
-            padding,
-            location.width() * block_size,
-            location.height() * block_size,
-        );
+    /// Returns a borrowed view of the region of the entry in its page at the mip level.
+    ///
+    /// The region is the texcoord of the entry scaled by [at_mip](Texcoord::at_mip), so it covers
+    /// every texel the entry touches at the mip level. The view borrows the mip map without
+    /// copying, e.g. to inspect or post-process baked entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `index` is out of `texcoords`.
+    /// - `mip_level` is out of the mip levels kept in the page (from `mip_start`).
+    /// - The region is out of its page.
+    pub fn entry_view(
+        &self,
+        index: usize,
+        mip_level: u32,
+    ) -> Result<image::SubImage<&OwnedTexture<P>>, AtlasError> {
+        let texcoord = self
+            .texcoords
+            .get(index)
+            .ok_or(AtlasError::NoEntry { index })?;
+        let texture = texcoord
+            .page
+            .checked_sub(self.page_base)
+            .and_then(|page| self.textures.get(page as usize))
+            .ok_or(AtlasError::OutOfPage { index })?;
+        let mip_map = mip_level
+            .checked_sub(self.mip_start)
+            .and_then(|mip_level| texture.mip_maps.get(mip_level as usize))
+            .ok_or({
+                AtlasError::MipLevelOutOfRange {
+                    mip_level,
+                    mip_level_count: texture.mip_maps.len() as u32,
+                }
+            })?;
+
+        let rect = texcoord.at_mip(mip_level);
+        if rect.max_x > mip_map.width() || rect.max_y > mip_map.height() {
+            return Err(AtlasError::OutOfPage { index });
+        }
+        let (width, height) = (rect.max_x - rect.min_x, rect.max_y - rect.min_y);
+        Ok(image::imageops::crop_imm(
+            mip_map, rect.min_x, rect.min_y, width, height,
+        ))
+    }
+
+    /// Returns the raw subpixels of every page at the mip level, in page order.
+    ///
+    /// Each slice is a tightly packed row-major mip map, so the slices are the layers of an array
+    /// texture in the order of a `write_texture` call per layer (or of one call after
+    /// concatenation). All layers have the same extent unless the atlas is built with
+    /// `shrink_pages`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `mip_level` is out of the `mip_level_count` mip levels from
+    /// `mip_start` (or the mip maps of a page cropped by `shrink_pages`).
+    pub fn as_array_layers(&self, mip_level: u32) -> Result<Vec<&[P::Subpixel]>, AtlasError> {
+        let out_of_range = |mip_level_count| AtlasError::MipLevelOutOfRange {
+            mip_level,
+            mip_level_count,
+        };
+        let index = mip_level
+            .checked_sub(self.mip_start)
+            .filter(|&index| index < self.mip_level_count)
+            .ok_or(out_of_range(self.mip_level_count))?;
+
+        self.textures
+            .iter()
+            .map(|texture| {
+                let mip_map = texture.mip_maps.get(index as usize);
+                let mip_map = mip_map.ok_or_else(|| out_of_range(texture.mip_maps.len() as u32))?;
+                Ok(mip_map.as_raw().as_slice())
+            })
+            .collect()
+    }
 
-        for mip_level in 0..mip_level_count {
-            let width = src.width() >> mip_level;
-            let height = src.height() >> mip_level;
-            let mip_map = image::imageops::resize(&src, width, height, filter.into());
+    /// Returns an image of the first mip map of every page laid out in a grid of `columns`.
+    ///
+    /// Pages are placed in rows from the top-left in page order. `columns` is clamped to
+    /// `1..=page_count`, so a single page is returned as is. Cells after the last page are filled
+    /// with `background` of the generation (zero without it, or after deserialization). The first
+    /// mip map is the mip level `mip_start`, so each cell is `size >> mip_start` square.
+    pub fn contact_sheet(&self, columns: u32) -> image::ImageBuffer<P, Vec<P::Subpixel>> {
+        let page_count = self.textures.len() as u32;
+        let columns = columns.clamp(1, page_count.max(1));
+        let rows = page_count.div_ceil(columns);
 
-            let target = &mut textures[page as usize].mip_maps[mip_level as usize];
-            let x = location.x() as i64 * (block_size >> mip_level) as i64;
-            let y = location.y() as i64 * (block_size >> mip_level) as i64;
-            image::imageops::replace(target, &mip_map, x, y);
+        let cell = self.size >> self.mip_start;
+        let width = columns * cell;
+        let height = rows * cell;
+        let mut sheet = match self.packer.as_ref().and_then(|packer| packer.background) {
+            Some(background) => image::ImageBuffer::from_pixel(width, height, background),
+            None => image::ImageBuffer::new(width, height),
+        };
+        for (i, texture) in (0..).zip(&self.textures) {
+            let Some(mip_map) = texture.mip_maps.first() else {
+                continue;
+            };
+            let x = (i % columns * cell) as i64;
+            let y = (i / columns * cell) as i64;
+            image::imageops::replace(&mut sheet, mip_map, x, y);
         }
+        sheet
     }
+}
 
-    Ok(Atlas {
-        page_count,
-        size,
-        mip_level_count,
-        textures,
-        texcoords,
-    })
+/// A packing statistics of texture atlas.
+///
+/// - `used_area`: A total pixel area covered by entries.
+/// - `total_area`: A total pixel area of all pages (`page_count * size * size` unless shrunk).
+/// - `occupancy`: A ratio of `used_area` to `total_area`.
+/// - `per_page`: A ratio of used area to page area for each page.
+#[derive(Clone, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AtlasStats {
+    pub used_area: u64,
+    pub total_area: u64,
+    pub occupancy: f32,
+    pub per_page: Vec<f32>,
 }
 
-#[inline]
-#[rustfmt::skip]
-fn resample<I>(
-    src: &I,
-    mip: AtlasEntryMipOption,
-    shift_x: u32,
-    shift_y: u32,
-    width: u32,
-    height: u32,
-) -> image::ImageBuffer<I::Pixel, Vec<<I::Pixel as image::Pixel>::Subpixel>>
+#[cfg(feature = "std")]
+impl<P> Atlas<P>
 where
-    I: image::GenericImage,
+    P: image::PixelWithColorType,
+    [P::Subpixel]: EncodableLayout,
 {
-    let mut target = image::ImageBuffer::new(width, height);
-    match mip {
-        AtlasEntryMipOption::Clamp => {
-            for x in 0..width {
-                for y in 0..height {
-                    let sx = (x as i32 - shift_x as i32).max(0).min(src.width() as i32 - 1);
-                    let sy = (y as i32 - shift_y as i32).max(0).min(src.height() as i32 - 1);
-                    *target.get_pixel_mut(x, y) = src.get_pixel(sx as u32, sy as u32);
-                }
-            }
-        }
-        AtlasEntryMipOption::Repeat => {
-            for x in 0..width {
-                for y in 0..height {
-                    let sx = (x as i32 - shift_x as i32).rem_euclid(src.width() as i32);
-                    let sy = (y as i32 - shift_y as i32).rem_euclid(src.height() as i32);
-                    *target.get_pixel_mut(x, y) = src.get_pixel(sx as u32, sy as u32);
-                }
+    /// Writes every mip map of every texture into `dir` as `{prefix}-{page}-{mip}.png`.
+    ///
+    /// It is available with `png` feature, which enables the PNG encoder.
+    #[cfg(feature = "png")]
+    pub fn save_pages(&self, dir: &path::Path, prefix: &str) -> io::Result<()> {
+        self.save_pages_with_format(dir, prefix, image::ImageFormat::Png)
+    }
+
+    /// Writes every mip map of every texture into `dir` as `{prefix}-{page}-{mip}.{ext}` in the
+    /// format, where `ext` is the primary extension of the format (e.g. `exr`). `mip` is the mip
+    /// level, so it starts from `mip_start`.
+    ///
+    /// The encoder of `image` is chosen by the format, and each format accepts only some pixel
+    /// types: PNG stores integer subpixels, while HDR (Radiance) stores `Rgb<f32>` and OpenEXR
+    /// stores `Rgb<f32>` and `Rgba<f32>`. An unsupported combination (e.g. `Rgba<f32>` as PNG) or
+    /// a format whose encoder is not enabled returns an error of `io::ErrorKind::Unsupported`
+    /// before any file is written, since each mip map is encoded into memory first. Encoders are
+    /// enabled by `png`, `hdr` and `exr` features.
+    pub fn save_pages_with_format(
+        &self,
+        dir: &path::Path,
+        prefix: &str,
+        format: image::ImageFormat,
+    ) -> io::Result<()> {
+        let extension = format.extensions_str().first().copied().unwrap_or_default();
+        for (page, texture) in (self.page_base..).zip(&self.textures) {
+            for (mip_level, mip_map) in (self.mip_start..).zip(&texture.mip_maps) {
+                let path = dir.join(format!("{}-{}-{}.{}", prefix, page, mip_level, extension));
+
+                let mut bytes = io::Cursor::new(Vec::new());
+                mip_map
+                    .write_to(&mut bytes, format)
+                    .map_err(|err| match err {
+                        image::ImageError::IoError(err) => err,
+                        image::ImageError::Unsupported(err) => {
+                            io::Error::new(io::ErrorKind::Unsupported, err)
+                        }
+                        err => io::Error::other(err),
+                    })?;
+                fs::write(path, bytes.into_inner())?;
             }
         }
-        AtlasEntryMipOption::Mirror => {
-            for x in 0..width {
-                for y in 0..height {
-                    let xx = (x as i32 - shift_x as i32).div_euclid(src.width() as i32);
-                    let yy = (y as i32 - shift_y as i32).div_euclid(src.height() as i32);
-                    let mut sx = (x as i32 - shift_x as i32).rem_euclid(src.width() as i32);
-                    let mut sy = (y as i32 - shift_y as i32).rem_euclid(src.height() as i32);
-                    if xx & 1 == 0 { sx = src.width() as i32 - 1 - sx; }
-                    if yy & 1 == 0 { sy = src.height() as i32 - 1 - sy; }
-                    *target.get_pixel_mut(x, y) = src.get_pixel(sx as u32, sy as u32);
-                }
-            }
+        Ok(())
+    }
+
+    /// Writes every texture into `dir` as `{prefix}-{page}.ktx2` including its full mip chain.
+    pub fn save_ktx(&self, dir: &path::Path, prefix: &str) -> io::Result<()> {
+        for (page, texture) in (self.page_base..).zip(&self.textures) {
+            let path = dir.join(format!("{}-{}.ktx2", prefix, page));
+            let mut writer = io::BufWriter::new(fs::File::create(path)?);
+            texture.write_ktx2(&mut writer)?;
+            writer.flush()?;
         }
+        Ok(())
     }
-    target
 }
 
-/// A result of texture atlas generation.
-///
-/// - `page_count`: A output texture count.
-/// - `size`: A output texture width and height (same width and height).
-/// - `mip_level_count`: A mip map count of output texture (1 is no mip map).
-/// - `textures`: A vec of output texture.
-/// - `texcoord`: A vec of texcoord in output texture (same order as `entries`).
-#[derive(Clone, Default)]
-pub struct Atlas<P: image::Pixel> {
-    pub page_count: u32,
-    pub size: u32,
-    pub mip_level_count: u32,
-    pub textures: Vec<Texture<P>>,
-    pub texcoords: Vec<Texcoord>,
+#[cfg(feature = "std")]
+impl<P> Atlas<P>
+where
+    P: image::Pixel,
+    image::DynamicImage: From<image::ImageBuffer<P, Vec<P::Subpixel>>>,
+{
+    /// Converts every texture into dynamic images, indexed by page (from `page_base`) and mip
+    /// level.
+    ///
+    /// The pixel type must have a variant of [DynamicImage](image::DynamicImage) (e.g. `Rgba<u8>`
+    /// or `Rgb<f32>`). The buffers are moved without copy.
+    #[inline]
+    pub fn into_dynamic_pages(self) -> Vec<Vec<image::DynamicImage>> {
+        self.textures
+            .into_iter()
+            .map(Texture::into_dynamic)
+            .collect()
+    }
+}
+
+#[cfg(feature = "json")]
+impl<P: image::Pixel> Atlas<P> {
+    /// Returns a JSON document of the frames of entries in the hash format of TexturePacker.
+    ///
+    /// Each entry is a frame keyed by its name in `names` (or its index beyond `names`) with
+    /// `frame` in pixels of the page, `rotated`, `trimmed`, `spriteSourceSize` and `sourceSize`
+    /// from its texcoord, and `page` of its page index. `meta` holds `size` of the first page,
+    /// `pageSizes` of every page (smaller than `size` when cropped by `shrink_pages`) and `pages`,
+    /// the page count.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TransposedFrame` if an entry is stored transposed by `allow_rotation`, since the
+    /// rotated frames of TexturePacker are rotated clockwise instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use image_atlas::*;
+    ///
+    /// let atlas = create_atlas(&AtlasDescriptor {
+    ///     max_page_count: 1,
+    ///     size: 64,
+    ///     entries: &[AtlasEntry::new(image::RgbaImage::new(16, 16))],
+    ///     ..Default::default()
+    /// })
+    /// .unwrap();
+    /// let json = atlas.to_frames_json(&["player.png"]).unwrap();
+    /// ```
+    pub fn to_frames_json(&self, names: &[&str]) -> Result<String, AtlasError> {
+        if let Some(index) = self.texcoords.iter().position(|texcoord| texcoord.rotated) {
+            return Err(AtlasError::TransposedFrame { index });
+        }
+
+        let frames = self
+            .texcoords
+            .iter()
+            .enumerate()
+            .map(|(i, texcoord)| {
+                let name = names
+                    .get(i)
+                    .map_or_else(|| i.to_string(), |name| name.to_string());
+                let (w, h) = (
+                    texcoord.max_x - texcoord.min_x,
+                    texcoord.max_y - texcoord.min_y,
+                );
+                let source_size = (texcoord.source_width, texcoord.source_height);
+                let frame = serde_json::json!({
+                    "frame": { "x": texcoord.min_x, "y": texcoord.min_y, "w": w, "h": h },
+                    "rotated": false,
+                    "trimmed": (w, h) != source_size,
+                    "spriteSourceSize": {
+                        "x": texcoord.trim_offset_x,
+                        "y": texcoord.trim_offset_y,
+                        "w": w,
+                        "h": h,
+                    },
+                    "sourceSize": { "w": source_size.0, "h": source_size.1 },
+                    "page": texcoord.page,
+                });
+                (name, frame)
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let page_sizes = (0..self.page_count as usize)
+            .map(|i| {
+                let size = self
+                    .textures
+                    .get(i)
+                    .map_or(self.size, |texture| texture.size << self.mip_start);
+                serde_json::json!({ "w": size, "h": size })
+            })
+            .collect::<Vec<_>>();
+
+        let document = serde_json::json!({
+            "frames": frames,
+            "meta": {
+                "app": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+                "size": page_sizes.first(),
+                "pageSizes": page_sizes,
+                "pages": self.page_count,
+            },
+        });
+        Ok(serde_json::to_string_pretty(&document).expect("JSON value is serializable"))
+    }
 }
 
+#[cfg(feature = "std")]
 impl<P> fmt::Debug for Atlas<P>
 where
     P: image::Pixel + fmt::Debug,
@@ -545,24 +7356,299 @@ where
             .field("page_count", &self.page_count)
             .field("size", &self.size)
             .field("mip_level_count", &self.mip_level_count)
+            .field("mip_start", &self.mip_start)
             .field("textures", &self.textures)
             .field("texcoords", &self.texcoords)
+            .field("reserved", &self.reserved)
+            .field("page_base", &self.page_base)
+            .field("block_waste", &self.block_waste)
+            .field("scale_factors", &self.scale_factors)
+            .field("alpha_coverage", &self.alpha_coverage)
+            .field("free_rects", &self.free_rects)
+            .field("packed_rects", &self.packed_rects)
+            .field("sources", &self.sources)
+            .field("baked_rotations", &self.baked_rotations)
             .finish()
     }
 }
 
+#[cfg(feature = "std")]
+impl<P: image::Pixel> fmt::Display for Atlas<P> {
+    /// Writes a one-line summary of the atlas with the occupancy of [stats](Atlas::stats).
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Atlas {{ pages: {}, size: {}, mips: {}, entries: {}, occupancy: {:.1}% }}",
+            self.page_count,
+            self.size,
+            self.mip_level_count,
+            self.texcoords.len(),
+            self.occupancy() * 100.0
+        )
+    }
+}
+
+/// A packing state of texture atlas generation retained for insertion.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+struct AtlasPacker<P: image::Pixel> {
+    mip: AtlasMipOption,
+    mip_color_space: AtlasMipColorSpace,
+    premultiply_alpha: bool,
+    mip_sharpen: Option<SharpenParams>,
+    allow_rotation: bool,
+    pack_heuristic: PackHeuristic,
+    pack_algorithm: PackAlgorithm,
+    sdf: Option<SdfParams>,
+    extrude: u32,
+    origin_alignment: u32,
+    bleed_alpha: bool,
+    sampling: AtlasSampling,
+    channel_swizzle: Option<[usize; 4]>,
+    background: Option<P>,
+    unbounded: bool,
+    collect_packed_rects: bool,
+    retain_sources: bool,
+    max_entries_per_page: Option<u32>,
+    occupied: Vec<Texcoord>,
+    bins: BTreeMap<u32, rectangle_pack::TargetBin>,
+    slots: BTreeMap<usize, (u32, u32)>,
+    sub_blocks: BTreeMap<usize, u32>,
+}
+
+#[cfg(feature = "std")]
+impl<P: image::Pixel> AtlasPacker<P> {
+    #[inline]
+    fn new<I>(desc: &AtlasDescriptor<'_, I>, bins: BTreeMap<u32, rectangle_pack::TargetBin>) -> Self
+    where
+        I: image::GenericImageView<Pixel = P>,
+    {
+        Self {
+            mip: desc.mip,
+            mip_color_space: desc.mip_color_space,
+            premultiply_alpha: desc.premultiply_alpha,
+            mip_sharpen: desc.mip_sharpen,
+            allow_rotation: desc.allow_rotation,
+            pack_heuristic: desc.pack_heuristic,
+            pack_algorithm: desc.pack_algorithm,
+            sdf: desc.sdf,
+            extrude: desc.extrude,
+            origin_alignment: desc.origin_alignment,
+            bleed_alpha: desc.bleed_alpha,
+            sampling: desc.sampling,
+            channel_swizzle: desc.channel_swizzle,
+            background: desc.background,
+            unbounded: desc.max_page_count == 0,
+            collect_packed_rects: desc.collect_packed_rects,
+            retain_sources: desc.retain_sources,
+            max_entries_per_page: desc.max_entries_per_page,
+            occupied: desc.occupied.to_vec(),
+            bins,
+            slots: BTreeMap::new(),
+            sub_blocks: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the padding (including extruded border) or half-block gutter, and the block width
+    /// and height (1 without block).
+    #[inline]
+    fn layout(&self) -> (u32, (u32, u32)) {
+        mip_layout(self.mip, self.extrude)
+    }
+
+    /// Returns a copy of entry transformed as the generation does (signed distance field, channel
+    /// swizzle and resizing, in this order), or `None` when nothing applies.
+    #[inline]
+    fn prepare<I>(
+        &self,
+        entry: &AtlasEntry<I>,
+    ) -> Result<Option<AtlasEntry<OwnedTexture<P>>>, AtlasError>
+    where
+        I: image::GenericImageView<Pixel = P>,
+    {
+        if self.sdf.is_none() && self.channel_swizzle.is_none() && resized(entry).is_none() {
+            return Ok(None);
+        }
+
+        let mut entry = entry.map_texture(|texture| match self.sdf {
+            Some(sdf) => signed_distance_field(texture, sdf),
+            None => owned_texture(texture),
+        });
+        if let Some(swizzle) = self.channel_swizzle {
+            entry = entry.map_texture(|texture| swizzle_channels(texture, swizzle));
+        }
+        if resized(&entry).is_some() {
+            entry = resize_entry(&entry, self.sampling)?;
+        }
+        Ok(Some(entry))
+    }
+
+    /// Writes an entry view into its location of the page by the mip option.
+    ///
+    /// `unit` is the block (or sub-block) size the location is measured in with block options.
+    #[inline]
+    fn blit<I>(
+        &self,
+        texture: &mut Texture<P>,
+        entry: &AtlasEntry<I>,
+        view: &image::SubImage<&I>,
+        rotated: bool,
+        (location, unit): (rectangle_pack::PackedLocation, (u32, u32)),
+        shift: (u32, u32),
+    ) -> Result<(), AtlasError>
+    where
+        I: image::GenericImageView<Pixel = P>,
+        P: 'static,
+    {
+        let (border, _) = self.layout();
+        match self.mip {
+            AtlasMipOption::NoMip | AtlasMipOption::NoMipWithPadding(_) => {
+                let padding = border - self.extrude;
+                blit_with_padding(
+                    &mut texture.mip_maps[0],
+                    &**view,
+                    entry.wraps().0,
+                    rotated,
+                    padding,
+                    self.extrude,
+                    location,
+                )?;
+                if self.bleed_alpha {
+                    bleed_alpha(&mut texture.mip_maps[0], location);
+                }
+            }
+            AtlasMipOption::Mip(filter) | AtlasMipOption::MipWithPadding(filter, _) => {
+                blit_with_padding(
+                    &mut texture.mip_maps[0],
+                    &**view,
+                    entry.wraps().0,
+                    rotated,
+                    border,
+                    0,
+                    location,
+                )?;
+                generate_mip_maps(
+                    texture,
+                    filter,
+                    self.mip_color_space,
+                    self.premultiply_alpha,
+                    self.mip_sharpen,
+                );
+            }
+            AtlasMipOption::MipWithBlock(filter, _)
+            | AtlasMipOption::MipWithBlockRect(filter, ..) => {
+                let extent = dimensions(&**view, rotated);
+                let src = resample(
+                    &**view,
+                    entry.mip,
+                    rotated,
+                    (0, 0),
+                    extent,
+                    extent,
+                    AtlasSampling::Nearest,
+                )?;
+                blit_with_block(
+                    texture,
+                    &src,
+                    entry.wraps(),
+                    block_cell(location, unit),
+                    shift,
+                    (entry.generate_mips).then(|| entry.mip_filter.unwrap_or(filter)),
+                    (
+                        self.mip_color_space,
+                        self.premultiply_alpha,
+                        self.mip_sharpen,
+                    ),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// A output texture entry of texture atlas.
 ///
 /// - `size`: A output texture width and height (same width and height).
 /// - `mip_level_count`: A mip map count of output texture (1 is no mip map).
 /// - `mip_maps`: A vec of mip map.
+/// - `padding`: A minimum gutter in pixels between each entry and the others at mip level 0.
+///
+/// `padding` is the padding (including extruded border) of padding options, and the half-block
+/// gutter of `MipWithBlock` (of the smaller side with `MipWithBlockRect`). It halves at each mip
+/// level (see [mip_padding](Texture::mip_padding)), so it tells up to which mip level an entry
+/// can be sampled with filtering or wrapping without reading its neighbors. A texture created by
+/// [new](Texture::new) has no padding.
+///
+/// With `serde` feature, each mip map is serialized as its dimensions and raw subpixels.
+#[cfg(feature = "std")]
 #[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "P::Subpixel: serde::Serialize",
+        deserialize = "P::Subpixel: serde::Deserialize<'de>"
+    ))
+)]
 pub struct Texture<P: image::Pixel> {
     pub size: u32,
     pub mip_level_count: u32,
+    #[cfg_attr(feature = "serde", serde(with = "serde_mip_maps"))]
     pub mip_maps: Vec<image::ImageBuffer<P, Vec<P::Subpixel>>>,
+    pub padding: u32,
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+mod serde_mip_maps {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    type MipMaps<P> = Vec<image::ImageBuffer<P, Vec<<P as image::Pixel>::Subpixel>>>;
+
+    #[derive(Serialize)]
+    struct MipMapRef<'a, S> {
+        width: u32,
+        height: u32,
+        data: &'a [S],
+    }
+
+    #[derive(Deserialize)]
+    struct MipMap<S> {
+        width: u32,
+        height: u32,
+        data: Vec<S>,
+    }
+
+    pub fn serialize<P, S>(mip_maps: &MipMaps<P>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        P: image::Pixel,
+        P::Subpixel: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(mip_maps.iter().map(|mip_map| MipMapRef {
+            width: mip_map.width(),
+            height: mip_map.height(),
+            data: mip_map.as_raw(),
+        }))
+    }
+
+    pub fn deserialize<'de, P, D>(deserializer: D) -> Result<MipMaps<P>, D::Error>
+    where
+        P: image::Pixel,
+        P::Subpixel: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Vec::<MipMap<P::Subpixel>>::deserialize(deserializer)?
+            .into_iter()
+            .map(|mip_map| {
+                image::ImageBuffer::from_raw(mip_map.width, mip_map.height, mip_map.data)
+                    .ok_or_else(|| D::Error::custom("mip map data does not match its dimensions"))
+            })
+            .collect()
+    }
 }
 
+#[cfg(feature = "std")]
 impl<P: image::Pixel> Texture<P> {
     #[inline]
     pub fn new(size: u32, mip_level_count: u32) -> Self {
@@ -574,10 +7660,236 @@ impl<P: image::Pixel> Texture<P> {
             size,
             mip_level_count,
             mip_maps,
+            padding: 0,
+        }
+    }
+
+    /// Returns a new texture whose all mip maps are filled with `pixel`.
+    #[inline]
+    pub fn from_pixel(size: u32, mip_level_count: u32, pixel: P) -> Self {
+        let mip_maps = (0..mip_level_count)
+            .map(|mip_level| size >> mip_level)
+            .map(|size| image::ImageBuffer::from_pixel(size, size, pixel))
+            .collect::<Vec<_>>();
+        Self {
+            size,
+            mip_level_count,
+            mip_maps,
+            padding: 0,
+        }
+    }
+
+    /// Returns a gutter in pixels around each entry at the mip level.
+    ///
+    /// Each mip level halves the gutter, rounding down, so it becomes zero at coarse mip levels.
+    #[inline]
+    pub fn mip_padding(&self, mip_level: u32) -> u32 {
+        self.padding.checked_shr(mip_level).unwrap_or(0)
+    }
+
+    /// Returns the raw subpixels of every mip map concatenated from mip level 0.
+    ///
+    /// Each mip map is tightly packed row-major, with `width * P::CHANNEL_COUNT` subpixels per row
+    /// and no row padding, and mip level `i` follows the levels before it without a gap. This is
+    /// the layout of `write_texture` of `wgpu` for a whole mip chain with `bytes_per_row` of the
+    /// tight row size per level. Copies from a buffer require rows aligned to 256 bytes, which
+    /// this layout does not provide.
+    #[inline]
+    pub fn packed_subpixels(&self) -> Vec<P::Subpixel> {
+        let len = self
+            .mip_maps
+            .iter()
+            .map(|mip_map| mip_map.as_raw().len())
+            .sum();
+        let mut subpixels = Vec::with_capacity(len);
+        for mip_map in &self.mip_maps {
+            subpixels.extend_from_slice(mip_map.as_raw());
+        }
+        subpixels
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P: image::Pixel<Subpixel = u8>> Texture<P> {
+    /// Returns the bytes of every mip map concatenated from mip level 0.
+    ///
+    /// See the [packed_subpixels](Texture::packed_subpixels) for the layout.
+    #[inline]
+    pub fn packed_bytes(&self) -> Vec<u8> {
+        self.packed_subpixels()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P> Texture<P>
+where
+    P: image::PixelWithColorType,
+    [P::Subpixel]: EncodableLayout,
+{
+    /// Writes the texture as a KTX2 file including all mip maps.
+    ///
+    /// Supported pixels are 8-bit and 16-bit unsigned normalized, and 32-bit float (RGB and RGBA
+    /// only).
+    ///
+    /// The data format descriptor has a single basic block, and the level data is stored from the
+    /// smallest mip map to the largest one, each aligned to the least common multiple of the
+    /// texel size and 4.
+    pub fn write_ktx2<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        let (vk_format, is_float) = ktx2_format(P::COLOR_TYPE).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "pixel type has no corresponding KTX2 format",
+            )
+        })?;
+
+        let channel_count = P::CHANNEL_COUNT as u32;
+        let type_size = std::mem::size_of::<P::Subpixel>() as u32;
+        let texel_size = type_size * channel_count;
+        let level_count = self.mip_maps.len() as u32;
+
+        let alignment = match texel_size % 4 {
+            0 => texel_size,
+            2 => texel_size * 2,
+            _ => texel_size * 4,
+        } as u64;
+
+        let dfd_offset = 80 + 24 * level_count;
+        let dfd_length = 4 + 24 + 16 * channel_count;
+
+        let mut level_index = vec![(0u64, 0u64); self.mip_maps.len()];
+        let mut offset = (dfd_offset + dfd_length) as u64;
+        for (mip_level, mip_map) in self.mip_maps.iter().enumerate().rev() {
+            offset = offset.div_ceil(alignment) * alignment;
+            let length = mip_map.as_raw().as_bytes().len() as u64;
+            level_index[mip_level] = (offset, length);
+            offset += length;
+        }
+
+        let mut header = Vec::with_capacity((dfd_offset + dfd_length) as usize);
+        header.extend_from_slice(&KTX2_IDENTIFIER);
+        for value in [
+            vk_format,
+            type_size,
+            self.size,
+            self.size,
+            0,
+            0,
+            1,
+            level_count,
+            0,
+            dfd_offset,
+            dfd_length,
+            0,
+            0,
+        ] {
+            header.extend_from_slice(&value.to_le_bytes());
+        }
+        header.extend_from_slice(&0u64.to_le_bytes());
+        header.extend_from_slice(&0u64.to_le_bytes());
+        for &(offset, length) in &level_index {
+            header.extend_from_slice(&offset.to_le_bytes());
+            header.extend_from_slice(&length.to_le_bytes());
+            header.extend_from_slice(&length.to_le_bytes());
+        }
+
+        let bit_length = type_size * 8;
+        for value in [
+            dfd_length,
+            0,
+            2 | ((24 + 16 * channel_count) << 16),
+            1 | (1 << 8) | (1 << 16),
+            0,
+            texel_size,
+            0,
+        ] {
+            header.extend_from_slice(&value.to_le_bytes());
         }
+        for channel in 0..channel_count {
+            let channel_type = if P::HAS_ALPHA && channel == channel_count - 1 {
+                15
+            } else {
+                channel
+            };
+            let (qualifiers, lower, upper) = if is_float {
+                (0xc0, (-1.0f32).to_bits(), 1.0f32.to_bits())
+            } else {
+                (0x00, 0, u32::MAX >> (32 - bit_length))
+            };
+            let sample = (channel * bit_length)
+                | ((bit_length - 1) << 16)
+                | ((channel_type | qualifiers) << 24);
+            for value in [sample, 0, lower, upper] {
+                header.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        writer.write_all(&header)?;
+
+        let mut position = header.len() as u64;
+        for (mip_level, mip_map) in self.mip_maps.iter().enumerate().rev() {
+            let (offset, length) = level_index[mip_level];
+            writer.write_all(&vec![0; (offset - position) as usize])?;
+
+            let bytes = mip_map.as_raw().as_bytes();
+            if cfg!(target_endian = "big") {
+                let mut bytes = bytes.to_vec();
+                bytes
+                    .chunks_exact_mut(type_size as usize)
+                    .for_each(|chunk| chunk.reverse());
+                writer.write_all(&bytes)?;
+            } else {
+                writer.write_all(bytes)?;
+            }
+            position = offset + length;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xab, 0x4b, 0x54, 0x58, 0x20, 0x32, 0x30, 0xbb, 0x0d, 0x0a, 0x1a, 0x0a,
+];
+
+#[cfg(feature = "std")]
+#[rustfmt::skip]
+#[inline]
+fn ktx2_format(color_type: image::ExtendedColorType) -> Option<(u32, bool)> {
+    match color_type {
+        image::ExtendedColorType::L8 => Some((9, false)),
+        image::ExtendedColorType::La8 => Some((16, false)),
+        image::ExtendedColorType::Rgb8 => Some((23, false)),
+        image::ExtendedColorType::Rgba8 => Some((37, false)),
+        image::ExtendedColorType::L16 => Some((70, false)),
+        image::ExtendedColorType::La16 => Some((77, false)),
+        image::ExtendedColorType::Rgb16 => Some((84, false)),
+        image::ExtendedColorType::Rgba16 => Some((91, false)),
+        image::ExtendedColorType::Rgb32F => Some((106, true)),
+        image::ExtendedColorType::Rgba32F => Some((109, true)),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P> Texture<P>
+where
+    P: image::Pixel,
+    image::DynamicImage: From<image::ImageBuffer<P, Vec<P::Subpixel>>>,
+{
+    /// Converts the mip maps into dynamic images in order of mip level.
+    ///
+    /// The pixel type must have a variant of [DynamicImage](image::DynamicImage) (e.g. `Rgba<u8>`
+    /// or `Rgb<f32>`). The buffers are moved without copy.
+    #[inline]
+    pub fn into_dynamic(self) -> Vec<image::DynamicImage> {
+        self.mip_maps
+            .into_iter()
+            .map(image::DynamicImage::from)
+            .collect()
     }
 }
 
+#[cfg(feature = "std")]
 impl<P> fmt::Debug for Texture<P>
 where
     P: image::Pixel + fmt::Debug,
@@ -589,10 +7901,77 @@ where
             .field("size", &self.size)
             .field("mip_level_count", &self.mip_level_count)
             .field("mip_maps", &self.mip_maps)
+            .field("padding", &self.padding)
             .finish()
     }
 }
 
+/// A output 3D texture atlas.
+///
+/// - `page_count`: A page count of output volumes.
+/// - `size`: A width, height and depth of each volume.
+/// - `pages`: A vec of volume, each of which is a vec of `size` slices ordered by z.
+/// - `texcoords`: A vec of element coordinate of each brick (same order as `entries`).
+///
+/// See the [create_volume_atlas](create_volume_atlas) for generation.
+#[cfg(feature = "std")]
+#[derive(Clone, Default, Debug)]
+pub struct VolumeAtlas<P: image::Pixel> {
+    pub page_count: u32,
+    pub size: u32,
+    pub pages: Vec<Vec<image::ImageBuffer<P, Vec<P::Subpixel>>>>,
+    pub texcoords: Vec<VolumeTexcoord>,
+}
+
+/// A layout of texture atlas without pixels.
+///
+/// - `page_count`: A page count of output textures.
+/// - `size`: A width and height of each page.
+/// - `mip_level_count`: A mip map count of each page.
+/// - `page_base`: A page index of the first output texture.
+/// - `texcoords`: A vec of texcoord of each size (same order as `sizes`).
+/// - `reserved`: A vec of texcoord of blank region (same order as `reserve`).
+/// - `free_rects`: A vec of free regions per page, collected only with `collect_free_rects`.
+/// - `packed_rects`: A vec of raw placements of the packer, collected only with
+///   `collect_packed_rects`. The index of a size is its position in `sizes`, and the index of
+///   `reserve` region `i` is `sizes.len() + i`.
+///
+/// See the [plan_atlas](plan_atlas) for computation.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AtlasLayout {
+    pub page_count: u32,
+    pub size: u32,
+    pub mip_level_count: u32,
+    pub page_base: u32,
+    pub texcoords: Vec<Texcoord>,
+    pub reserved: Vec<Texcoord>,
+    pub free_rects: Vec<Vec<Texcoord>>,
+    pub packed_rects: Vec<PackedRect>,
+}
+
+/// A raw placement of the packer before conversion to texcoord.
+///
+/// - `index`: An index of entry (`entries.len() + i` for `reserve` region `i`).
+/// - `page`: A page index of texture.
+/// - `x`: A x position of the placement.
+/// - `y`: A y position of the placement.
+/// - `w`: A width of the placement.
+/// - `h`: A height of the placement.
+///
+/// Positions and extents are in packer units (blocks with `MipWithBlock` and `MipWithBlockRect`,
+/// pixels otherwise) and include the padding (or gutter) of the entry.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackedRect {
+    pub index: usize,
+    pub page: u32,
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
 /// An element coordinate representing `u32` position.
 ///
 /// - `page`: A page index of texture.
@@ -600,8 +7979,19 @@ where
 /// - `min_y`: A minimum y position.
 /// - `max_x`: A maximum x position.
 /// - `max_y`: A maximum y position.
+/// - `size`: A width and height of its page (same width and height).
+/// - `rotated`: Whether the element is stored transposed (x and y swapped).
+/// - `trim_offset_x`: A x offset of the stored region in the source texture.
+/// - `trim_offset_y`: A y offset of the stored region in the source texture.
+/// - `source_width`: A width of the source texture before trimming.
+/// - `source_height`: A height of the source texture before trimming.
+///
+/// `to_f32` and `to_f64` methods are provided for normalized texcoord. They divide by `size`,
+/// which is the realized size of the page (smaller than the descriptor `size` when the page is
+/// cropped by `shrink_pages`), so normalized coordinates are always within `0.0..=1.0`.
 ///
-/// `to_f32` and `to_f64` methods are provided for normalized texcoord.
+/// Texcoords are ordered in reading order of the pages, by `page`, `min_y`, `min_x`, `max_y` and
+/// `max_x`, then by the other fields in declaration order.
 #[repr(C)]
 #[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -612,10 +8002,18 @@ pub struct Texcoord {
     pub max_x: u32,
     pub max_y: u32,
     pub size: u32,
+    pub rotated: bool,
+    pub trim_offset_x: u32,
+    pub trim_offset_y: u32,
+    pub source_width: u32,
+    pub source_height: u32,
 }
 
 impl Texcoord {
     /// Returns a normalized texcoord using f32.
+    ///
+    /// The origin is top-left (y = 0 at the top), which matches Vulkan, Direct3D, Metal and
+    /// WebGPU.
     #[inline]
     pub fn to_f32(self) -> Texcoord32 {
         Texcoord32 {
@@ -624,10 +8022,14 @@ impl Texcoord {
             min_y: self.min_y as f32 / self.size as f32,
             max_x: self.max_x as f32 / self.size as f32,
             max_y: self.max_y as f32 / self.size as f32,
+            rotated: self.rotated,
         }
     }
 
     /// Returns a normalized texcoord using f64.
+    ///
+    /// The origin is top-left (y = 0 at the top), which matches Vulkan, Direct3D, Metal and
+    /// WebGPU.
     #[inline]
     pub fn to_f64(self) -> Texcoord64 {
         Texcoord64 {
@@ -636,8 +8038,181 @@ impl Texcoord {
             min_y: self.min_y as f64 / self.size as f64,
             max_x: self.max_x as f64 / self.size as f64,
             max_y: self.max_y as f64 / self.size as f64,
+            rotated: self.rotated,
+        }
+    }
+
+    /// Returns a normalized texcoord using f32 with flipped y axis.
+    ///
+    /// The origin is bottom-left (y = 0 at the bottom), which matches OpenGL and WebGL.
+    #[inline]
+    pub fn to_f32_flipped(self) -> Texcoord32 {
+        Texcoord32 {
+            page: self.page,
+            min_x: self.min_x as f32 / self.size as f32,
+            min_y: 1.0 - self.max_y as f32 / self.size as f32,
+            max_x: self.max_x as f32 / self.size as f32,
+            max_y: 1.0 - self.min_y as f32 / self.size as f32,
+            rotated: self.rotated,
+        }
+    }
+
+    /// Returns a normalized texcoord using f64 with flipped y axis.
+    ///
+    /// The origin is bottom-left (y = 0 at the bottom), which matches OpenGL and WebGL.
+    #[inline]
+    pub fn to_f64_flipped(self) -> Texcoord64 {
+        Texcoord64 {
+            page: self.page,
+            min_x: self.min_x as f64 / self.size as f64,
+            min_y: 1.0 - self.max_y as f64 / self.size as f64,
+            max_x: self.max_x as f64 / self.size as f64,
+            max_y: 1.0 - self.min_y as f64 / self.size as f64,
+            rotated: self.rotated,
+        }
+    }
+
+    /// Returns a normalized texcoord using f32 inset by half a texel.
+    ///
+    /// The bounds point at the centers of the edge texels, so bilinear filtering never picks up
+    /// neighbor pixels. The origin is the same as [to_f32](Texcoord::to_f32).
+    #[inline]
+    pub fn to_f32_inset(self) -> Texcoord32 {
+        Texcoord32 {
+            page: self.page,
+            min_x: (self.min_x as f32 + 0.5) / self.size as f32,
+            min_y: (self.min_y as f32 + 0.5) / self.size as f32,
+            max_x: (self.max_x as f32 - 0.5) / self.size as f32,
+            max_y: (self.max_y as f32 - 0.5) / self.size as f32,
+            rotated: self.rotated,
+        }
+    }
+
+    /// Returns a normalized texcoord using f64 inset by half a texel.
+    ///
+    /// The bounds point at the centers of the edge texels, so bilinear filtering never picks up
+    /// neighbor pixels. The origin is the same as [to_f64](Texcoord::to_f64).
+    #[inline]
+    pub fn to_f64_inset(self) -> Texcoord64 {
+        Texcoord64 {
+            page: self.page,
+            min_x: (self.min_x as f64 + 0.5) / self.size as f64,
+            min_y: (self.min_y as f64 + 0.5) / self.size as f64,
+            max_x: (self.max_x as f64 - 0.5) / self.size as f64,
+            max_y: (self.max_y as f64 - 0.5) / self.size as f64,
+            rotated: self.rotated,
+        }
+    }
+
+    /// Returns an affine transform from a UV of the entry into a normalized UV of its page.
+    ///
+    /// The matrix is column-major (`[x axis, y axis, translation]`), so a UV `(u, v)` in `0..=1`
+    /// of the source texture maps to `m[0] * u + m[1] * v + m[2]`. Rotation (x and y swapped) is
+    /// encoded in the axes, so a shader can apply one matrix regardless of `rotated`. The origin is
+    /// the same as [to_f32](Texcoord::to_f32).
+    #[inline]
+    pub fn uv_transform(self) -> [[f32; 2]; 3] {
+        let Texcoord32 {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+            ..
+        } = self.to_f32();
+        let (width, height) = (max_x - min_x, max_y - min_y);
+        match self.rotated {
+            true => [[0.0, height], [width, 0.0], [min_x, min_y]],
+            false => [[width, 0.0], [0.0, height], [min_x, min_y]],
+        }
+    }
+
+    /// Returns an affine transform from a normalized UV of the page into a UV of the entry.
+    ///
+    /// This is the inverse of [uv_transform](Texcoord::uv_transform) in the same layout. The
+    /// transform is not finite for an element of zero width or height.
+    #[inline]
+    pub fn inverse_uv_transform(self) -> [[f32; 2]; 3] {
+        let Texcoord32 {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+            ..
+        } = self.to_f32();
+        let (width, height) = (max_x - min_x, max_y - min_y);
+        match self.rotated {
+            true => [
+                [0.0, 1.0 / width],
+                [1.0 / height, 0.0],
+                [-min_y / height, -min_x / width],
+            ],
+            false => [
+                [1.0 / width, 0.0],
+                [0.0, 1.0 / height],
+                [-min_x / width, -min_y / height],
+            ],
         }
     }
+
+    /// Returns the texcoord in texels of a mip level.
+    ///
+    /// `min_x` and `min_y` are rounded down and `max_x` and `max_y` are rounded up, so the rect
+    /// covers every texel the element touches at the mip level. The rect keeps at least 1 texel
+    /// per axis and is clamped to `size`, which is `size >> mip_level` (at least 1). The other
+    /// fields are kept.
+    #[inline]
+    pub fn at_mip(self, mip_level: u32) -> Texcoord {
+        let floor = |value: u32| value.checked_shr(mip_level).unwrap_or(0);
+        let ceil = |value: u32| {
+            let rounded = (value as u64 + (1u64 << mip_level.min(32)) - 1) >> mip_level.min(32);
+            rounded as u32
+        };
+
+        let size = floor(self.size).max(1);
+        let min_x = floor(self.min_x).min(size - 1);
+        let min_y = floor(self.min_y).min(size - 1);
+        Texcoord {
+            min_x,
+            min_y,
+            max_x: ceil(self.max_x).clamp(min_x + 1, size),
+            max_y: ceil(self.max_y).clamp(min_y + 1, size),
+            size,
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for Texcoord {
+    /// Writes the texcoord as `page:(min_x,min_y)-(max_x,max_y)`.
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:({},{})-({},{})",
+            self.page, self.min_x, self.min_y, self.max_x, self.max_y
+        )
+    }
+}
+
+impl Ord for Texcoord {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        let key = |t: &Self| {
+            (
+                (t.page, t.min_y, t.min_x, t.max_y, t.max_x),
+                (t.size, t.rotated, t.trim_offset_x, t.trim_offset_y),
+                (t.source_width, t.source_height),
+            )
+        };
+        key(self).cmp(&key(other))
+    }
+}
+
+impl PartialOrd for Texcoord {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 /// An element coordinate representing `f32` position.
@@ -647,6 +8222,7 @@ impl Texcoord {
 /// - `min_y`: A minimum y position (normalized).
 /// - `max_x`: A maximum x position (normalized).
 /// - `max_y`: A maximum y position (normalized).
+/// - `rotated`: Whether the element is stored transposed (x and y swapped).
 #[repr(C)]
 #[derive(Clone, Copy, PartialEq, Default, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -656,6 +8232,7 @@ pub struct Texcoord32 {
     pub min_y: f32,
     pub max_x: f32,
     pub max_y: f32,
+    pub rotated: bool,
 }
 
 impl From<Texcoord> for Texcoord32 {
@@ -665,6 +8242,42 @@ impl From<Texcoord> for Texcoord32 {
     }
 }
 
+#[cfg(feature = "glam")]
+impl Texcoord32 {
+    /// Returns the minimum position as `glam::Vec2`.
+    #[inline]
+    pub fn min_vec2(self) -> glam::Vec2 {
+        glam::Vec2::new(self.min_x, self.min_y)
+    }
+
+    /// Returns the maximum position as `glam::Vec2`.
+    #[inline]
+    pub fn max_vec2(self) -> glam::Vec2 {
+        glam::Vec2::new(self.max_x, self.max_y)
+    }
+}
+
+#[cfg(feature = "mint")]
+impl Texcoord32 {
+    /// Returns the minimum position as `mint::Vector2`.
+    #[inline]
+    pub fn min_vector2(self) -> mint::Vector2<f32> {
+        mint::Vector2 {
+            x: self.min_x,
+            y: self.min_y,
+        }
+    }
+
+    /// Returns the maximum position as `mint::Vector2`.
+    #[inline]
+    pub fn max_vector2(self) -> mint::Vector2<f32> {
+        mint::Vector2 {
+            x: self.max_x,
+            y: self.max_y,
+        }
+    }
+}
+
 /// An element coordinate representing `f64` position.
 ///
 /// - `page`: A page index of texture.
@@ -672,6 +8285,7 @@ impl From<Texcoord> for Texcoord32 {
 /// - `min_y`: A minimum y position (normalized).
 /// - `max_x`: A maximum x position (normalized).
 /// - `max_y`: A maximum y position (normalized).
+/// - `rotated`: Whether the element is stored transposed (x and y swapped).
 #[repr(C)]
 #[derive(Clone, Copy, PartialEq, Default, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -681,6 +8295,7 @@ pub struct Texcoord64 {
     pub min_y: f64,
     pub max_x: f64,
     pub max_y: f64,
+    pub rotated: bool,
 }
 
 impl From<Texcoord> for Texcoord64 {
@@ -690,21 +8305,220 @@ impl From<Texcoord> for Texcoord64 {
     }
 }
 
+#[cfg(feature = "glam")]
+impl Texcoord64 {
+    /// Returns the minimum position as `glam::DVec2`.
+    #[inline]
+    pub fn min_dvec2(self) -> glam::DVec2 {
+        glam::DVec2::new(self.min_x, self.min_y)
+    }
+
+    /// Returns the maximum position as `glam::DVec2`.
+    #[inline]
+    pub fn max_dvec2(self) -> glam::DVec2 {
+        glam::DVec2::new(self.max_x, self.max_y)
+    }
+}
+
+#[cfg(feature = "mint")]
+impl Texcoord64 {
+    /// Returns the minimum position as `mint::Vector2`.
+    #[inline]
+    pub fn min_vector2(self) -> mint::Vector2<f64> {
+        mint::Vector2 {
+            x: self.min_x,
+            y: self.min_y,
+        }
+    }
+
+    /// Returns the maximum position as `mint::Vector2`.
+    #[inline]
+    pub fn max_vector2(self) -> mint::Vector2<f64> {
+        mint::Vector2 {
+            x: self.max_x,
+            y: self.max_y,
+        }
+    }
+}
+
+/// An element coordinate of a brick in a 3D texture atlas representing `u32` position.
+///
+/// - `page`: A page index of volume.
+/// - `min_x`: A minimum x position.
+/// - `min_y`: A minimum y position.
+/// - `min_z`: A minimum z position (the first slice).
+/// - `max_x`: A maximum x position.
+/// - `max_y`: A maximum y position.
+/// - `max_z`: A maximum z position (past the last slice).
+/// - `size`: A width, height and depth of its volume.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VolumeTexcoord {
+    pub page: u32,
+    pub min_x: u32,
+    pub min_y: u32,
+    pub min_z: u32,
+    pub max_x: u32,
+    pub max_y: u32,
+    pub max_z: u32,
+    pub size: u32,
+}
+
+impl VolumeTexcoord {
+    /// Returns the normalized minimum and maximum positions (x, y, z) using f32.
+    #[inline]
+    pub fn to_f32(self) -> ([f32; 3], [f32; 3]) {
+        let size = self.size as f32;
+        (
+            [
+                self.min_x as f32 / size,
+                self.min_y as f32 / size,
+                self.min_z as f32 / size,
+            ],
+            [
+                self.max_x as f32 / size,
+                self.max_y as f32 / size,
+                self.max_z as f32 / size,
+            ],
+        )
+    }
+}
+
 /// An error type for texture atlas generation.
 ///
-/// - `ZeroMaxPageCount`: `max_page_count` is zero.
-/// - `InvalidSize(size)`: `size` is not power of two.
+/// - `ZeroMaxPageCount`: `max_page_count` is zero. It is no longer returned since zero means
+///   unbounded, and is kept for compatibility.
+/// - `InvalidSize(size)`: `size` is not power of two (or is zero with `relaxed_mip`).
 /// - `InvalidBlockSize(block_size)`: `block_size` is not power of two.
-/// - `ZeroEntry`: `entries` is empty.
+/// - `InvalidOriginAlignment(origin_alignment)`: `origin_alignment` is not power of two.
+/// - `InvalidSubBlockSize(sub_block_size)`: `sub_block_size` is not power of two from 2 to less
+///   than the smaller block side, or is given without block option.
+/// - `BlockLargerThanPage { block_size, size }`: `block_size` (or a side of non-square block) is
+///   larger than `size`, so no entry fits in a page.
+/// - `NoAlphaChannel`: `premultiply_alpha` (or `bleed_alpha` without mip map) is enabled for pixel
+///   without alpha channel.
+/// - `InvalidSwizzle(swizzle)`: `channel_swizzle` refers to a channel out of the pixel.
+/// - `InvalidSdfSpread(spread)`: `spread` of `sdf` is zero, negative, infinite or NaN.
+/// - `ZeroMipLevelCount`: `max_mip_level_count` is zero.
+/// - `ZeroMaxEntriesPerPage`: `max_entries_per_page` is zero.
+/// - `PackAlgorithmDisabled(pack_algorithm)`: `pack_algorithm` requires a feature which is not
+///   enabled.
+/// - `MinPageCountExceedsMax { min_page_count, max_page_count }`: `min_page_count` is larger
+///   than nonzero `max_page_count`.
+/// - `EmptyMipRange(mip_range)`: `mip_range` contains no mip level.
+/// - `InsufficientGutter { needed, got }`: With `strict_gutter`, the padding `got` is narrower
+///   than the padding `needed` to keep neighbors out of the mip filter at the coarsest mip level
+///   (both in pixels at mip level 0).
+/// - `ZeroEntry`: `entries` and `reserve` are empty.
+/// - `ZeroSizedEntry { index }`: An entry has zero width or height.
+/// - `EntryTooLarge { index, width, height, max }`: An entry including its padding is larger than
+///   `size`.
+/// - `NonFiniteSubpixel { index }`: An entry has a NaN or infinite float subpixel, which can not
+///   be filtered by mip map generation.
+/// - `PinnedPageOverflow { page }`: The entries with `pin_page` of `page` do not fit in it, or
+///   `page` is before `page_base` or out of nonzero `max_page_count`.
+/// - `PreservedMismatch { index }`: An entry in `preserve` can not be placed back at its previous
+///   texcoord, e.g. its extent or a layout option differs from the previous generation, or the
+///   texcoord is out of the pages or overlaps another preserved one.
+/// - `NoPackingState`: The atlas has no packing state for insertion.
+/// - `NoEntry { index }`: An entry to update (or preserve) is out of `texcoords` (or `entries`).
+/// - `SlotTooSmall { index, width, height, slot_width, slot_height }`: An entry to update
+///   including its padding is larger than the region of the replaced entry.
+/// - `IncompatibleAtlas { index }`: An atlas to combine has a different `size` or
+///   `mip_level_count` from the first one.
+/// - `OutOfPage { index }`: A texcoord with its padding is out of its page.
+/// - `Overlap { a, b }`: Texcoords of two entries (or reserved regions) with their padding overlap.
+/// - `TransposedFrame { index }`: An entry is stored transposed by `allow_rotation`, which the
+///   frames of TexturePacker (rotated clockwise) can not describe.
+/// - `MipLevelOutOfRange { mip_level, mip_level_count }`: A requested mip level is out of the mip
+///   levels kept in a page, or `mip_range` starts at `mip_level` not less than the mip map count.
+/// - `Overflow`: A position or extent of the layout can not be represented in `u32` (e.g. with
+///   extreme `size`, padding, `block_size` or `page_base`).
+/// - `Internal(message)`: An internal invariant is violated (e.g. a resampled position is out of
+///   the entry bounds). This is a bug of this crate and should be reported.
 /// - `Packing(err)`: Packing error occurred.
 ///
+/// The messages of `InvalidSize` and `InvalidBlockSize` suggest the nearest valid value by
+/// [round_up_pow2](round_up_pow2).
+///
 /// See the [RectanglePackError](rectangle_pack::RectanglePackError) for details.
 #[derive(Debug)]
 pub enum AtlasError {
+    #[deprecated(note = "zero `max_page_count` means unbounded and is no longer an error")]
     ZeroMaxPageCount,
     InvalidSize(u32),
     InvalidBlockSize(u32),
+    InvalidOriginAlignment(u32),
+    InvalidSubBlockSize(u32),
+    BlockLargerThanPage {
+        block_size: u32,
+        size: u32,
+    },
+    NoAlphaChannel,
+    InvalidSwizzle([usize; 4]),
+    InvalidSdfSpread(f32),
+    ZeroMipLevelCount,
+    ZeroMaxEntriesPerPage,
+    PackAlgorithmDisabled(PackAlgorithm),
+    MinPageCountExceedsMax {
+        min_page_count: u32,
+        max_page_count: u32,
+    },
+    EmptyMipRange(Range<u32>),
+    InsufficientGutter {
+        needed: u32,
+        got: u32,
+    },
     ZeroEntry,
+    ZeroSizedEntry {
+        index: usize,
+    },
+    EntryTooLarge {
+        index: usize,
+        width: u32,
+        height: u32,
+        max: u32,
+    },
+    NonFiniteSubpixel {
+        index: usize,
+    },
+    PinnedPageOverflow {
+        page: u32,
+    },
+    PreservedMismatch {
+        index: usize,
+    },
+    NoPackingState,
+    NoEntry {
+        index: usize,
+    },
+    SlotTooSmall {
+        index: usize,
+        width: u32,
+        height: u32,
+        slot_width: u32,
+        slot_height: u32,
+    },
+    IncompatibleAtlas {
+        index: usize,
+    },
+    OutOfPage {
+        index: usize,
+    },
+    Overlap {
+        a: usize,
+        b: usize,
+    },
+    TransposedFrame {
+        index: usize,
+    },
+    MipLevelOutOfRange {
+        mip_level: u32,
+        mip_level_count: u32,
+    },
+    Overflow,
+    Internal(&'static str),
     Packing(rectangle_pack::RectanglePackError),
 }
 
@@ -712,19 +8526,169 @@ impl fmt::Display for AtlasError {
     #[rustfmt::skip]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            #[allow(deprecated)]
             AtlasError::ZeroMaxPageCount => write!(f, "max page count is zero."),
-            AtlasError::InvalidSize(size) => write!(f, "size is not power of two: {}.", size),
-            AtlasError::InvalidBlockSize(block_size) => write!(f, "block size is not power of two: {}.", block_size),
+            AtlasError::InvalidSize(size) => write!(f, "size {} is not a power of two; try {}.", size, round_up_pow2(*size)),
+            AtlasError::InvalidBlockSize(block_size) => write!(f, "block size {} is not a power of two; try {}.", block_size, round_up_pow2(*block_size)),
+            AtlasError::InvalidOriginAlignment(origin_alignment) => write!(f, "origin alignment {} is not a power of two; try {}.", origin_alignment, round_up_pow2(*origin_alignment)),
+            AtlasError::InvalidSubBlockSize(sub_block_size) => write!(f, "sub-block size {} is not a power of two from 2 to less than the block size.", sub_block_size),
+            AtlasError::BlockLargerThanPage { block_size, size } => write!(f, "block size {} is larger than size {}.", block_size, size),
+            AtlasError::NoAlphaChannel => write!(f, "pixel has no alpha channel."),
+            AtlasError::InvalidSwizzle(swizzle) => write!(f, "channel swizzle {:?} refers to a channel out of the pixel.", swizzle),
+            AtlasError::InvalidSdfSpread(spread) => write!(f, "sdf spread {} is not a positive finite number.", spread),
+            AtlasError::ZeroMipLevelCount => write!(f, "max mip level count is zero."),
+            AtlasError::ZeroMaxEntriesPerPage => write!(f, "max entries per page is zero."),
+            AtlasError::PackAlgorithmDisabled(pack_algorithm) => write!(f, "pack algorithm {:?} requires a feature which is not enabled.", pack_algorithm),
+            AtlasError::MinPageCountExceedsMax { min_page_count, max_page_count } => write!(f, "min page count {} is larger than max page count {}.", min_page_count, max_page_count),
+            AtlasError::EmptyMipRange(mip_range) => write!(f, "mip range {:?} is empty.", mip_range),
+            AtlasError::InsufficientGutter { needed, got } => write!(f, "padding {} is narrower than {} needed by the mip filter.", got, needed),
             AtlasError::ZeroEntry => write!(f, "entry is empty."),
+            AtlasError::ZeroSizedEntry { index } => write!(f, "entry {} has zero width or height.", index),
+            AtlasError::EntryTooLarge { index, width, height, max } => write!(f, "entry {} is too large: {}x{} exceeds {}.", index, width, height, max),
+            AtlasError::NonFiniteSubpixel { index } => write!(f, "entry {} has non-finite subpixel.", index),
+            AtlasError::PinnedPageOverflow { page } => write!(f, "entries pinned to page {} do not fit in it.", page),
+            AtlasError::PreservedMismatch { index } => write!(f, "entry {} can not keep its previous texcoord.", index),
+            AtlasError::NoPackingState => write!(f, "atlas has no packing state."),
+            AtlasError::NoEntry { index } => write!(f, "entry {} does not exist.", index),
+            AtlasError::SlotTooSmall { index, width, height, slot_width, slot_height } => write!(f, "entry {} is too large: {}x{} exceeds its region {}x{}.", index, width, height, slot_width, slot_height),
+            AtlasError::IncompatibleAtlas { index } => write!(f, "atlas {} has different size or mip level count.", index),
+            AtlasError::OutOfPage { index } => write!(f, "entry {} is out of its page.", index),
+            AtlasError::Overlap { a, b } => write!(f, "entry {} overlaps entry {}.", a, b),
+            AtlasError::TransposedFrame { index } => write!(f, "entry {} is transposed, not rotated clockwise.", index),
+            AtlasError::MipLevelOutOfRange { mip_level, mip_level_count } => write!(f, "mip level {} is out of mip level count {}.", mip_level, mip_level_count),
+            AtlasError::Overflow => write!(f, "layout arithmetic overflows u32."),
+            AtlasError::Internal(message) => write!(f, "internal error: {}.", message),
             AtlasError::Packing(err) => err.fmt(f),
         }
     }
 }
 
-impl error::Error for AtlasError {}
+impl error::Error for AtlasError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AtlasError::Packing(rectangle_pack::RectanglePackError::NotEnoughBinSpace) => {
+                Some(&NOT_ENOUGH_BIN_SPACE)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A wrapper of packing error implementing `error::Error`.
+///
+/// `rectangle_pack` implements `error::Error` only under `cfg(std)`, which is never set by its
+/// features, so the error is exposed as the source through this wrapper.
+#[derive(Debug)]
+struct PackingError(rectangle_pack::RectanglePackError);
+
+static NOT_ENOUGH_BIN_SPACE: PackingError =
+    PackingError(rectangle_pack::RectanglePackError::NotEnoughBinSpace);
+
+impl fmt::Display for PackingError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl error::Error for PackingError {}
 
 impl From<rectangle_pack::RectanglePackError> for AtlasError {
     fn from(value: rectangle_pack::RectanglePackError) -> Self {
         AtlasError::Packing(value)
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn place_single() {
+        let rect = rectangle_pack::RectToInsert::new(8, 4, 1);
+        let mut target_bins = BTreeMap::from([
+            (0, rectangle_pack::TargetBin::new(8, 4, 1)),
+            (1, rectangle_pack::TargetBin::new(8, 4, 1)),
+        ]);
+        target_bins.get_mut(&0).unwrap().remove_filled_section(0);
+
+        let locations = super::place_single(&[(3, rect)], &mut target_bins, (8, 4)).unwrap();
+        let (page, location) = locations[&3];
+        assert_eq!(page, 1);
+        assert_eq!((location.x(), location.y()), (0, 0));
+        assert!(target_bins[&1].available_bin_sections().is_empty());
+
+        let small = rectangle_pack::RectToInsert::new(4, 4, 1);
+        let mut target_bins = BTreeMap::from([(0, rectangle_pack::TargetBin::new(8, 4, 1))]);
+        assert!(super::place_single(&[(0, small)], &mut target_bins, (8, 4)).is_none());
+        assert!(super::place_single(&[(0, rect), (1, rect)], &mut target_bins, (8, 4)).is_none());
+    }
+
+    #[test]
+    fn free_sections() {
+        let regions = [(2, 1, 5, 4), (4, 3, 8, 6), (0, 6, 3, 8), (1, 2, 3, 3)];
+        let sections = super::free_sections((8, 8), &regions);
+
+        let mut cells = [[0; 8]; 8];
+        for &(x, y, width, height) in &sections {
+            assert!(width > 0 && height > 0);
+            for row in &mut cells[y as usize..(y + height) as usize] {
+                for cell in &mut row[x as usize..(x + width) as usize] {
+                    *cell += 1;
+                }
+            }
+        }
+        for (y, row) in (0..).zip(cells) {
+            for (x, cell) in (0..).zip(row) {
+                let used = regions.iter().any(|&(min_x, min_y, max_x, max_y)| {
+                    (min_x..max_x).contains(&x) && (min_y..max_y).contains(&y)
+                });
+                assert_eq!(cell, !used as u32, "cell ({x}, {y})");
+            }
+        }
+
+        assert_eq!(super::free_sections((8, 4), &[]), [(0, 0, 8, 4)]);
+        assert!(super::free_sections((8, 4), &[(0, 0, 8, 4)]).is_empty());
+    }
+
+    #[test]
+    fn section_rect() {
+        for rect in [
+            (0, 0, 1, 1),
+            (3, 5, 7, 2),
+            (2, 9, 1, 16),
+            (0, 0, 4096, 4096),
+        ] {
+            let (x, y, width, height) = rect;
+            let section = super::bin_section(x, y, width, height);
+            assert_eq!(super::section_rect(&section), rect);
+        }
+    }
+
+    #[test]
+    fn resample_out_of_range() {
+        let src = image::GrayImage::from_pixel(4, 4, image::Luma([255]));
+        let resample = |shift| {
+            super::resample(
+                &src,
+                AtlasEntryMipOption::Clamp,
+                false,
+                shift,
+                (4, 4),
+                (4, 4),
+                AtlasSampling::Nearest,
+            )
+        };
+
+        assert!(resample((2, 2)).is_ok());
+        assert!(matches!(
+            resample((u32::MAX, 0)),
+            Err(AtlasError::Internal(_))
+        ));
+        assert!(matches!(
+            resample((0, 1 << 31)),
+            Err(AtlasError::Internal(_))
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+use image_atlas::*;
+
+#[test]
+fn plan_blank() {
+    let desc = AtlasDescriptor::<BlankTexture> {
+        max_page_count: 1,
+        size: 256,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 16),
+        allow_rotation: true,
+        reserve: &[(16, 16)],
+        ..Default::default()
+    };
+
+    let layout = plan_atlas(&desc, &[(64, 64), (32, 48)]).unwrap();
+    assert_eq!(layout.page_count, 1);
+    assert_eq!(layout.size, 256);
+    assert_eq!(layout.mip_level_count, 5);
+    assert_eq!(layout.texcoords.len(), 2);
+    assert_eq!(layout.reserved.len(), 1);
+
+    let texcoord = layout.texcoords[1];
+    assert!(texcoord.rotated);
+    assert_eq!(texcoord.max_x - texcoord.min_x, 48);
+    assert_eq!(texcoord.max_y - texcoord.min_y, 32);
+    assert_eq!((texcoord.source_width, texcoord.source_height), (32, 48));
+
+    let reserved = layout.reserved[0];
+    assert_eq!(reserved.max_x - reserved.min_x, 16);
+    assert_eq!((reserved.source_width, reserved.source_height), (16, 16));
+}
+
+#[test]
+fn plan_errors() {
+    let desc = AtlasDescriptor::<BlankTexture> {
+        max_page_count: 1,
+        size: 64,
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        plan_atlas(&desc, &[(128, 16)]),
+        Err(AtlasError::EntryTooLarge { index: 0, .. })
+    ));
+    assert!(matches!(
+        plan_atlas(&desc, &[(0, 16)]),
+        Err(AtlasError::ZeroSizedEntry { index: 0 })
+    ));
+}
+
+#[test]
+fn minimum_size_blank() {
+    let entries = [AtlasEntry::new(BlankTexture::new(300, 300))];
+    let size = minimum_size(1, AtlasMipOption::NoMipWithPadding(2), &entries).unwrap();
+    assert_eq!(size, 512);
+
+    let bytes = estimate_memory(size, 1, 1, 4);
+    assert_eq!(bytes, 512 * 512 * 4);
+}
+
+#[test]
+fn pack_algorithm_disabled() {
+    let disabled = [
+        (PackAlgorithm::Skyline, cfg!(feature = "skyline")),
+        (PackAlgorithm::Shelf, cfg!(feature = "shelf")),
+    ];
+    for (pack_algorithm, enabled) in disabled {
+        let desc = AtlasDescriptor::<BlankTexture> {
+            max_page_count: 1,
+            size: 64,
+            pack_algorithm,
+            ..Default::default()
+        };
+
+        let result = plan_atlas(&desc, &[(16, 16)]);
+        match enabled {
+            true => assert!(result.is_ok()),
+            false => assert!(matches!(
+                result,
+                Err(AtlasError::PackAlgorithmDisabled(algorithm)) if algorithm == pack_algorithm
+            )),
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use std::{fs, path};
+#![cfg(feature = "std")]
+
+use std::{fs, io, path};
 
 use image::GenericImageView;
 use image_atlas::*;
@@ -13,12 +15,15 @@ fn usage() {
             AtlasEntry {
                 texture: image::RgbImage::new(512, 512),
                 mip: AtlasEntryMipOption::Clamp,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::new(512, 256),
                 mip: AtlasEntryMipOption::Clamp,
+                ..Default::default()
             },
         ],
+        ..Default::default()
     })
     .unwrap();
 
@@ -42,28 +47,35 @@ fn write_image() {
             AtlasEntry {
                 texture: image::RgbImage::from_fn(512, 512, |_, _| image::Rgb([255, 0, 0])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(512, 256, |_, _| image::Rgb([0, 255, 0])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(32, 32, |_, _| image::Rgb([0, 0, 255])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(8, 8, |_, _| image::Rgb([0, 255, 255])),
                 mip: AtlasEntryMipOption::Clamp,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(8, 8, |_, _| image::Rgb([255, 0, 255])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(8, 8, |_, _| image::Rgb([255, 255, 0])),
                 mip: AtlasEntryMipOption::Mirror,
+                ..Default::default()
             },
         ],
+        ..Default::default()
     })
     .unwrap();
 
@@ -96,26 +108,32 @@ fn result_equality() {
         AtlasEntry {
             texture: image::RgbImage::from_fn(512, 512, |_, _| image::Rgb([255, 0, 0])),
             mip: AtlasEntryMipOption::Repeat,
+            ..Default::default()
         },
         AtlasEntry {
             texture: image::RgbImage::from_fn(512, 256, |_, _| image::Rgb([0, 255, 0])),
             mip: AtlasEntryMipOption::Repeat,
+            ..Default::default()
         },
         AtlasEntry {
             texture: image::RgbImage::from_fn(32, 32, |_, _| image::Rgb([0, 0, 255])),
             mip: AtlasEntryMipOption::Repeat,
+            ..Default::default()
         },
         AtlasEntry {
             texture: image::RgbImage::from_fn(8, 8, |_, _| image::Rgb([0, 255, 255])),
             mip: AtlasEntryMipOption::Clamp,
+            ..Default::default()
         },
         AtlasEntry {
             texture: image::RgbImage::from_fn(8, 8, |_, _| image::Rgb([255, 0, 255])),
             mip: AtlasEntryMipOption::Repeat,
+            ..Default::default()
         },
         AtlasEntry {
             texture: image::RgbImage::from_fn(8, 8, |_, _| image::Rgb([255, 255, 0])),
             mip: AtlasEntryMipOption::Mirror,
+            ..Default::default()
         },
     ];
 
@@ -124,6 +142,7 @@ fn result_equality() {
         size: 2048,
         mip: AtlasMipOption::NoMip,
         entries,
+        ..Default::default()
     })
     .unwrap();
 
@@ -132,6 +151,7 @@ fn result_equality() {
         size: 2048,
         mip: AtlasMipOption::NoMipWithPadding(8),
         entries,
+        ..Default::default()
     })
     .unwrap();
 
@@ -140,6 +160,7 @@ fn result_equality() {
         size: 2048,
         mip: AtlasMipOption::Mip(AtlasMipFilter::Nearest),
         entries,
+        ..Default::default()
     })
     .unwrap();
 
@@ -148,6 +169,7 @@ fn result_equality() {
         size: 2048,
         mip: AtlasMipOption::MipWithPadding(AtlasMipFilter::Nearest, 8),
         entries,
+        ..Default::default()
     })
     .unwrap();
 
@@ -156,24 +178,16 @@ fn result_equality() {
         size: 2048,
         mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 32),
         entries,
+        ..Default::default()
     })
     .unwrap();
 
-    fn extract_view<P: image::Pixel>(
-        atlas: &Atlas<P>,
-    ) -> Vec<image::SubImage<&image::ImageBuffer<P, Vec<P::Subpixel>>>> {
-        atlas
-            .texcoords
-            .iter()
-            .map(|texcoord| {
-                let texture = &atlas.textures[texcoord.page as usize];
-                let image = &texture.mip_maps[0];
-                let x = texcoord.min_x;
-                let y = texcoord.min_y;
-                let w = texcoord.max_x - texcoord.min_x;
-                let h = texcoord.max_y - texcoord.min_y;
-                image.view(x, y, w, h)
-            })
+    type View<'a, P> =
+        image::SubImage<&'a image::ImageBuffer<P, Vec<<P as image::Pixel>::Subpixel>>>;
+
+    fn extract_view<P: image::Pixel>(atlas: &Atlas<P>) -> Vec<View<'_, P>> {
+        (0..atlas.texcoords.len())
+            .map(|index| atlas.entry_view(index, 0).unwrap())
             .collect::<Vec<_>>()
     }
 
@@ -201,28 +215,35 @@ fn page_minimizing() {
             AtlasEntry {
                 texture: image::RgbImage::from_fn(512, 512, |_, _| image::Rgb([255, 0, 0])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(512, 256, |_, _| image::Rgb([0, 255, 0])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(32, 32, |_, _| image::Rgb([0, 0, 255])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(8, 8, |_, _| image::Rgb([0, 255, 255])),
                 mip: AtlasEntryMipOption::Clamp,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(8, 8, |_, _| image::Rgb([255, 0, 255])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(8, 8, |_, _| image::Rgb([255, 255, 0])),
                 mip: AtlasEntryMipOption::Mirror,
+                ..Default::default()
             },
         ],
+        ..Default::default()
     })
     .unwrap();
 
@@ -239,34 +260,4267 @@ fn page_additional() {
             AtlasEntry {
                 texture: image::RgbImage::from_fn(512, 512, |_, _| image::Rgb([255, 0, 0])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(512, 256, |_, _| image::Rgb([0, 255, 0])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(512, 256, |_, _| image::Rgb([0, 255, 0])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(32, 32, |_, _| image::Rgb([0, 0, 255])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(8, 8, |_, _| image::Rgb([0, 255, 255])),
                 mip: AtlasEntryMipOption::Clamp,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(8, 8, |_, _| image::Rgb([255, 0, 255])),
                 mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
             },
             AtlasEntry {
                 texture: image::RgbImage::from_fn(8, 8, |_, _| image::Rgb([255, 255, 0])),
                 mip: AtlasEntryMipOption::Mirror,
+                ..Default::default()
             },
         ],
+        ..Default::default()
     })
     .unwrap();
 
     assert_eq!(atlas.page_count, 2);
 }
+
+#[test]
+fn rotation() {
+    let entries = &[
+        AtlasEntry {
+            texture: image::RgbImage::from_fn(64, 32, |_, _| image::Rgb([255, 0, 0])),
+            mip: AtlasEntryMipOption::Clamp,
+            ..Default::default()
+        },
+        AtlasEntry {
+            texture: image::RgbImage::from_fn(32, 64, |x, y| image::Rgb([x as u8, y as u8, 0])),
+            mip: AtlasEntryMipOption::Clamp,
+            ..Default::default()
+        },
+    ];
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMip,
+        entries,
+        ..Default::default()
+    });
+    assert!(result.is_err());
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMip,
+        allow_rotation: true,
+        entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(!atlas.texcoords[0].rotated);
+
+    let texcoord = atlas.texcoords[1];
+    assert!(texcoord.rotated);
+    assert_eq!(texcoord.max_x - texcoord.min_x, 64);
+    assert_eq!(texcoord.max_y - texcoord.min_y, 32);
+
+    let page = &atlas.textures[texcoord.page as usize].mip_maps[0];
+    for x in 0..32 {
+        for y in 0..64 {
+            let pixel = page.get_pixel(texcoord.min_x + y, texcoord.min_y + x);
+            assert_eq!(*pixel, image::Rgb([x as u8, y as u8, 0]));
+        }
+    }
+
+    // a tall entry is kept as it is when transposing does not save space.
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        allow_rotation: true,
+        entries: &entries[1..],
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(!atlas.texcoords[0].rotated);
+}
+
+#[test]
+fn mip_color_space() {
+    let entries = &[AtlasEntry {
+        texture: image::GrayImage::from_fn(2, 2, |x, y| image::Luma([((x + y) % 2 * 255) as u8])),
+        mip: AtlasEntryMipOption::Clamp,
+        ..Default::default()
+    }];
+
+    let linear = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 2,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let srgb = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 2,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        mip_color_space: AtlasMipColorSpace::Srgb,
+        entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let image::Luma([linear]) = *linear.textures[0].mip_maps[1].get_pixel(0, 0);
+    let image::Luma([srgb]) = *srgb.textures[0].mip_maps[1].get_pixel(0, 0);
+    assert!((127..=128).contains(&linear));
+    assert!((187..=189).contains(&srgb));
+}
+
+#[test]
+fn premultiply_alpha() {
+    let entries = &[AtlasEntry {
+        texture: image::RgbaImage::from_fn(2, 2, |x, y| match (x, y) {
+            (0, 0) => image::Rgba([255, 255, 255, 255]),
+            _ => image::Rgba([0, 0, 0, 0]),
+        }),
+        mip: AtlasEntryMipOption::Clamp,
+        ..Default::default()
+    }];
+
+    let straight = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 2,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let premultiplied = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 2,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        premultiply_alpha: true,
+        entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let image::Rgba([r, _, _, a]) = *straight.textures[0].mip_maps[1].get_pixel(0, 0);
+    assert!((63..=64).contains(&r));
+    assert!((63..=64).contains(&a));
+
+    let image::Rgba([r, _, _, a]) = *premultiplied.textures[0].mip_maps[1].get_pixel(0, 0);
+    assert_eq!(r, 255);
+    assert!((63..=64).contains(&a));
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 2,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        premultiply_alpha: true,
+        entries: &[AtlasEntry {
+            texture: image::RgbImage::new(2, 2),
+            mip: AtlasEntryMipOption::Clamp,
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(AtlasError::NoAlphaChannel)));
+}
+
+#[test]
+fn mip_sharpen() {
+    let entries = &[AtlasEntry {
+        texture: image::GrayImage::from_fn(16, 16, |x, _| image::Luma([[64, 192][x as usize / 8]])),
+        mip: AtlasEntryMipOption::Clamp,
+        ..Default::default()
+    }];
+    let create = |mip_sharpen| {
+        create_atlas(&AtlasDescriptor {
+            max_page_count: 1,
+            size: 16,
+            mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+            mip_sharpen,
+            entries,
+            ..Default::default()
+        })
+        .unwrap()
+    };
+
+    let plain = create(None);
+    let zero = create(Some(SharpenParams { amount: 0.0 }));
+    let sharpened = create(Some(SharpenParams { amount: 1.0 }));
+    assert_eq!(plain.textures[0].mip_maps, zero.textures[0].mip_maps);
+    assert_eq!(
+        plain.textures[0].mip_maps[0],
+        sharpened.textures[0].mip_maps[0]
+    );
+
+    let plain = &plain.textures[0].mip_maps[1];
+    let sharpened = &sharpened.textures[0].mip_maps[1];
+    let image::Luma([plain_low]) = *plain.get_pixel(3, 0);
+    let image::Luma([plain_high]) = *plain.get_pixel(4, 0);
+    let image::Luma([low]) = *sharpened.get_pixel(3, 0);
+    let image::Luma([high]) = *sharpened.get_pixel(4, 0);
+    assert!(low < plain_low);
+    assert!(high > plain_high);
+    assert_eq!(sharpened.get_pixel(0, 0), plain.get_pixel(0, 0));
+}
+
+#[test]
+fn descriptor_builder() {
+    let entries = [AtlasEntry {
+        texture: image::RgbImage::new(32, 32),
+        mip: AtlasEntryMipOption::Clamp,
+        ..Default::default()
+    }];
+
+    let desc = AtlasDescriptor::builder()
+        .max_page_count(1)
+        .size(64)
+        .mip(AtlasMipOption::MipWithBlock(AtlasMipFilter::Nearest, 16))
+        .entries(&entries)
+        .build()
+        .unwrap();
+    assert_eq!(
+        desc,
+        AtlasDescriptor {
+            max_page_count: 1,
+            size: 64,
+            mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Nearest, 16),
+            entries: &entries,
+            ..Default::default()
+        }
+    );
+
+    let result = AtlasDescriptor::builder()
+        .size(64)
+        .entries(&entries)
+        .build();
+    assert!(result.is_ok_and(|desc| desc.max_page_count == 0));
+
+    let result = AtlasDescriptor::builder()
+        .max_page_count(1)
+        .size(100)
+        .mip(AtlasMipOption::Mip(AtlasMipFilter::Nearest))
+        .entries(&entries)
+        .build();
+    assert!(matches!(result, Err(AtlasError::InvalidSize(100))));
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "size 100 is not a power of two; try 128."
+    );
+
+    let result = AtlasDescriptor::builder()
+        .max_page_count(1)
+        .size(100)
+        .mip(AtlasMipOption::NoMip)
+        .entries(&entries)
+        .build();
+    assert!(result.is_ok());
+}
+
+#[test]
+fn entry_too_large() {
+    let entries = &[
+        AtlasEntry {
+            texture: image::RgbImage::new(16, 16),
+            mip: AtlasEntryMipOption::Clamp,
+            ..Default::default()
+        },
+        AtlasEntry {
+            texture: image::RgbImage::new(64, 16),
+            mip: AtlasEntryMipOption::Clamp,
+            ..Default::default()
+        },
+    ];
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMipWithPadding(2),
+        entries,
+        ..Default::default()
+    });
+    assert!(matches!(
+        result,
+        Err(AtlasError::EntryTooLarge {
+            index: 1,
+            width: 68,
+            height: 20,
+            max: 64,
+        })
+    ));
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Nearest, 16),
+        entries,
+        ..Default::default()
+    });
+    assert!(matches!(
+        result,
+        Err(AtlasError::EntryTooLarge {
+            index: 1,
+            width: 80,
+            height: 32,
+            max: 64,
+        })
+    ));
+}
+
+#[test]
+fn trim() {
+    let texture = image::RgbaImage::from_fn(8, 8, |x, y| match (x, y) {
+        (3..=4, 2..=4) => image::Rgba([255, 0, 0, 255]),
+        _ => image::Rgba([0, 0, 0, 0]),
+    });
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMip,
+        entries: &[
+            AtlasEntry {
+                texture: texture.clone(),
+                mip: AtlasEntryMipOption::Clamp,
+                trim: true,
+                ..Default::default()
+            },
+            AtlasEntry {
+                texture,
+                mip: AtlasEntryMipOption::Clamp,
+                trim: false,
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let texcoord = atlas.texcoords[0];
+    assert_eq!(texcoord.max_x - texcoord.min_x, 2);
+    assert_eq!(texcoord.max_y - texcoord.min_y, 3);
+    assert_eq!((texcoord.trim_offset_x, texcoord.trim_offset_y), (3, 2));
+    assert_eq!((texcoord.source_width, texcoord.source_height), (8, 8));
+
+    let page = &atlas.textures[texcoord.page as usize].mip_maps[0];
+    let view = page.view(texcoord.min_x, texcoord.min_y, 2, 3);
+    assert!(view
+        .pixels()
+        .all(|(_, _, p)| p == image::Rgba([255, 0, 0, 255])));
+
+    let texcoord = atlas.texcoords[1];
+    assert_eq!(texcoord.max_x - texcoord.min_x, 8);
+    assert_eq!(texcoord.max_y - texcoord.min_y, 8);
+    assert_eq!((texcoord.trim_offset_x, texcoord.trim_offset_y), (0, 0));
+    assert_eq!((texcoord.source_width, texcoord.source_height), (8, 8));
+}
+
+#[test]
+fn dynamic_image() {
+    let atlas = create_atlas_dynamic(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMip,
+        entries: &[
+            AtlasEntry {
+                texture: image::RgbImage::from_pixel(8, 8, image::Rgb([255, 0, 0])).into(),
+                mip: AtlasEntryMipOption::Clamp,
+                ..Default::default()
+            },
+            AtlasEntry {
+                texture: image::GrayImage::from_pixel(8, 8, image::Luma([128])).into(),
+                mip: AtlasEntryMipOption::Clamp,
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let texcoord = atlas.texcoords[0];
+    let page = &atlas.textures[texcoord.page as usize].mip_maps[0];
+    let pixel = page.get_pixel(texcoord.min_x, texcoord.min_y);
+    assert_eq!(*pixel, image::Rgba([255, 0, 0, 255]));
+
+    let texcoord = atlas.texcoords[1];
+    let page = &atlas.textures[texcoord.page as usize].mip_maps[0];
+    let pixel = page.get_pixel(texcoord.min_x, texcoord.min_y);
+    assert_eq!(*pixel, image::Rgba([128, 128, 128, 255]));
+}
+
+#[test]
+fn deduplicate() {
+    let entries = &[
+        AtlasEntry {
+            texture: image::RgbImage::from_pixel(16, 8, image::Rgb([255, 0, 0])),
+            mip: AtlasEntryMipOption::Clamp,
+            ..Default::default()
+        },
+        AtlasEntry {
+            texture: image::RgbImage::from_pixel(16, 8, image::Rgb([0, 255, 0])),
+            mip: AtlasEntryMipOption::Clamp,
+            ..Default::default()
+        },
+        AtlasEntry {
+            texture: image::RgbImage::from_pixel(16, 8, image::Rgb([255, 0, 0])),
+            mip: AtlasEntryMipOption::Clamp,
+            ..Default::default()
+        },
+    ];
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 16,
+        mip: AtlasMipOption::NoMip,
+        entries,
+        ..Default::default()
+    });
+    assert!(result.is_err());
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 16,
+        mip: AtlasMipOption::NoMip,
+        deduplicate: true,
+        entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_eq!(atlas.texcoords.len(), 3);
+    assert_eq!(atlas.texcoords[0], atlas.texcoords[2]);
+    assert_ne!(atlas.texcoords[0], atlas.texcoords[1]);
+}
+
+#[test]
+fn extrude() {
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 8,
+        mip: AtlasMipOption::NoMip,
+        extrude: 2,
+        entries: &[AtlasEntry {
+            texture: image::GrayImage::from_fn(4, 4, |x, y| image::Luma([(x * 4 + y) as u8])),
+            mip: AtlasEntryMipOption::Repeat,
+            ..Default::default()
+        }],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let texcoord = atlas.texcoords[0];
+    assert_eq!((texcoord.min_x, texcoord.min_y), (2, 2));
+    assert_eq!((texcoord.max_x, texcoord.max_y), (6, 6));
+
+    let page = &atlas.textures[0].mip_maps[0];
+    for x in 0..8 {
+        for y in 0..8 {
+            let sx = x.clamp(2, 5) - 2;
+            let sy = y.clamp(2, 5) - 2;
+            assert_eq!(*page.get_pixel(x, y), image::Luma([(sx * 4 + sy) as u8]));
+        }
+    }
+}
+
+#[test]
+fn max_mip_level_count() {
+    let entries = &[AtlasEntry {
+        texture: image::RgbImage::new(16, 16),
+        mip: AtlasEntryMipOption::Clamp,
+        ..Default::default()
+    }];
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Nearest),
+        max_mip_level_count: Some(3),
+        entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.mip_level_count, 3);
+    assert_eq!(atlas.textures[0].mip_level_count, 3);
+    assert_eq!(atlas.textures[0].mip_maps.len(), 3);
+    assert_eq!(atlas.textures[0].mip_maps[2].width(), 16);
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Nearest, 16),
+        max_mip_level_count: Some(8),
+        entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.mip_level_count, 5);
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Nearest),
+        max_mip_level_count: Some(0),
+        entries,
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(AtlasError::ZeroMipLevelCount)));
+}
+
+#[test]
+fn memory_usage() {
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 64,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Nearest),
+        entries: &[
+            AtlasEntry {
+                texture: image::RgbaImage::new(64, 64),
+                mip: AtlasEntryMipOption::Clamp,
+                ..Default::default()
+            },
+            AtlasEntry {
+                texture: image::RgbaImage::new(64, 64),
+                mip: AtlasEntryMipOption::Clamp,
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let estimated = estimate_memory(atlas.size, atlas.page_count, atlas.mip_level_count, 4);
+    assert_eq!(atlas.memory_usage(), estimated);
+    assert_eq!(estimated, 2 * 4 * (4096 + 1024 + 256 + 64 + 16 + 4 + 1));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde_round_trip() {
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 16),
+        entries: &[AtlasEntry {
+            texture: image::RgbImage::from_fn(16, 16, |x, y| image::Rgb([x as u8, y as u8, 0])),
+            mip: AtlasEntryMipOption::Clamp,
+            ..Default::default()
+        }],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let json = serde_json::to_string(&atlas).unwrap();
+    let restored: Atlas<image::Rgb<u8>> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.page_count, atlas.page_count);
+    assert_eq!(restored.size, atlas.size);
+    assert_eq!(restored.mip_level_count, atlas.mip_level_count);
+    assert_eq!(restored.texcoords, atlas.texcoords);
+    for (restored, texture) in restored.textures.iter().zip(&atlas.textures) {
+        assert_eq!(restored.mip_maps, texture.mip_maps);
+    }
+
+    let mut restored = restored;
+    let result = restored.insert(&AtlasEntry {
+        texture: image::RgbImage::new(4, 4),
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(AtlasError::NoPackingState)));
+
+    let size = serde_json::from_str::<Pow2Size>("256").unwrap();
+    assert_eq!(serde_json::to_string(&size).unwrap(), "256");
+    assert!(serde_json::from_str::<Pow2Size>("300").is_err());
+}
+
+#[test]
+fn write_ktx2() {
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Nearest, 16),
+        entries: &[AtlasEntry {
+            texture: image::RgbaImage::from_pixel(8, 8, image::Rgba([255, 0, 0, 255])),
+            ..Default::default()
+        }],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let texture = &atlas.textures[0];
+    let mut bytes = vec![];
+    texture.write_ktx2(&mut bytes).unwrap();
+
+    let read_u32 =
+        |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+    let read_u64 =
+        |offset: usize| u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+
+    assert_eq!(&bytes[..12], b"\xabKTX 20\xbb\r\n\x1a\n");
+    assert_eq!(read_u32(12), 37);
+    assert_eq!(read_u32(20), 64);
+    assert_eq!(read_u32(24), 64);
+    assert_eq!(read_u32(40), atlas.mip_level_count);
+
+    for (mip_level, mip_map) in texture.mip_maps.iter().enumerate() {
+        let offset = read_u64(80 + 24 * mip_level) as usize;
+        let length = read_u64(88 + 24 * mip_level) as usize;
+        assert_eq!(offset % 4, 0);
+        assert_eq!(&bytes[offset..offset + length], mip_map.as_raw().as_slice());
+    }
+}
+
+#[test]
+fn auto_size() {
+    let entries = &[
+        AtlasEntry {
+            texture: image::RgbImage::new(300, 300),
+            ..Default::default()
+        },
+        AtlasEntry {
+            texture: image::RgbImage::new(300, 250),
+            ..Default::default()
+        },
+    ];
+
+    let atlas = create_atlas_auto(1, AtlasMipOption::NoMipWithPadding(2), entries).unwrap();
+    assert_eq!(atlas.size, 1024);
+
+    let atlas = create_atlas_auto(2, AtlasMipOption::NoMipWithPadding(2), entries).unwrap();
+    assert_eq!(atlas.size, 512);
+
+    let entries = &[AtlasEntry {
+        texture: image::GrayImage::new(AUTO_MAX_SIZE + 1, 1),
+        ..Default::default()
+    }];
+    let result = create_atlas_auto(1, AtlasMipOption::NoMip, entries);
+    assert!(matches!(result, Err(AtlasError::EntryTooLarge { .. })));
+
+    let entries = &[AtlasEntry {
+        texture: image::RgbImage::new(600, 600),
+        ..Default::default()
+    }];
+    let mip = AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 512);
+    let atlas = create_atlas_auto(1, mip, entries).unwrap();
+    assert_eq!(atlas.size, 2048);
+}
+
+#[test]
+fn stats() {
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 64,
+        mip: AtlasMipOption::NoMip,
+        entries: &[
+            AtlasEntry {
+                texture: image::RgbImage::new(64, 32),
+                ..Default::default()
+            },
+            AtlasEntry {
+                texture: image::RgbImage::new(64, 48),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let stats = atlas.stats();
+    assert_eq!(stats.used_area, 64 * 32 + 64 * 48);
+    assert_eq!(stats.total_area, 2 * 64 * 64);
+    assert_eq!(stats.occupancy, 0.625);
+    assert_eq!(atlas.occupancy(), 0.625);
+
+    let mut per_page = stats.per_page.clone();
+    per_page.sort_by(f32::total_cmp);
+    assert_eq!(per_page, vec![0.5, 0.75]);
+}
+
+#[test]
+fn background() {
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Nearest),
+        background: Some(image::Rgba([255, 0, 255, 255])),
+        entries: &[AtlasEntry {
+            texture: image::RgbaImage::from_pixel(8, 8, image::Rgba([0, 255, 0, 255])),
+            ..Default::default()
+        }],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let texcoord = &atlas.texcoords[0];
+    let mip_map = &atlas.textures[0].mip_maps[0];
+    assert_eq!(
+        *mip_map.get_pixel(texcoord.min_x, texcoord.min_y),
+        image::Rgba([0, 255, 0, 255])
+    );
+    assert_eq!(*mip_map.get_pixel(63, 63), image::Rgba([255, 0, 255, 255]));
+
+    let last_mip_map = atlas.textures[0].mip_maps.last().unwrap();
+    assert_eq!(
+        *last_mip_map.get_pixel(0, 0),
+        image::Rgba([255, 0, 255, 255])
+    );
+}
+
+#[test]
+fn texcoord_flipped() {
+    let texcoord = Texcoord {
+        min_x: 16,
+        min_y: 0,
+        max_x: 32,
+        max_y: 16,
+        size: 64,
+        ..Default::default()
+    };
+
+    let texcoord32 = texcoord.to_f32_flipped();
+    assert_eq!((texcoord32.min_x, texcoord32.max_x), (0.25, 0.5));
+    assert_eq!((texcoord32.min_y, texcoord32.max_y), (0.75, 1.0));
+
+    let texcoord64 = texcoord.to_f64_flipped();
+    assert_eq!((texcoord64.min_x, texcoord64.max_x), (0.25, 0.5));
+    assert_eq!((texcoord64.min_y, texcoord64.max_y), (0.75, 1.0));
+}
+
+#[test]
+fn texcoord_inset() {
+    let texcoord = Texcoord {
+        min_x: 0,
+        min_y: 8,
+        max_x: 16,
+        max_y: 32,
+        size: 64,
+        ..Default::default()
+    };
+
+    let texcoord32 = texcoord.to_f32_inset();
+    assert_eq!(
+        (texcoord32.min_x, texcoord32.max_x),
+        (0.5 / 64.0, 15.5 / 64.0)
+    );
+    assert_eq!(
+        (texcoord32.min_y, texcoord32.max_y),
+        (8.5 / 64.0, 31.5 / 64.0)
+    );
+
+    let texcoord64 = texcoord.to_f64_inset();
+    assert_eq!(
+        (texcoord64.min_x, texcoord64.max_x),
+        (0.5 / 64.0, 15.5 / 64.0)
+    );
+    assert_eq!(
+        (texcoord64.min_y, texcoord64.max_y),
+        (8.5 / 64.0, 31.5 / 64.0)
+    );
+}
+
+#[test]
+fn pages() {
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 64,
+        mip: AtlasMipOption::NoMip,
+        entries: &[
+            AtlasEntry {
+                texture: image::RgbImage::new(64, 48),
+                ..Default::default()
+            },
+            AtlasEntry {
+                texture: image::RgbImage::new(64, 32),
+                ..Default::default()
+            },
+            AtlasEntry {
+                texture: image::RgbImage::new(16, 16),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let mut count = 0;
+    for (page, texture, texcoords) in atlas.pages() {
+        assert!(std::ptr::eq(texture, &atlas.textures[page as usize]));
+        for (i, texcoord) in texcoords {
+            assert_eq!(texcoord.page, page);
+            assert_eq!(texcoord, &atlas.texcoords[i]);
+            count += 1;
+        }
+    }
+    assert_eq!(count, atlas.texcoords.len());
+
+    let page = atlas.texcoords[1].page;
+    assert!(atlas.texcoords_on_page(page).any(|(i, _)| i == 1));
+}
+
+#[test]
+fn reserve() {
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMipWithPadding(1),
+        background: Some(image::Rgb([255, 0, 255])),
+        reserve: &[(24, 16)],
+        entries: &[AtlasEntry {
+            texture: image::RgbImage::from_pixel(32, 32, image::Rgb([0, 255, 0])),
+            ..Default::default()
+        }],
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_eq!(atlas.texcoords.len(), 1);
+    assert_eq!(atlas.reserved.len(), 1);
+
+    let texcoord = &atlas.reserved[0];
+    assert_eq!(texcoord.max_x - texcoord.min_x, 24);
+    assert_eq!(texcoord.max_y - texcoord.min_y, 16);
+    assert_eq!((texcoord.source_width, texcoord.source_height), (24, 16));
+
+    let mip_map = &atlas.textures[0].mip_maps[0];
+    for y in texcoord.min_y..texcoord.max_y {
+        for x in texcoord.min_x..texcoord.max_x {
+            assert_eq!(*mip_map.get_pixel(x, y), image::Rgb([255, 0, 255]));
+        }
+    }
+
+    let atlas = create_atlas(&AtlasDescriptor::<image::RgbImage> {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Nearest, 16),
+        reserve: &[(24, 16)],
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.reserved[0].max_x - atlas.reserved[0].min_x, 24);
+}
+
+#[test]
+fn mip_filter_override() {
+    let checker =
+        image::GrayImage::from_fn(16, 16, |x, y| image::Luma([((x + y) % 2 * 255) as u8]));
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 128,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 32),
+        entries: &[
+            AtlasEntry {
+                texture: checker.clone(),
+                mip: AtlasEntryMipOption::Repeat,
+                ..Default::default()
+            },
+            AtlasEntry {
+                texture: checker,
+                mip: AtlasEntryMipOption::Repeat,
+                mip_filter: Some(AtlasMipFilter::Nearest),
+                ..Default::default()
+            },
+        ],
+        deduplicate: true,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_ne!(atlas.texcoords[0], atlas.texcoords[1]);
+
+    let mip_map = &atlas.textures[0].mip_maps[1];
+    let sample =
+        |texcoord: &Texcoord| mip_map.get_pixel(texcoord.min_x / 2 + 2, texcoord.min_y / 2 + 2)[0];
+    assert!((100..=155).contains(&sample(&atlas.texcoords[0])));
+    assert!([0, 255].contains(&sample(&atlas.texcoords[1])));
+}
+
+#[test]
+fn zero_sized_entry() {
+    let entries = &[
+        AtlasEntry {
+            texture: image::RgbImage::new(8, 8),
+            ..Default::default()
+        },
+        AtlasEntry {
+            texture: image::RgbImage::new(0, 8),
+            ..Default::default()
+        },
+    ];
+
+    for mip in [
+        AtlasMipOption::NoMip,
+        AtlasMipOption::Mip(AtlasMipFilter::Nearest),
+        AtlasMipOption::MipWithBlock(AtlasMipFilter::Nearest, 16),
+    ] {
+        let result = create_atlas(&AtlasDescriptor {
+            max_page_count: 1,
+            size: 64,
+            mip,
+            entries,
+            ..Default::default()
+        });
+        assert!(matches!(
+            result,
+            Err(AtlasError::ZeroSizedEntry { index: 1 })
+        ));
+    }
+
+    let result = AtlasDescriptor::builder()
+        .max_page_count(1)
+        .size(64)
+        .reserve(&[(4, 0)])
+        .entries(&entries[..1])
+        .build();
+    assert!(matches!(
+        result,
+        Err(AtlasError::ZeroSizedEntry { index: 1 })
+    ));
+}
+
+#[test]
+fn error_source() {
+    use std::error::Error;
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 16,
+        mip: AtlasMipOption::NoMip,
+        entries: &[
+            AtlasEntry {
+                texture: image::RgbImage::new(16, 16),
+                ..Default::default()
+            },
+            AtlasEntry {
+                texture: image::RgbImage::new(16, 16),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    });
+    let err = result.unwrap_err();
+    assert!(matches!(err, AtlasError::Packing(_)));
+    let source = err.source().unwrap();
+    assert_eq!(source.to_string(), err.to_string());
+
+    assert!(AtlasError::ZeroEntry.source().is_none());
+}
+
+#[test]
+fn internal_error() {
+    use std::error::Error;
+
+    let err = AtlasError::Internal("resample position out of the source bounds");
+    assert_eq!(
+        err.to_string(),
+        "internal error: resample position out of the source bounds."
+    );
+    assert!(err.source().is_none());
+}
+
+#[test]
+fn pack_heuristic() {
+    let entries = (0..24)
+        .map(|i| AtlasEntry {
+            texture: image::GrayImage::new(8 + i * 7 % 40, 8 + i * 13 % 36),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    for pack_heuristic in [
+        PackHeuristic::ContainsSmallestBox,
+        PackHeuristic::ContainsLargestBox,
+    ] {
+        let atlas = create_atlas(&AtlasDescriptor {
+            max_page_count: 4,
+            size: 128,
+            mip: AtlasMipOption::NoMipWithPadding(1),
+            pack_heuristic,
+            entries: &entries,
+            ..Default::default()
+        })
+        .unwrap();
+
+        for (i, a) in atlas.texcoords.iter().enumerate() {
+            assert_eq!(a.max_x - a.min_x, entries[i].texture.width());
+            assert_eq!(a.max_y - a.min_y, entries[i].texture.height());
+            assert!(a.max_x <= atlas.size && a.max_y <= atlas.size);
+
+            for b in &atlas.texcoords[i + 1..] {
+                let overlap = a.page == b.page
+                    && a.min_x < b.max_x
+                    && b.min_x < a.max_x
+                    && a.min_y < b.max_y
+                    && b.min_y < a.max_y;
+                assert!(!overlap, "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+}
+
+#[test]
+fn insert() {
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 64,
+        mip: AtlasMipOption::NoMipWithPadding(1),
+        entries: &[AtlasEntry {
+            texture: image::RgbImage::from_pixel(40, 40, image::Rgb([255, 0, 0])),
+            ..Default::default()
+        }],
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 1);
+
+    let entry = AtlasEntry {
+        texture: image::RgbImage::from_pixel(20, 20, image::Rgb([0, 255, 0])),
+        ..Default::default()
+    };
+    let texcoord = atlas.insert(&entry).unwrap();
+    assert_eq!(atlas.texcoords.len(), 2);
+    assert_eq!(atlas.texcoords[1], texcoord);
+    assert_eq!(texcoord.page, 0);
+
+    let first = atlas.texcoords[0];
+    let overlap = first.min_x < texcoord.max_x
+        && texcoord.min_x < first.max_x
+        && first.min_y < texcoord.max_y
+        && texcoord.min_y < first.max_y;
+    assert!(!overlap);
+
+    let mip_map = &atlas.textures[0].mip_maps[0];
+    assert_eq!(
+        *mip_map.get_pixel(texcoord.min_x, texcoord.min_y),
+        image::Rgb([0, 255, 0])
+    );
+    assert_eq!(
+        *mip_map.get_pixel(first.min_x, first.min_y),
+        image::Rgb([255, 0, 0])
+    );
+
+    let large = AtlasEntry {
+        texture: image::RgbImage::from_pixel(50, 50, image::Rgb([0, 0, 255])),
+        ..Default::default()
+    };
+    let texcoord = atlas.insert(&large).unwrap();
+    assert_eq!(texcoord.page, 1);
+    assert_eq!(atlas.page_count, 2);
+    assert_eq!(atlas.textures.len(), 2);
+
+    let result = atlas.insert(&large);
+    assert!(matches!(result, Err(AtlasError::Packing(_))));
+    assert_eq!(atlas.texcoords.len(), 3);
+}
+
+#[test]
+fn texcoord_table_f32() {
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 64,
+        mip: AtlasMipOption::NoMip,
+        entries: &[
+            AtlasEntry {
+                texture: image::RgbImage::new(64, 48),
+                ..Default::default()
+            },
+            AtlasEntry {
+                texture: image::RgbImage::new(32, 32),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let (rects, pages) = atlas.texcoord_table_f32();
+    assert_eq!(rects.len(), atlas.texcoords.len());
+    assert_eq!(pages.len(), atlas.texcoords.len());
+    for (i, texcoord) in atlas.texcoords.iter().enumerate() {
+        let texcoord = texcoord.to_f32();
+        assert_eq!(
+            rects[i],
+            [
+                texcoord.min_x,
+                texcoord.min_y,
+                texcoord.max_x,
+                texcoord.max_y
+            ]
+        );
+        assert_eq!(pages[i], texcoord.page);
+    }
+}
+
+#[test]
+fn create_into() {
+    let desc = AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        entries: &[AtlasEntry {
+            texture: image::RgbImage::from_pixel(32, 32, image::Rgb([255, 0, 0])),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let mut atlas = create_atlas(&desc).unwrap();
+    let ptr = atlas.textures[0].mip_maps[0].as_ptr();
+
+    let desc = AtlasDescriptor {
+        entries: &[AtlasEntry {
+            texture: image::RgbImage::from_pixel(16, 16, image::Rgb([0, 0, 255])),
+            ..Default::default()
+        }],
+        ..desc
+    };
+    create_atlas_into(&mut atlas, &desc).unwrap();
+    assert_eq!(atlas.textures[0].mip_maps[0].as_ptr(), ptr);
+
+    let expected = create_atlas(&desc).unwrap();
+    assert_eq!(atlas.texcoords, expected.texcoords);
+    for (texture, expected) in atlas.textures.iter().zip(&expected.textures) {
+        assert_eq!(texture.mip_maps, expected.mip_maps);
+    }
+
+    let desc = AtlasDescriptor { size: 128, ..desc };
+    create_atlas_into(&mut atlas, &desc).unwrap();
+    assert_eq!(atlas.textures[0].size, 128);
+
+    let desc = AtlasDescriptor { size: 100, ..desc };
+    assert!(create_atlas_into(&mut atlas, &desc).is_err());
+    assert_eq!(atlas.textures.len(), 1);
+}
+
+#[test]
+fn block_clamp_no_bleed() {
+    let red = image::Rgba([255, 0, 0, 255]);
+    let blue = image::Rgba([0, 0, 255, 255]);
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 128,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 16),
+        entries: &[
+            AtlasEntry {
+                texture: image::RgbaImage::from_pixel(20, 20, red),
+                mip: AtlasEntryMipOption::Clamp,
+                ..Default::default()
+            },
+            AtlasEntry {
+                texture: image::RgbaImage::from_pixel(30, 12, blue),
+                mip: AtlasEntryMipOption::Clamp,
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let mip_level = atlas.mip_level_count - 1;
+    let mip_map = &atlas.textures[0].mip_maps[mip_level as usize];
+    for (texcoord, color) in atlas.texcoords.iter().zip([red, blue]) {
+        // bilinear footprint of the texcoord rect at the coarsest mip level
+        let min_x = (texcoord.min_x as f32 / (1 << mip_level) as f32 - 0.5).floor() as u32;
+        let min_y = (texcoord.min_y as f32 / (1 << mip_level) as f32 - 0.5).floor() as u32;
+        let max_x = (texcoord.max_x as f32 / (1 << mip_level) as f32 - 0.5).ceil() as u32;
+        let max_y = (texcoord.max_y as f32 / (1 << mip_level) as f32 - 0.5).ceil() as u32;
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                assert_eq!(*mip_map.get_pixel(x, y), color);
+            }
+        }
+    }
+}
+
+#[test]
+fn sdf() {
+    let texture = image::GrayImage::from_fn(32, 32, |x, y| {
+        let (dx, dy) = (x as f32 - 15.5, y as f32 - 15.5);
+        image::Luma([if dx * dx + dy * dy < 64.0 { 255 } else { 0 }])
+    });
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMip,
+        sdf: Some(SdfParams { spread: 4.0 }),
+        entries: &[AtlasEntry {
+            texture: texture.clone(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let texcoord = &atlas.texcoords[0];
+    let mip_map = &atlas.textures[0].mip_maps[0];
+    let value = |x: u32, y: u32| mip_map.get_pixel(texcoord.min_x + x, texcoord.min_y + y)[0];
+
+    assert_eq!(value(16, 16), 255);
+    assert_eq!(value(0, 0), 0);
+    for x in 0..31 {
+        let (a, b) = (value(x, 16), value(x + 1, 16));
+        assert_eq!(texture.get_pixel(x, 16)[0] == 255, a > 127);
+        if x < 15 {
+            assert!(a <= b);
+        }
+    }
+}
+
+#[test]
+fn create_as() {
+    let atlas = create_atlas_as::<image::Rgba<u8>, _>(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMip,
+        background: Some(image::Rgb([0, 0, 255])),
+        entries: &[AtlasEntry {
+            texture: image::RgbImage::from_pixel(16, 16, image::Rgb([255, 0, 0])),
+            ..Default::default()
+        }],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let texcoord = &atlas.texcoords[0];
+    let mip_map = &atlas.textures[0].mip_maps[0];
+    assert_eq!(
+        *mip_map.get_pixel(texcoord.min_x, texcoord.min_y),
+        image::Rgba([255, 0, 0, 255])
+    );
+    assert_eq!(*mip_map.get_pixel(63, 63), image::Rgba([0, 0, 255, 255]));
+}
+
+#[test]
+fn stable() {
+    // extents of equal area are placed in input order without `stable`.
+    let extents = [
+        (8, 64),
+        (16, 32),
+        (32, 16),
+        (64, 8),
+        (8, 32),
+        (16, 16),
+        (32, 8),
+        (24, 24),
+    ];
+    let entries = extents
+        .iter()
+        .map(|&(width, height)| AtlasEntry {
+            texture: image::GrayImage::new(width, height),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    let reversed = entries.iter().rev().cloned().collect::<Vec<_>>();
+
+    let desc = AtlasDescriptor {
+        max_page_count: 2,
+        size: 128,
+        mip: AtlasMipOption::NoMipWithPadding(1),
+        stable: true,
+        entries: &entries,
+        ..Default::default()
+    };
+    let atlas = create_atlas(&desc).unwrap();
+    let reversed_atlas = create_atlas(&AtlasDescriptor {
+        entries: &reversed,
+        ..desc.clone()
+    })
+    .unwrap();
+
+    let n = entries.len();
+    for (i, texcoord) in atlas.texcoords.iter().enumerate() {
+        assert_eq!(texcoord, &reversed_atlas.texcoords[n - 1 - i]);
+    }
+}
+
+#[test]
+fn single_entry() {
+    let texture = image::RgbaImage::from_fn(62, 62, |x, y| image::Rgba([x as u8, y as u8, 0, 255]));
+    let entry = AtlasEntry {
+        texture,
+        ..Default::default()
+    };
+
+    // a single entry filling the page skips the packer.
+    let desc = AtlasDescriptor {
+        max_page_count: 2,
+        size: 64,
+        mip: AtlasMipOption::NoMipWithPadding(1),
+        entries: std::slice::from_ref(&entry),
+        ..Default::default()
+    };
+    let mut atlas = create_atlas(&desc).unwrap();
+
+    let entries = [entry.clone(), entry.clone()];
+    let general = create_atlas(&AtlasDescriptor {
+        entries: &entries,
+        ..desc.clone()
+    })
+    .unwrap();
+
+    assert_eq!(atlas.page_count, 1);
+    assert_eq!(atlas.texcoords[0], general.texcoords[0]);
+    assert_eq!(atlas.textures[0].mip_maps, general.textures[0].mip_maps);
+
+    let texcoord = atlas.insert(&entry).unwrap();
+    assert_eq!(texcoord, general.texcoords[1]);
+}
+
+#[test]
+fn luma16_mip() {
+    let texture = image::ImageBuffer::from_fn(32, 32, |x, _| match x % 2 {
+        0 => image::Luma([0u16]),
+        _ => image::Luma([u16::MAX]),
+    });
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        entries: &[AtlasEntry {
+            texture,
+            ..Default::default()
+        }],
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_eq!(atlas.mip_level_count, 6);
+    // the border pixels are affected by the edge clamping of filter
+    for mip_map in &atlas.textures[0].mip_maps[1..4] {
+        for x in 1..mip_map.width() - 1 {
+            let pixel = mip_map.get_pixel(x, 0);
+            assert!(pixel.0[0].abs_diff(u16::MAX / 2) <= 1, "{:?}", pixel);
+        }
+    }
+}
+
+#[test]
+fn rgba32f_mip() {
+    let texture = image::Rgba32FImage::from_fn(32, 32, |x, y| match (x + y) % 2 {
+        0 => image::Rgba([4.0, -1.0, 0.5, 1.0]),
+        _ => image::Rgba([2.0, 1.0, 0.5, 1.0]),
+    });
+
+    for mip in [
+        AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 32),
+    ] {
+        let atlas = create_atlas(&AtlasDescriptor {
+            max_page_count: 1,
+            size: 64,
+            mip,
+            entries: &[AtlasEntry {
+                texture: texture.clone(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+
+        // samples an interior pixel of the entry, where the checker is averaged
+        let texcoord = &atlas.texcoords[0];
+        let mip_map = &atlas.textures[0].mip_maps[2];
+        let (x, y) = (texcoord.min_x / 4 + 2, texcoord.min_y / 4 + 2);
+        let value = mip_map.get_pixel(x, y).0;
+        let expected = [3.0, 0.0, 0.5, 1.0];
+        for (value, expected) in value.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-3, "{:?}", value);
+        }
+    }
+}
+
+#[test]
+fn non_finite_subpixel() {
+    let mut texture = image::Rgba32FImage::new(8, 8);
+    texture.put_pixel(3, 3, image::Rgba([f32::NAN, 0.0, 0.0, 1.0]));
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        entries: &[AtlasEntry {
+            texture,
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+
+    assert!(matches!(
+        result,
+        Err(AtlasError::NonFiniteSubpixel { index: 0 })
+    ));
+}
+
+#[test]
+fn entry_at() {
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMipWithPadding(2),
+        entries: &[
+            AtlasEntry {
+                texture: image::GrayImage::new(16, 16),
+                ..Default::default()
+            },
+            AtlasEntry {
+                texture: image::GrayImage::new(8, 24),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    })
+    .unwrap();
+
+    for (i, texcoord) in atlas.texcoords.iter().enumerate() {
+        let (x, y) = (texcoord.min_x, texcoord.min_y);
+        assert_eq!(atlas.entry_at(0, x, y), Some(i));
+        assert_eq!(
+            atlas.entry_at(0, texcoord.max_x - 1, texcoord.max_y - 1),
+            Some(i)
+        );
+        assert_eq!(atlas.entry_at(0, x - 1, y), None);
+        assert_eq!(atlas.entry_at(0, texcoord.max_x, y), None);
+        assert_eq!(atlas.entry_at(1, x, y), None);
+    }
+}
+
+#[test]
+fn block_waste() {
+    let entries = [
+        AtlasEntry {
+            texture: image::GrayImage::new(32, 32),
+            ..Default::default()
+        },
+        AtlasEntry {
+            texture: image::GrayImage::new(33, 20),
+            ..Default::default()
+        },
+    ];
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 256,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 32),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    // 33x20 with half-block gutter is 65x52, rounded up to 96x64
+    assert_eq!(atlas.block_waste, vec![0, 96 * 64 - 65 * 52]);
+
+    atlas.insert(&entries[1]).unwrap();
+    assert_eq!(atlas.block_waste, vec![0, 2764, 2764]);
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 256,
+        mip: AtlasMipOption::MipWithPadding(AtlasMipFilter::Linear, 4),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.block_waste, vec![0, 0]);
+}
+
+#[test]
+fn create_owned() {
+    fn build(size: u32) -> Atlas<image::Rgba<u8>> {
+        let entries = (1..4)
+            .map(|i| AtlasEntry {
+                texture: image::RgbaImage::new(i * 8, i * 8),
+                ..Default::default()
+            })
+            .collect();
+        let desc = AtlasDescriptor {
+            max_page_count: 1,
+            size,
+            mip: AtlasMipOption::NoMipWithPadding(1),
+            ..Default::default()
+        };
+        create_atlas_owned(&desc, entries).unwrap()
+    }
+
+    let atlas = build(64);
+    assert_eq!(atlas.texcoords.len(), 3);
+    assert_eq!(atlas.texcoords[2].max_x - atlas.texcoords[2].min_x, 24);
+}
+
+#[test]
+fn center_anchor() {
+    let texture = image::RgbaImage::from_pixel(40, 20, image::Rgba([255, 0, 0, 255]));
+    let entry = |anchor| AtlasEntry {
+        texture: texture.clone(),
+        anchor,
+        ..Default::default()
+    };
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 256,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 32),
+        entries: &[
+            entry(AtlasEntryAnchor::TopLeft),
+            entry(AtlasEntryAnchor::Center),
+        ],
+        ..Default::default()
+    })
+    .unwrap();
+    let inserted = atlas.insert(&entry(AtlasEntryAnchor::Center)).unwrap();
+
+    // 40x20 with half-block gutter is 72x52, rounded up to 96x64
+    let top_left = &atlas.texcoords[0];
+    assert_eq!(top_left.min_x % 32, 16);
+    assert_eq!(top_left.min_y % 32, 16);
+    for center in [&atlas.texcoords[1], &inserted] {
+        assert_eq!(center.min_x % 32, 28);
+        assert_eq!(center.min_y % 32, 22);
+        assert_eq!(center.max_x - center.min_x, 40);
+        assert_eq!(center.max_y - center.min_y, 20);
+
+        let page = &atlas.textures[center.page as usize].mip_maps[0];
+        assert_eq!(
+            page.get_pixel(center.min_x, center.min_y).0,
+            [255, 0, 0, 255]
+        );
+        assert_eq!(
+            page.get_pixel(center.max_x - 1, center.max_y - 1).0,
+            [255, 0, 0, 255]
+        );
+    }
+}
+
+#[test]
+fn on_progress() {
+    let entries = (1..5)
+        .map(|i| AtlasEntry {
+            texture: image::RgbaImage::new(i * 8, i * 8),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    let progress = std::cell::RefCell::new(Vec::new());
+    let on_progress = |p: AtlasProgress| progress.borrow_mut().push(p);
+    create_atlas(
+        &AtlasDescriptor::builder()
+            .max_page_count(1)
+            .size(256)
+            .mip(AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 32))
+            .on_progress(&on_progress)
+            .entries(&entries)
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let progress = progress.into_inner();
+    let count = |stage| progress.iter().filter(|p| p.stage == stage).count();
+    assert_eq!(count(AtlasProgressStage::Packing), 2);
+    assert_eq!(count(AtlasProgressStage::Blitting), 4);
+    assert_eq!(count(AtlasProgressStage::MipGeneration), 4);
+    assert_eq!(
+        progress.last(),
+        Some(&AtlasProgress {
+            stage: AtlasProgressStage::MipGeneration,
+            done: 4,
+            total: 4,
+        })
+    );
+}
+
+#[test]
+fn page_base_occupied() {
+    let entries = [
+        AtlasEntry {
+            texture: image::GrayImage::new(100, 60),
+            ..Default::default()
+        },
+        AtlasEntry {
+            texture: image::GrayImage::new(40, 90),
+            ..Default::default()
+        },
+    ];
+
+    let first = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 256,
+        mip: AtlasMipOption::NoMipWithPadding(2),
+        page_base: 3,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(first.texcoords.iter().all(|texcoord| texcoord.page == 3));
+    assert_eq!(first.pages().next().unwrap().0, 3);
+
+    let second = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 256,
+        mip: AtlasMipOption::NoMipWithPadding(2),
+        page_base: 3,
+        occupied: &first.texcoords,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    for a in &second.texcoords {
+        assert_eq!(a.page, 3);
+        for b in &first.texcoords {
+            let apart = a.max_x + 2 <= b.min_x
+                || b.max_x + 2 <= a.min_x
+                || a.max_y + 2 <= b.min_y
+                || b.max_y + 2 <= a.min_y;
+            assert!(apart, "{a:?} overlaps {b:?}");
+        }
+    }
+}
+
+#[test]
+fn round_up_pow2() {
+    assert_eq!(image_atlas::round_up_pow2(0), 1);
+    assert_eq!(image_atlas::round_up_pow2(1000), 1024);
+    assert_eq!(image_atlas::round_up_pow2(4096), 4096);
+    assert_eq!(image_atlas::round_up_pow2(u32::MAX), 1 << 31);
+}
+
+#[test]
+fn generate_mips() {
+    let entries = [
+        AtlasEntry {
+            texture: image::GrayImage::from_pixel(32, 32, image::Luma([255])),
+            ..Default::default()
+        },
+        AtlasEntry {
+            texture: image::GrayImage::from_pixel(32, 32, image::Luma([255])),
+            generate_mips: false,
+            ..Default::default()
+        },
+    ];
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 256,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 32),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(atlas.mip_level_count > 1);
+
+    let texture = &atlas.textures[0];
+    for (i, texcoord) in atlas.texcoords.iter().enumerate() {
+        let center_x = (texcoord.min_x + texcoord.max_x) / 2;
+        let center_y = (texcoord.min_y + texcoord.max_y) / 2;
+        assert_eq!(texture.mip_maps[0].get_pixel(center_x, center_y).0, [255]);
+
+        let mip = texture.mip_maps[1]
+            .get_pixel(center_x >> 1, center_y >> 1)
+            .0;
+        assert_eq!(mip, if i == 0 { [255] } else { [0] });
+    }
+}
+
+#[test]
+fn free_rects() {
+    let entries = [
+        AtlasEntry {
+            texture: image::GrayImage::new(100, 60),
+            ..Default::default()
+        },
+        AtlasEntry {
+            texture: image::GrayImage::new(40, 90),
+            ..Default::default()
+        },
+    ];
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 256,
+        mip: AtlasMipOption::NoMip,
+        collect_free_rects: true,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.free_rects.len(), 1);
+
+    let area = |t: &Texcoord| (t.max_x - t.min_x) * (t.max_y - t.min_y);
+    let overlaps = |a: &Texcoord, b: &Texcoord| {
+        a.min_x < b.max_x && b.min_x < a.max_x && a.min_y < b.max_y && b.min_y < a.max_y
+    };
+    let free_rects = &atlas.free_rects[0];
+    let free_area = free_rects.iter().map(area).sum::<u32>();
+    assert_eq!(free_area, 256 * 256 - 100 * 60 - 40 * 90);
+    for (i, a) in free_rects.iter().enumerate() {
+        assert!(atlas.texcoords.iter().all(|b| !overlaps(a, b)));
+        assert!(free_rects[i + 1..].iter().all(|b| !overlaps(a, b)));
+    }
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 256,
+        mip: AtlasMipOption::NoMip,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(atlas.free_rects.is_empty());
+}
+
+#[cfg(feature = "skyline")]
+#[test]
+fn skyline() {
+    let entries = (0..64)
+        .map(|i| AtlasEntry {
+            texture: image::GrayImage::new(16 + i % 3 * 8, 24),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 256,
+        mip: AtlasMipOption::NoMipWithPadding(1),
+        pack_algorithm: PackAlgorithm::Skyline,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    atlas.insert(&entries[0]).unwrap();
+    let overlaps = |a: &Texcoord, b: &Texcoord| {
+        a.min_x < b.max_x + 1
+            && b.min_x < a.max_x + 1
+            && a.min_y < b.max_y + 1
+            && b.min_y < a.max_y + 1
+    };
+    for (i, a) in atlas.texcoords.iter().enumerate() {
+        assert!(a.max_x <= 256 && a.max_y <= 256);
+        assert!(atlas.texcoords[i + 1..].iter().all(|b| !overlaps(a, b)));
+    }
+    // entries of equal height are packed in rows starting at the top edge
+    assert_eq!(atlas.texcoords.iter().map(|t| t.min_y).min(), Some(1));
+    assert!(atlas.texcoords[..64]
+        .iter()
+        .all(|t| (t.min_y - 1) % 26 == 0));
+}
+
+#[cfg(feature = "shelf")]
+#[test]
+fn shelf() {
+    let entries = (0..300)
+        .map(|i| AtlasEntry::new(image::GrayImage::new(4 + i % 7, 6 + i % 5)))
+        .collect::<Vec<_>>();
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 0,
+        size: 128,
+        mip: AtlasMipOption::NoMipWithPadding(1),
+        pack_algorithm: PackAlgorithm::Shelf,
+        collect_free_rects: true,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    atlas.verify_layout().unwrap();
+    let free_area = atlas
+        .free_rects
+        .iter()
+        .flatten()
+        .map(|free| (free.max_x - free.min_x) * (free.max_y - free.min_y));
+    assert!(free_area.sum::<u32>() > 0);
+
+    // the tallest entries are packed in the first row from the top-left corner.
+    let first = atlas
+        .texcoords
+        .iter()
+        .filter(|t| t.page == 0 && t.min_y == 1);
+    assert!(first.clone().all(|t| t.max_y - t.min_y == 10));
+    assert!(first.clone().any(|t| t.min_x == 1));
+
+    for entry in &entries[..20] {
+        atlas.insert(entry).unwrap();
+    }
+    atlas.verify_layout().unwrap();
+}
+
+#[test]
+fn contact_sheet() {
+    let entries = (0..3)
+        .map(|i| AtlasEntry {
+            texture: image::GrayImage::from_pixel(64, 64, image::Luma([10 + i])),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 3,
+        size: 64,
+        mip: AtlasMipOption::NoMip,
+        background: Some(image::Luma([255])),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 3);
+
+    let sheet = atlas.contact_sheet(2);
+    assert_eq!(sheet.dimensions(), (128, 128));
+    for (i, texture) in atlas.textures.iter().enumerate() {
+        let (x, y) = (i as u32 % 2 * 64, i as u32 / 2 * 64);
+        assert_eq!(
+            sheet.get_pixel(x + 5, y + 5),
+            texture.mip_maps[0].get_pixel(5, 5)
+        );
+    }
+    assert_eq!(sheet.get_pixel(100, 100).0, [255]);
+
+    assert_eq!(atlas.contact_sheet(0).dimensions(), (64, 192));
+    assert_eq!(atlas.contact_sheet(8).dimensions(), (192, 64));
+}
+
+#[test]
+fn group() {
+    let groups = [Some(7), None, None, Some(7)];
+    let entries = groups
+        .iter()
+        .map(|&group| AtlasEntry {
+            texture: image::GrayImage::new(64, 128),
+            group,
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 128,
+        mip: AtlasMipOption::NoMip,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.texcoords[0].page, atlas.texcoords[3].page);
+    assert_eq!(atlas.texcoords[1].page, atlas.texcoords[2].page);
+
+    // a group larger than a page is packed ungrouped
+    let entries = entries
+        .into_iter()
+        .map(|entry| AtlasEntry {
+            group: Some(9),
+            ..entry
+        })
+        .collect::<Vec<_>>();
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 128,
+        mip: AtlasMipOption::NoMip,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 2);
+
+    // only the group larger than a page is packed ungrouped, and the other one is kept
+    let groups = [Some(9), Some(9), Some(7), Some(9), Some(7)];
+    let entries = groups
+        .iter()
+        .map(|&group| AtlasEntry {
+            texture: image::GrayImage::new(64, 128),
+            group,
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 3,
+        size: 128,
+        mip: AtlasMipOption::NoMip,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 3);
+    assert_eq!(atlas.texcoords[2].page, atlas.texcoords[4].page);
+}
+
+#[test]
+fn mip_padding() {
+    let entries = [AtlasEntry {
+        texture: image::GrayImage::new(40, 20),
+        ..Default::default()
+    }];
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 128,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 32),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    let texture = &atlas.textures[0];
+    assert_eq!(texture.padding, 16);
+    assert_eq!(texture.mip_padding(1), 8);
+    assert_eq!(texture.mip_padding(5), 0);
+    assert_eq!(texture.mip_padding(40), 0);
+
+    let big = AtlasEntry {
+        texture: image::GrayImage::new(96, 96),
+        ..Default::default()
+    };
+    atlas.insert(&big).unwrap();
+    assert_eq!(atlas.textures[1].padding, 16);
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 128,
+        mip: AtlasMipOption::NoMipWithPadding(2),
+        extrude: 1,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.textures[0].padding, 3);
+}
+
+#[test]
+fn save_exr() {
+    let entries = [AtlasEntry {
+        texture: image::Rgb32FImage::from_fn(16, 16, |x, y| image::Rgb([x as f32, y as f32, 2.5])),
+        ..Default::default()
+    }];
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        mip: AtlasMipOption::NoMip,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let dir_path = path::Path::new("target/img");
+    fs::create_dir_all(dir_path).unwrap();
+    atlas
+        .save_pages_with_format(dir_path, "exr", image::ImageFormat::OpenExr)
+        .unwrap();
+
+    let page = image::open(dir_path.join("exr-0-0.exr"))
+        .unwrap()
+        .into_rgb32f();
+    assert_eq!(page, atlas.textures[0].mip_maps[0]);
+
+    let entries = [AtlasEntry {
+        texture: image::Rgba32FImage::new(16, 16),
+        ..Default::default()
+    }];
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        mip: AtlasMipOption::NoMip,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    let err = atlas
+        .save_pages_with_format(dir_path, "unsupported", image::ImageFormat::Png)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    assert!(!dir_path.join("unsupported-0-0.png").exists());
+}
+
+#[test]
+fn shrink_pages() {
+    let entries = [AtlasEntry {
+        texture: image::GrayImage::new(300, 200),
+        ..Default::default()
+    }];
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 1024,
+        mip: AtlasMipOption::MipWithPadding(AtlasMipFilter::Linear, 4),
+        shrink_pages: true,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    let texture = &atlas.textures[0];
+    assert_eq!(texture.size, 512);
+    assert_eq!(texture.mip_level_count, 10);
+    assert_eq!(texture.mip_maps.len(), 10);
+    assert_eq!(texture.mip_maps[1].dimensions(), (256, 256));
+    assert_eq!(atlas.texcoords[0].size, 512);
+    assert_eq!(atlas.stats().total_area, 512 * 512);
+    assert!(matches!(
+        atlas.insert(&entries[0]),
+        Err(AtlasError::NoPackingState)
+    ));
+}
+
+#[test]
+fn overflow() {
+    let entries = [AtlasEntry {
+        texture: image::GrayImage::new(16, 16),
+        ..Default::default()
+    }];
+
+    // the second page of a full-page entry is past `u32::MAX`
+    let pages = [
+        AtlasEntry {
+            texture: image::GrayImage::new(48, 48),
+            ..Default::default()
+        },
+        AtlasEntry {
+            texture: image::GrayImage::new(48, 48),
+            ..Default::default()
+        },
+    ];
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 64,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 16),
+        page_base: u32::MAX,
+        entries: &pages,
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(AtlasError::Overflow)));
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMipWithPadding(u32::MAX / 2),
+        entries: &entries,
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(AtlasError::Overflow)));
+}
+
+#[test]
+fn block_rect() {
+    let entries = (0..4)
+        .map(|_| AtlasEntry {
+            texture: image::GrayImage::new(48, 8),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 128,
+        mip: AtlasMipOption::MipWithBlockRect(AtlasMipFilter::Nearest, 64, 16),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 1);
+    assert_eq!(atlas.mip_level_count, 5);
+    assert_eq!(atlas.textures[0].padding, 8);
+    assert_eq!(atlas.block_waste, vec![128 * 32 - 112 * 24; 4]);
+
+    let mut min_ys = atlas
+        .texcoords
+        .iter()
+        .map(|texcoord| {
+            assert_eq!((texcoord.min_x, texcoord.max_x), (32, 80));
+            texcoord.min_y
+        })
+        .collect::<Vec<_>>();
+    min_ys.sort_unstable();
+    assert_eq!(min_ys, vec![8, 40, 72, 104]);
+
+    let texcoord = atlas.insert(&entries[0]).unwrap();
+    assert_eq!((texcoord.page, texcoord.min_x, texcoord.min_y), (1, 32, 8));
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 128,
+        mip: AtlasMipOption::MipWithBlockRect(AtlasMipFilter::Nearest, 64, 12),
+        entries: &entries,
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(AtlasError::InvalidBlockSize(12))));
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn load_dir() {
+    let dir_path = path::Path::new("target/img/entries");
+    let _ = fs::remove_dir_all(dir_path);
+    fs::create_dir_all(dir_path.join("nested.png")).unwrap();
+    image::GrayImage::new(8, 4)
+        .save(dir_path.join("b.png"))
+        .unwrap();
+    image::RgbImage::new(2, 6)
+        .save(dir_path.join("a.png"))
+        .unwrap();
+    fs::write(dir_path.join("readme.txt"), "not an image").unwrap();
+
+    let (entries, names) = entries_from_dir(dir_path, AtlasEntryMipOption::Repeat).unwrap();
+    assert_eq!(names, vec!["a", "b"]);
+    assert_eq!(entries[0].texture.dimensions(), (2, 6));
+    assert_eq!(entries[1].texture.dimensions(), (8, 4));
+    assert!(entries
+        .iter()
+        .all(|entry| entry.mip == AtlasEntryMipOption::Repeat));
+
+    fs::write(dir_path.join("c.png"), "broken").unwrap();
+    let err = entries_from_dir(dir_path, AtlasEntryMipOption::Clamp).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("c.png"));
+}
+
+#[test]
+fn unbounded_page_count() {
+    let entries = (0..10)
+        .map(|_| AtlasEntry {
+            texture: image::GrayImage::new(40, 40),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 64,
+        entries: &entries,
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(AtlasError::Packing(_))));
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 0,
+        size: 64,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 10);
+    assert_eq!(atlas.textures.len(), 10);
+
+    let texcoord = atlas.insert(&entries[0]).unwrap();
+    assert_eq!(texcoord.page, 10);
+    assert_eq!(atlas.page_count, 11);
+}
+
+#[test]
+fn insert_unbounded_occupied() {
+    let occupied = [Texcoord {
+        page: 1,
+        min_x: 0,
+        min_y: 0,
+        max_x: 64,
+        max_y: 64,
+        size: 64,
+        ..Default::default()
+    }];
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 0,
+        size: 64,
+        occupied: &occupied,
+        entries: &[AtlasEntry::new(image::GrayImage::new(64, 64))],
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 1);
+
+    // the new page 1 is fully occupied, so the entry goes to the page after it
+    let texcoord = atlas
+        .insert(&AtlasEntry::new(image::GrayImage::new(32, 32)))
+        .unwrap();
+    assert_ne!(texcoord.page, 1);
+    assert_eq!(texcoord.page, 2);
+    assert_eq!(atlas.page_count, 3);
+}
+
+#[test]
+fn concat() {
+    let create = |width, page_base| {
+        create_atlas(&AtlasDescriptor {
+            max_page_count: 0,
+            size: 64,
+            page_base,
+            collect_free_rects: true,
+            entries: &[
+                AtlasEntry {
+                    texture: image::GrayImage::from_pixel(width, 40, image::Luma([width as u8])),
+                    ..Default::default()
+                },
+                AtlasEntry {
+                    texture: image::GrayImage::new(width, 40),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        })
+        .unwrap()
+    };
+
+    let atlas = Atlas::concat(vec![create(40, 3), create(20, 7)]).unwrap();
+    assert_eq!(atlas.page_count, 3);
+    assert_eq!(atlas.page_base, 3);
+    assert_eq!(atlas.textures.len(), 3);
+    assert_eq!(atlas.free_rects.len(), 3);
+    assert_eq!(atlas.block_waste.len(), 4);
+    let pages = atlas
+        .texcoords
+        .iter()
+        .map(|texcoord| texcoord.page)
+        .collect::<Vec<_>>();
+    assert_eq!(pages, vec![3, 4, 5, 5]);
+    assert!(atlas.free_rects[2]
+        .iter()
+        .all(|texcoord| texcoord.page == 5));
+
+    let texcoord = atlas.texcoords[2];
+    let texture = &atlas.textures[(texcoord.page - atlas.page_base) as usize];
+    let pixel = texture.mip_maps[0].get_pixel(texcoord.min_x, texcoord.min_y);
+    assert_eq!(pixel, &image::Luma([20]));
+
+    let mut atlas = atlas;
+    let entry = AtlasEntry {
+        texture: image::GrayImage::new(8, 8),
+        ..Default::default()
+    };
+    assert!(matches!(
+        atlas.insert(&entry),
+        Err(AtlasError::NoPackingState)
+    ));
+
+    let other = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 128,
+        entries: &[entry],
+        ..Default::default()
+    })
+    .unwrap();
+    let result = Atlas::concat(vec![create(40, 0), other]);
+    assert!(matches!(
+        result,
+        Err(AtlasError::IncompatibleAtlas { index: 1 })
+    ));
+}
+
+#[test]
+fn sampling() {
+    let texture = image::GrayImage::from_fn(2, 1, |x, _| image::Luma([x as u8 * 255]));
+    let row = |sampling| {
+        let atlas = create_atlas(&AtlasDescriptor {
+            max_page_count: 1,
+            size: 8,
+            sampling,
+            entries: &[AtlasEntry {
+                texture: texture.clone(),
+                size: Some((4, 1)),
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+        let texcoord = atlas.texcoords[0];
+        assert_eq!(
+            (texcoord.max_x - texcoord.min_x, texcoord.source_width),
+            (4, 4)
+        );
+        (texcoord.min_x..texcoord.max_x)
+            .map(|x| atlas.textures[0].mip_maps[0].get_pixel(x, texcoord.min_y).0[0])
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(row(AtlasSampling::Nearest), vec![0, 0, 255, 255]);
+    assert_eq!(row(AtlasSampling::Bilinear), vec![0, 64, 191, 255]);
+
+    let exact = |size| {
+        create_atlas(&AtlasDescriptor {
+            max_page_count: 1,
+            size: 8,
+            sampling: AtlasSampling::Bilinear,
+            entries: &[AtlasEntry {
+                texture: texture.clone(),
+                size,
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+        .unwrap()
+        .textures[0]
+            .mip_maps[0]
+            .clone()
+    };
+    assert_eq!(exact(Some((2, 1))), exact(None));
+}
+
+#[test]
+fn channel_swizzle() {
+    let entries = [AtlasEntry {
+        texture: image::RgbaImage::from_pixel(4, 4, image::Rgba([10, 20, 30, 40])),
+        ..Default::default()
+    }];
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 8,
+        channel_swizzle: Some([2, 1, 0, 3]),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    let pixel = atlas.textures[0].mip_maps[0].get_pixel(0, 0);
+    assert_eq!(pixel, &image::Rgba([30, 20, 10, 40]));
+
+    let texcoord = atlas.insert(&entries[0]).unwrap();
+    let texture = &atlas.textures[texcoord.page as usize].mip_maps[0];
+    let pixel = texture.get_pixel(texcoord.min_x, texcoord.min_y);
+    assert_eq!(pixel, &image::Rgba([30, 20, 10, 40]));
+
+    let entries = [AtlasEntry {
+        texture: image::RgbImage::new(4, 4),
+        ..Default::default()
+    }];
+    let result = AtlasDescriptor::builder()
+        .max_page_count(1)
+        .size(8)
+        .channel_swizzle([0, 1, 3, 0])
+        .entries(&entries)
+        .build();
+    assert!(matches!(
+        result,
+        Err(AtlasError::InvalidSwizzle([0, 1, 3, 0]))
+    ));
+}
+
+#[test]
+fn entry_setters() {
+    let texture = image::RgbImage::new(8, 8);
+    assert_eq!(
+        AtlasEntry::new(texture.clone()),
+        AtlasEntry {
+            texture: texture.clone(),
+            ..Default::default()
+        }
+    );
+
+    let entry = AtlasEntry::with_mip(texture.clone(), AtlasEntryMipOption::Repeat)
+        .trim(true)
+        .mip_filter(AtlasMipFilter::Nearest)
+        .anchor(AtlasEntryAnchor::Center)
+        .generate_mips(false)
+        .group(Some(2))
+        .size(4, 4)
+        .depth(2)
+        .gutter_wrap(AtlasEntryMipOption::Clamp)
+        .mip_wrap(AtlasEntryMipOption::Mirror)
+        .bake_rotations(true)
+        .pin_page(1);
+    assert_eq!(
+        entry,
+        AtlasEntry {
+            texture,
+            mip: AtlasEntryMipOption::Repeat,
+            trim: true,
+            mip_filter: Some(AtlasMipFilter::Nearest),
+            anchor: AtlasEntryAnchor::Center,
+            generate_mips: false,
+            group: Some(2),
+            size: Some((4, 4)),
+            depth: Some(2),
+            gutter_wrap: Some(AtlasEntryMipOption::Clamp),
+            mip_wrap: Some(AtlasEntryMipOption::Mirror),
+            bake_rotations: true,
+            pin_page: Some(1),
+        }
+    );
+    assert_eq!(
+        entry.clone().mip(AtlasEntryMipOption::Clamp).mip,
+        AtlasEntryMipOption::Clamp
+    );
+}
+
+#[test]
+fn block_larger_than_page() {
+    let entries = [AtlasEntry {
+        texture: image::GrayImage::new(8, 8),
+        ..Default::default()
+    }];
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 64),
+        entries: &entries,
+        ..Default::default()
+    });
+    assert!(matches!(
+        result,
+        Err(AtlasError::BlockLargerThanPage {
+            block_size: 64,
+            size: 32
+        })
+    ));
+
+    let result = AtlasDescriptor::builder()
+        .max_page_count(1)
+        .size(32)
+        .mip(AtlasMipOption::MipWithBlockRect(
+            AtlasMipFilter::Linear,
+            16,
+            64,
+        ))
+        .entries(&entries)
+        .build();
+    assert!(matches!(
+        result,
+        Err(AtlasError::BlockLargerThanPage { block_size: 64, .. })
+    ));
+}
+
+#[test]
+fn shrink_pages_normalized() {
+    let entries = [
+        AtlasEntry {
+            texture: image::GrayImage::new(300, 200),
+            ..Default::default()
+        },
+        AtlasEntry {
+            texture: image::GrayImage::new(100, 100),
+            ..Default::default()
+        },
+    ];
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 0,
+        size: 1024,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 16),
+        shrink_pages: true,
+        collect_free_rects: true,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.textures[0].size, 512);
+
+    let free_rects = atlas.free_rects.iter().flatten();
+    for texcoord in atlas.texcoords.iter().chain(free_rects) {
+        let page = &atlas.textures[(texcoord.page - atlas.page_base) as usize];
+        assert_eq!(texcoord.size, page.size);
+
+        let uv = texcoord.to_f64();
+        for value in [uv.min_x, uv.min_y, uv.max_x, uv.max_y] {
+            assert!((0.0..=1.0).contains(&value));
+        }
+        let uv = texcoord.to_f32_flipped();
+        for value in [uv.min_x, uv.min_y, uv.max_x, uv.max_y] {
+            assert!((0.0..=1.0).contains(&value));
+        }
+    }
+}
+
+#[test]
+fn update_entry() {
+    let red = image::Rgba([255, 0, 0, 255]);
+    let blue = image::Rgba([0, 0, 255, 255]);
+    let entries = [
+        AtlasEntry::new(image::RgbaImage::from_pixel(8, 8, red)),
+        AtlasEntry::new(image::RgbaImage::from_pixel(8, 8, red)),
+    ];
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        mip: AtlasMipOption::NoMipWithPadding(1),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    let old = atlas.texcoords[0];
+    let other = atlas.texcoords[1];
+
+    let entry = AtlasEntry::new(image::RgbaImage::from_pixel(6, 5, blue));
+    atlas.update_entry(0, &entry).unwrap();
+    let texcoord = atlas.texcoords[0];
+    assert_eq!((texcoord.min_x, texcoord.min_y), (old.min_x, old.min_y));
+    assert_eq!(
+        (texcoord.max_x, texcoord.max_y),
+        (old.min_x + 6, old.min_y + 5)
+    );
+    let page = &atlas.textures[0].mip_maps[0];
+    assert_eq!(page.get_pixel(texcoord.min_x, texcoord.min_y), &blue);
+    assert_eq!(
+        page.get_pixel(old.max_x - 1, old.max_y - 1),
+        &image::Rgba([0; 4])
+    );
+    assert_eq!(page.get_pixel(other.min_x, other.min_y), &red);
+    assert_eq!(atlas.texcoords[1], other);
+
+    let entry = AtlasEntry::new(image::RgbaImage::from_pixel(9, 8, blue));
+    assert!(matches!(
+        atlas.update_entry(0, &entry),
+        Err(AtlasError::SlotTooSmall {
+            index: 0,
+            width: 11,
+            height: 10,
+            slot_width: 10,
+            slot_height: 10
+        })
+    ));
+    assert!(matches!(
+        atlas.update_entry(2, &entry),
+        Err(AtlasError::NoEntry { index: 2 })
+    ));
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 128,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Nearest, 16),
+        entries: &[AtlasEntry::new(image::RgbaImage::from_pixel(20, 20, red))],
+        ..Default::default()
+    })
+    .unwrap();
+    let entry = AtlasEntry::new(image::RgbaImage::from_pixel(30, 30, blue));
+    atlas.update_entry(0, &entry).unwrap();
+    let texcoord = atlas.texcoords[0];
+    assert_eq!(texcoord.max_x - texcoord.min_x, 30);
+    assert_eq!(atlas.block_waste[0], 48 * 48 - 46 * 46);
+    let mip_map = &atlas.textures[0].mip_maps[1];
+    let (x, y) = ((texcoord.min_x + 15) / 2, (texcoord.min_y + 15) / 2);
+    assert_eq!(mip_map.get_pixel(x, y), &blue);
+
+    let entry = AtlasEntry::new(image::RgbaImage::from_pixel(40, 40, blue));
+    assert!(matches!(
+        atlas.update_entry(0, &entry),
+        Err(AtlasError::SlotTooSmall { .. })
+    ));
+}
+
+#[test]
+fn packed_rects() {
+    let entries = [
+        AtlasEntry::new(image::GrayImage::new(30, 20)),
+        AtlasEntry::new(image::GrayImage::new(10, 10)),
+    ];
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMipWithPadding(2),
+        collect_packed_rects: true,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.packed_rects.len(), 2);
+    for (packed, texcoord) in atlas.packed_rects.iter().zip(&atlas.texcoords) {
+        assert_eq!(packed.page, texcoord.page);
+        assert_eq!(
+            (packed.x + 2, packed.y + 2),
+            (texcoord.min_x, texcoord.min_y)
+        );
+        assert_eq!(packed.w, texcoord.max_x - texcoord.min_x + 4);
+        assert_eq!(packed.h, texcoord.max_y - texcoord.min_y + 4);
+    }
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 128,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Nearest, 16),
+        collect_packed_rects: true,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(
+        atlas.packed_rects[0],
+        PackedRect {
+            index: 0,
+            page: 0,
+            x: atlas.texcoords[0].min_x / 16,
+            y: atlas.texcoords[0].min_y / 16,
+            w: 3,
+            h: 3,
+        }
+    );
+
+    atlas
+        .insert(&AtlasEntry::new(image::GrayImage::new(5, 5)))
+        .unwrap();
+    assert_eq!(atlas.packed_rects.len(), 3);
+    assert_eq!(atlas.packed_rects[2].index, 2);
+    assert_eq!((atlas.packed_rects[2].w, atlas.packed_rects[2].h), (2, 2));
+}
+
+#[test]
+fn as_array_layers() {
+    let entries = [
+        AtlasEntry::new(image::GrayImage::from_pixel(40, 40, image::Luma([7]))),
+        AtlasEntry::new(image::GrayImage::from_pixel(40, 40, image::Luma([9]))),
+    ];
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        size: 64,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Nearest),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.mip_level_count, 7);
+
+    let layers = atlas.as_array_layers(1).unwrap();
+    assert_eq!(layers.len(), 2);
+    for (layer, texture) in layers.iter().zip(&atlas.textures) {
+        assert_eq!(layer.len(), 32 * 32);
+        assert_eq!(*layer, texture.mip_maps[1].as_raw().as_slice());
+    }
+    assert_eq!(atlas.as_array_layers(6).unwrap()[1], &[9]);
+
+    assert!(matches!(
+        atlas.as_array_layers(7),
+        Err(AtlasError::MipLevelOutOfRange {
+            mip_level: 7,
+            mip_level_count: 7
+        })
+    ));
+}
+
+#[test]
+fn alpha_coverage() {
+    let texture = image::RgbaImage::from_fn(16, 16, |x, _| match x < 4 {
+        true => image::Rgba([255, 255, 255, 255]),
+        false => image::Rgba([255, 255, 255, 0]),
+    });
+    let entries = [
+        AtlasEntry::new(texture.clone()),
+        AtlasEntry::new(texture).generate_mips(false),
+    ];
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 128,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 16),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.mip_level_count, 5);
+    assert_eq!(atlas.alpha_coverage.len(), 2);
+    assert_eq!(atlas.alpha_coverage[0].len(), 5);
+    assert_eq!(atlas.alpha_coverage[0][0], 0.25);
+    for &coverage in &atlas.alpha_coverage[0][..3] {
+        assert!((coverage - 0.25).abs() < 0.01);
+    }
+    // the entry is downscaled alone by the triangle filter, whose footprint widens with the mip
+    // level: the left texel of the 2x2 mip map weighs the opaque columns by 3/7 (alpha 109) and
+    // the right one reads none, and the single texel of the last mip map weighs them by 2.5/12
+    // (alpha 53).
+    assert_eq!(atlas.alpha_coverage[0][3], 109.0 / 255.0 / 2.0);
+    assert_eq!(atlas.alpha_coverage[0][4], 53.0 / 255.0);
+    assert_eq!(atlas.alpha_coverage[1], [0.25]);
+
+    let opaque = image::RgbaImage::from_pixel(8, 8, image::Rgba([0, 0, 0, 255]));
+    atlas.update_entry(1, &AtlasEntry::new(opaque)).unwrap();
+    assert_eq!(atlas.alpha_coverage[1], [1.0; 5]);
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 128,
+        mip: AtlasMipOption::NoMip,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(atlas.alpha_coverage.iter().all(Vec::is_empty));
+}
+
+#[test]
+fn min_page_count() {
+    let entries = [AtlasEntry::new(image::GrayImage::from_pixel(
+        16,
+        16,
+        image::Luma([255]),
+    ))];
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 4,
+        min_page_count: 3,
+        size: 32,
+        mip: AtlasMipOption::NoMip,
+        background: Some(image::Luma([7])),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 3);
+    assert_eq!(atlas.textures.len(), 3);
+    assert!(atlas.textures[1..]
+        .iter()
+        .all(|texture| texture.mip_maps[0].pixels().all(|p| p.0 == [7])));
+
+    atlas
+        .insert(&AtlasEntry::new(image::GrayImage::new(32, 32)))
+        .unwrap();
+    assert_eq!(atlas.page_count, 3);
+    assert_eq!(atlas.texcoords[1].page, 1);
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 0,
+        min_page_count: 2,
+        size: 32,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Nearest, 8),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 2);
+
+    let result = AtlasDescriptor::builder()
+        .max_page_count(1)
+        .min_page_count(2)
+        .size(32)
+        .entries(&entries)
+        .build();
+    assert!(matches!(
+        result,
+        Err(AtlasError::MinPageCountExceedsMax {
+            min_page_count: 2,
+            max_page_count: 1
+        })
+    ));
+}
+
+#[test]
+fn verify_layout() {
+    let entries = [
+        AtlasEntry::new(image::GrayImage::new(20, 10)),
+        AtlasEntry::new(image::GrayImage::new(12, 30)),
+        AtlasEntry::new(image::GrayImage::new(20, 10)),
+        AtlasEntry::new(image::GrayImage::new(7, 9)),
+    ];
+
+    for mip in [
+        AtlasMipOption::NoMipWithPadding(3),
+        AtlasMipOption::MipWithBlock(AtlasMipFilter::Nearest, 16),
+    ] {
+        let atlas = create_atlas(&AtlasDescriptor {
+            max_page_count: 2,
+            size: 64,
+            mip,
+            deduplicate: true,
+            reserve: &[(10, 10)],
+            entries: &entries,
+            ..Default::default()
+        })
+        .unwrap();
+        atlas.verify_layout().unwrap();
+    }
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMipWithPadding(3),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    let mut texcoord = atlas.texcoords[3];
+    texcoord.min_x = atlas.texcoords[1].max_x + 2;
+    texcoord.min_y = atlas.texcoords[1].min_y;
+    texcoord.max_x = texcoord.min_x + 7;
+    texcoord.max_y = texcoord.min_y + 9;
+    atlas.texcoords[3] = Texcoord {
+        page: atlas.texcoords[1].page,
+        ..texcoord
+    };
+    assert!(matches!(
+        atlas.verify_layout(),
+        Err(AtlasError::Overlap { a: 1, b: 3 })
+    ));
+
+    atlas.texcoords[3].min_x = 1;
+    assert!(matches!(
+        atlas.verify_layout(),
+        Err(AtlasError::OutOfPage { index: 3 })
+    ));
+}
+
+#[test]
+fn mip_range() {
+    let entries = [AtlasEntry::new(image::GrayImage::from_pixel(
+        30,
+        30,
+        image::Luma([200]),
+    ))];
+
+    let full = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::MipWithPadding(AtlasMipFilter::Linear, 2),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::MipWithPadding(AtlasMipFilter::Linear, 2),
+        mip_range: Some(1..4),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.mip_start, 1);
+    assert_eq!(atlas.mip_level_count, 3);
+    assert_eq!(atlas.textures[0].size, 32);
+    assert_eq!(atlas.textures[0].padding, 1);
+    assert_eq!(atlas.textures[0].mip_maps, full.textures[0].mip_maps[1..4]);
+    assert_eq!(
+        atlas.as_array_layers(2).unwrap(),
+        full.as_array_layers(2).unwrap()
+    );
+    assert_eq!(
+        atlas.entry_view(0, 1).unwrap().to_image(),
+        full.entry_view(0, 1).unwrap().to_image()
+    );
+    assert!(matches!(
+        atlas.entry_view(0, 0),
+        Err(AtlasError::MipLevelOutOfRange { mip_level: 0, .. })
+    ));
+    assert_eq!(atlas.contact_sheet(1).dimensions(), (32, 32));
+    assert!(matches!(
+        atlas.insert(&entries[0]),
+        Err(AtlasError::NoPackingState)
+    ));
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 16),
+        mip_range: Some(2..10),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.mip_start, 2);
+    assert_eq!(atlas.mip_level_count, 3);
+    assert_eq!(atlas.textures[0].mip_maps.len(), 3);
+    assert_eq!(atlas.textures[0].mip_maps[0].dimensions(), (16, 16));
+    assert_eq!(atlas.alpha_coverage[0].len(), 3);
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        mip_range: Some(7..9),
+        entries: &entries,
+        ..Default::default()
+    });
+    assert!(matches!(
+        result,
+        Err(AtlasError::MipLevelOutOfRange {
+            mip_level: 7,
+            mip_level_count: 7
+        })
+    ));
+
+    let result = AtlasDescriptor::builder()
+        .max_page_count(1)
+        .size(64)
+        .mip(AtlasMipOption::Mip(AtlasMipFilter::Linear))
+        .mip_range(3..3)
+        .entries(&entries)
+        .build();
+    assert!(matches!(result, Err(AtlasError::EmptyMipRange(_))));
+}
+
+#[cfg(all(feature = "glam", feature = "mint"))]
+#[test]
+fn vector_conversions() {
+    let texcoord = Texcoord {
+        min_x: 16,
+        min_y: 32,
+        max_x: 48,
+        max_y: 64,
+        size: 64,
+        ..Default::default()
+    };
+
+    let texcoord32 = texcoord.to_f32();
+    assert_eq!(texcoord32.min_vec2(), glam::Vec2::new(0.25, 0.5));
+    assert_eq!(texcoord32.max_vec2(), glam::Vec2::new(0.75, 1.0));
+    assert_eq!(texcoord32.min_vector2(), mint::Vector2 { x: 0.25, y: 0.5 });
+    assert_eq!(texcoord32.max_vector2(), mint::Vector2 { x: 0.75, y: 1.0 });
+
+    let texcoord64 = texcoord.to_f64();
+    assert_eq!(texcoord64.min_dvec2(), glam::DVec2::new(0.25, 0.5));
+    assert_eq!(texcoord64.max_dvec2(), glam::DVec2::new(0.75, 1.0));
+    assert_eq!(texcoord64.max_vector2(), mint::Vector2 { x: 0.75, y: 1.0 });
+}
+
+#[test]
+fn block_gutter_wrap_per_mip() {
+    let texture = image::RgbaImage::from_fn(24, 20, |x, y| {
+        image::Rgba([(x * 10) as u8, (y * 12) as u8, ((x + y) * 5) as u8, 255])
+    });
+    let entries = [
+        AtlasEntry::with_mip(texture.clone(), AtlasEntryMipOption::Repeat),
+        AtlasEntry::with_mip(texture, AtlasEntryMipOption::Repeat).anchor(AtlasEntryAnchor::Center),
+    ];
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 128,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 16),
+        collect_packed_rects: true,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.mip_level_count, 5);
+
+    let texture = &atlas.textures[0];
+    for (texcoord, packed) in atlas.texcoords.iter().zip(&atlas.packed_rects) {
+        for mip_level in 0..atlas.mip_level_count {
+            let mip_map = &texture.mip_maps[mip_level as usize];
+            let cell_x = (packed.x * 16) >> mip_level;
+            let cell_y = (packed.y * 16) >> mip_level;
+            let min_x = texcoord.min_x >> mip_level;
+            let min_y = texcoord.min_y >> mip_level;
+            let max_x = (texcoord.max_x >> mip_level).max(min_x + 1);
+            let max_y = (texcoord.max_y >> mip_level).max(min_y + 1);
+
+            // the texel past each edge repeats the texel of the opposite edge.
+            for y in min_y..max_y {
+                assert_eq!(mip_map[(max_x, y)], mip_map[(min_x, y)]);
+                if min_x > cell_x {
+                    assert_eq!(mip_map[(min_x - 1, y)], mip_map[(max_x - 1, y)]);
+                }
+            }
+            for x in min_x..max_x {
+                assert_eq!(mip_map[(x, max_y)], mip_map[(x, min_y)]);
+                if min_y > cell_y {
+                    assert_eq!(mip_map[(x, min_y - 1)], mip_map[(x, max_y - 1)]);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn pow2_size() {
+    assert_eq!(Pow2Size::new(256).map(Pow2Size::get), Some(256));
+    assert_eq!(Pow2Size::new(0), None);
+    assert_eq!(Pow2Size::new(300), None);
+    assert_eq!(Pow2Size::from_log2(31).map(u32::from), Some(1 << 31));
+    assert_eq!(Pow2Size::from_log2(32), None);
+    assert_eq!(Pow2Size::from_log2(8).unwrap().log2(), 8);
+    assert!(matches!(
+        Pow2Size::try_from(300),
+        Err(AtlasError::InvalidSize(300))
+    ));
+
+    let entries = [AtlasEntry::new(image::GrayImage::new(20, 20))];
+    let desc = AtlasDescriptor::builder()
+        .max_page_count(1)
+        .pow2_size(Pow2Size::from_log2(6).unwrap())
+        .mip(AtlasMipOption::Mip(AtlasMipFilter::Linear))
+        .entries(&entries)
+        .build()
+        .unwrap();
+    assert_eq!(desc.size, 64);
+    assert_eq!(create_atlas(&desc).unwrap().mip_level_count, 7);
+
+    let size = Pow2Size::new(128).unwrap();
+    let desc = AtlasDescriptor::builder()
+        .max_page_count(1)
+        .mip_with_size(
+            AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 32),
+            size,
+        )
+        .entries(&entries)
+        .build()
+        .unwrap();
+    assert_eq!(desc.size, 128);
+    assert_eq!(create_atlas(&desc).unwrap().mip_level_count, 6);
+}
+
+#[test]
+fn bleed_alpha() {
+    let mut texture = image::RgbaImage::new(5, 5);
+    texture.put_pixel(1, 1, image::Rgba([255, 0, 0, 255]));
+    texture.put_pixel(4, 4, image::Rgba([0, 0, 255, 128]));
+    let entries = [AtlasEntry::new(texture)];
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        mip: AtlasMipOption::NoMipWithPadding(2),
+        bleed_alpha: true,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    atlas.insert(&entries[0]).unwrap();
+
+    let mip_map = &atlas.textures[0].mip_maps[0];
+    for texcoord in &atlas.texcoords {
+        let (x, y) = (texcoord.min_x, texcoord.min_y);
+        assert_eq!(mip_map[(x + 1, y + 1)], image::Rgba([255, 0, 0, 255]));
+        assert_eq!(mip_map[(x + 4, y + 4)], image::Rgba([0, 0, 255, 128]));
+        // transparent pixels (including the padding) take the nearest color with zero alpha.
+        assert_eq!(mip_map[(x - 2, y - 2)], image::Rgba([255, 0, 0, 0]));
+        assert_eq!(mip_map[(x + 2, y)], image::Rgba([255, 0, 0, 0]));
+        assert_eq!(mip_map[(x + 3, y + 4)], image::Rgba([0, 0, 255, 0]));
+    }
+
+    let entries = [AtlasEntry::new(image::GrayImage::new(4, 4))];
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        mip: AtlasMipOption::NoMip,
+        bleed_alpha: true,
+        entries: &entries,
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(AtlasError::NoAlphaChannel)));
+}
+
+#[test]
+fn plan_and_bake() {
+    let entries = [
+        AtlasEntry::new(image::RgbaImage::from_pixel(
+            20,
+            12,
+            image::Rgba([255, 0, 0, 255]),
+        )),
+        AtlasEntry::new(image::RgbaImage::from_pixel(
+            8,
+            30,
+            image::Rgba([0, 255, 0, 255]),
+        )),
+        AtlasEntry::new(image::RgbaImage::from_pixel(
+            16,
+            16,
+            image::Rgba([0, 0, 255, 128]),
+        )),
+    ];
+    let desc = AtlasDescriptor {
+        max_page_count: 1,
+        size: 128,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 8),
+        allow_rotation: true,
+        entries: &entries,
+        ..Default::default()
+    };
+    let expected = create_atlas(&desc).unwrap();
+
+    let sizes = entries
+        .iter()
+        .map(|entry| entry.texture.dimensions())
+        .collect::<Vec<_>>();
+    let mut atlas = create_atlas_planned(&desc, &sizes).unwrap();
+    assert!(atlas.texcoords[1].rotated);
+    let layout = plan_atlas(&desc, &sizes).unwrap();
+    assert_eq!(layout.texcoords, atlas.texcoords);
+    assert_eq!(layout.page_count, atlas.page_count);
+    assert_eq!(layout.mip_level_count, atlas.mip_level_count);
+    for (index, entry) in entries.iter().enumerate() {
+        atlas.bake_entry(index, entry).unwrap();
+    }
+    assert_eq!(atlas.texcoords, expected.texcoords);
+    assert_eq!(atlas.block_waste, expected.block_waste);
+    assert_eq!(atlas.alpha_coverage, expected.alpha_coverage);
+    assert_eq!(atlas.textures[0].mip_maps, expected.textures[0].mip_maps);
+
+    let entry = AtlasEntry::new(image::RgbaImage::new(40, 12));
+    assert!(matches!(
+        atlas.bake_entry(0, &entry),
+        Err(AtlasError::SlotTooSmall { index: 0, .. })
+    ));
+}
+
+#[test]
+fn uv_transform() {
+    let mut texture = image::RgbaImage::new(4, 8);
+    texture.put_pixel(3, 0, image::Rgba([255, 0, 0, 255]));
+    texture.put_pixel(0, 7, image::Rgba([0, 0, 255, 255]));
+    // the page fits both entries only with the tall one transposed.
+    let entries = [
+        AtlasEntry::new(texture),
+        AtlasEntry::new(image::RgbaImage::new(8, 4)),
+    ];
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 8,
+        allow_rotation: true,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    let texcoord = atlas.texcoords[0];
+    assert!(texcoord.rotated);
+
+    let apply = |m: [[f32; 2]; 3], (u, v): (f32, f32)| {
+        (
+            m[0][0] * u + m[1][0] * v + m[2][0],
+            m[0][1] * u + m[1][1] * v + m[2][1],
+        )
+    };
+    let mip_map = &atlas.textures[0].mip_maps[0];
+    let transform = texcoord.uv_transform();
+    for ((u, v), pixel) in [
+        ((3.5 / 4.0, 0.5 / 8.0), [255, 0, 0, 255]),
+        ((0.5 / 4.0, 7.5 / 8.0), [0, 0, 255, 255]),
+    ] {
+        let (x, y) = apply(transform, (u, v));
+        let (x, y) = ((x * 8.0) as u32, (y * 8.0) as u32);
+        assert_eq!(mip_map[(x, y)], image::Rgba(pixel));
+
+        let (x, y) = apply(transform, (u, v));
+        let (inverse_u, inverse_v) = apply(texcoord.inverse_uv_transform(), (x, y));
+        assert!((inverse_u - u).abs() < 1e-6 && (inverse_v - v).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn balance_pages() {
+    let entries = vec![AtlasEntry::new(image::RgbaImage::new(16, 16)); 20];
+    let desc = AtlasDescriptor {
+        max_page_count: 2,
+        size: 64,
+        entries: &entries,
+        ..Default::default()
+    };
+
+    let atlas = create_atlas(&desc).unwrap();
+    assert_eq!(atlas.stats().per_page, vec![1.0, 0.25]);
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        balance_pages: true,
+        ..desc
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 2);
+    assert_eq!(atlas.stats().per_page, vec![0.625, 0.625]);
+    atlas.verify_layout().unwrap();
+}
+
+#[test]
+fn texcoord_at_mip() {
+    let texcoord = Texcoord {
+        min_x: 5,
+        min_y: 3,
+        max_x: 13,
+        max_y: 10,
+        size: 16,
+        ..Default::default()
+    };
+    let rect = |mip_level| {
+        let t = texcoord.at_mip(mip_level);
+        (t.min_x, t.min_y, t.max_x, t.max_y, t.size)
+    };
+    assert_eq!(rect(0), (5, 3, 13, 10, 16));
+    assert_eq!(rect(1), (2, 1, 7, 5, 8));
+    assert_eq!(rect(3), (0, 0, 2, 2, 2));
+    assert_eq!(rect(5), (0, 0, 1, 1, 1));
+    assert_eq!(rect(40), (0, 0, 1, 1, 1));
+
+    let texcoord = Texcoord {
+        min_x: 6,
+        max_x: 7,
+        max_y: 1,
+        ..texcoord
+    };
+    let t = texcoord.at_mip(2);
+    assert_eq!((t.min_x, t.max_x, t.min_y, t.max_y), (1, 2, 0, 1));
+}
+
+#[test]
+fn origin_alignment() {
+    let entries = [
+        AtlasEntry::new(image::RgbaImage::new(5, 7)),
+        AtlasEntry::new(image::RgbaImage::new(3, 3)),
+        AtlasEntry::new(image::RgbaImage::new(9, 2)),
+    ];
+    let desc = AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::NoMipWithPadding(1),
+        origin_alignment: 4,
+        entries: &entries,
+        ..Default::default()
+    };
+
+    let mut atlas = create_atlas(&desc).unwrap();
+    atlas.insert(&entries[0]).unwrap();
+    for texcoord in &atlas.texcoords {
+        assert_eq!((texcoord.min_x % 4, texcoord.min_y % 4), (0, 0));
+    }
+    atlas.verify_layout().unwrap();
+
+    let result = create_atlas(&AtlasDescriptor {
+        origin_alignment: 3,
+        ..desc
+    });
+    assert!(matches!(result, Err(AtlasError::InvalidOriginAlignment(3))));
+}
+
+#[test]
+fn into_dynamic_pages() {
+    let entries = [AtlasEntry::new(image::RgbImage::from_pixel(
+        8,
+        8,
+        image::Rgb([255, 0, 0]),
+    ))];
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 16,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    let expected = atlas.textures[0].mip_maps.clone();
+
+    let pages = atlas.into_dynamic_pages();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].len(), expected.len());
+    for (image, mip_map) in pages[0].iter().zip(&expected) {
+        assert_eq!(image.as_rgb8(), Some(mip_map));
+    }
+}
+
+#[test]
+fn retain_sources() {
+    let red = image::Rgba([255, 0, 0, 255]);
+    let blue = image::Rgba([0, 0, 255, 255]);
+    let entries = [AtlasEntry::new(image::RgbaImage::from_pixel(8, 8, red))];
+    let desc = AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 8),
+        entries: &entries,
+        ..Default::default()
+    };
+
+    let mut atlas = create_atlas(&desc).unwrap();
+    assert!(atlas.sources.is_empty());
+    assert!(matches!(
+        atlas.rebake_entry(0),
+        Err(AtlasError::NoEntry { index: 0 })
+    ));
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        retain_sources: true,
+        ..desc
+    })
+    .unwrap();
+    assert_eq!(atlas.sources, entries);
+    atlas.insert(&entries[0]).unwrap();
+    assert_eq!(atlas.sources.len(), 2);
+
+    for pixel in atlas.sources[1].texture.pixels_mut() {
+        *pixel = blue;
+    }
+    atlas.rebake_entry(1).unwrap();
+    let texcoord = atlas.texcoords[1];
+    let mip_map = &atlas.textures[0].mip_maps[0];
+    assert_eq!(mip_map[(texcoord.min_x, texcoord.min_y)], blue);
+    let texcoord = atlas.texcoords[0];
+    assert_eq!(mip_map[(texcoord.min_x, texcoord.min_y)], red);
+}
+
+#[test]
+fn display() {
+    let entries = [AtlasEntry::new(image::RgbaImage::new(16, 8))];
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_eq!(atlas.texcoords[0].to_string(), "0:(0,0)-(16,8)");
+    assert_eq!(
+        atlas.to_string(),
+        "Atlas { pages: 1, size: 32, mips: 1, entries: 1, occupancy: 12.5% }"
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn frames_json() {
+    let mut texture = image::RgbaImage::new(16, 12);
+    texture.put_pixel(2, 3, image::Rgba([255; 4]));
+    texture.put_pixel(5, 7, image::Rgba([255; 4]));
+    let entries = [
+        AtlasEntry::new(image::RgbaImage::from_pixel(8, 8, image::Rgba([255; 4]))),
+        AtlasEntry::new(texture).trim(true),
+    ];
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let json = atlas.to_frames_json(&["a.png"]).unwrap();
+    let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+    let texcoord = atlas.texcoords[0];
+    assert_eq!(
+        value["frames"]["a.png"]["frame"],
+        serde_json::json!({ "x": texcoord.min_x, "y": texcoord.min_y, "w": 8, "h": 8 })
+    );
+    assert_eq!(value["frames"]["a.png"]["trimmed"], false);
+    assert_eq!(
+        value["frames"]["1"]["spriteSourceSize"],
+        serde_json::json!({ "x": 2, "y": 3, "w": 4, "h": 5 })
+    );
+    assert_eq!(
+        value["frames"]["1"]["sourceSize"],
+        serde_json::json!({ "w": 16, "h": 12 })
+    );
+    assert_eq!(value["frames"]["1"]["trimmed"], true);
+    assert_eq!(value["meta"]["pages"], 1);
+    assert_eq!(
+        value["meta"]["size"],
+        serde_json::json!({ "w": 32, "h": 32 })
+    );
+
+    let shrunk = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 256,
+        entries: &entries,
+        shrink_pages: true,
+        ..Default::default()
+    })
+    .unwrap();
+    let json = shrunk.to_frames_json(&[]).unwrap();
+    let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+    assert_eq!(
+        value["meta"]["size"],
+        serde_json::json!({ "w": 16, "h": 16 })
+    );
+    assert_eq!(
+        value["meta"]["pageSizes"],
+        serde_json::json!([{ "w": 16, "h": 16 }])
+    );
+
+    let mut transposed = atlas;
+    transposed.texcoords[1].rotated = true;
+    let result = transposed.to_frames_json(&[]);
+    assert!(matches!(
+        result,
+        Err(AtlasError::TransposedFrame { index: 1 })
+    ));
+}
+
+#[test]
+fn minimum_size() {
+    let entries = vec![AtlasEntry::new(image::RgbaImage::new(200, 120)); 12];
+    for mip in [
+        AtlasMipOption::NoMipWithPadding(2),
+        AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 32),
+    ] {
+        let size = image_atlas::minimum_size(2, mip, &entries).unwrap();
+        let atlas = create_atlas_auto(2, mip, &entries).unwrap();
+        assert_eq!(size, atlas.size);
+    }
+
+    let entries = [AtlasEntry::new(image::RgbaImage::new(10, 10))];
+    let mip = AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 1024);
+    let size = image_atlas::minimum_size(1, mip, &entries).unwrap();
+    let atlas = create_atlas_auto(1, mip, &entries).unwrap();
+    assert_eq!(size, atlas.size);
+
+    let entries = [AtlasEntry::new(image::RgbaImage::new(20000, 1))];
+    let result = image_atlas::minimum_size(1, AtlasMipOption::NoMip, &entries);
+    assert!(matches!(result, Err(AtlasError::EntryTooLarge { .. })));
+}
+
+#[test]
+fn sorted_texcoords() {
+    let texcoord = |page, min_x, min_y| Texcoord {
+        page,
+        min_x,
+        min_y,
+        max_x: min_x + 1,
+        max_y: min_y + 1,
+        ..Default::default()
+    };
+    assert!(texcoord(0, 5, 0) < texcoord(0, 0, 1));
+    assert!(texcoord(0, 5, 5) < texcoord(1, 0, 0));
+
+    let entries = [
+        AtlasEntry::new(image::RgbaImage::new(8, 8)),
+        AtlasEntry::new(image::RgbaImage::new(16, 16)),
+        AtlasEntry::new(image::RgbaImage::new(8, 8)),
+    ];
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        deduplicate: true,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let sorted = atlas.sorted_texcoords();
+    let indices = sorted.iter().map(|&(i, _)| i).collect::<Vec<_>>();
+    assert_eq!(indices.len(), 3);
+    assert!(sorted.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    for (i, texcoord) in sorted {
+        assert_eq!(atlas.texcoords[i], texcoord);
+    }
+    // deduplicated entries share a texcoord and keep their index order.
+    let position = |i| indices.iter().position(|&j| j == i).unwrap();
+    assert_eq!(position(2), position(0) + 1);
+}
+
+#[test]
+fn downscale_oversized() {
+    let entries = [
+        AtlasEntry::new(image::RgbaImage::new(120, 60)),
+        AtlasEntry::new(image::RgbaImage::new(8, 8)),
+    ];
+    let desc = AtlasDescriptor {
+        max_page_count: 2,
+        size: 64,
+        mip: AtlasMipOption::NoMipWithPadding(2),
+        entries: &entries,
+        ..Default::default()
+    };
+    assert!(matches!(
+        create_atlas(&desc),
+        Err(AtlasError::EntryTooLarge { index: 0, .. })
+    ));
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        downscale_oversized: true,
+        ..desc
+    })
+    .unwrap();
+    let texcoord = atlas.texcoords[0];
+    assert_eq!(
+        (
+            texcoord.max_x - texcoord.min_x,
+            texcoord.max_y - texcoord.min_y
+        ),
+        (60, 30)
+    );
+    assert_eq!(atlas.scale_factors, vec![0.5, 1.0]);
+    let texcoord = atlas.texcoords[1];
+    assert_eq!(texcoord.max_x - texcoord.min_x, 8);
+
+    // one-pixel stripes are averaged by the mip filter, where nearest sampling keeps 0 or 255.
+    let stripes = image::GrayImage::from_fn(128, 32, |x, _| image::Luma([(x % 2 * 255) as u8]));
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        downscale_oversized: true,
+        entries: &[AtlasEntry::new(stripes)],
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.scale_factors, vec![0.5]);
+    let texcoord = atlas.texcoords[0];
+    let mip_map = &atlas.textures[0].mip_maps[0];
+    for y in texcoord.min_y..texcoord.max_y {
+        for x in texcoord.min_x..texcoord.max_x {
+            let [value] = mip_map.get_pixel(x, y).0;
+            assert!((96..=160).contains(&value), "{} at ({}, {})", value, x, y);
+        }
+    }
+}
+
+#[test]
+fn from_iter_with_keys() {
+    let images = std::collections::HashMap::from([
+        ("c".to_string(), image::RgbaImage::new(3, 3)),
+        ("a".to_string(), image::RgbaImage::new(1, 1)),
+        ("b".to_string(), image::RgbaImage::new(2, 2)),
+    ]);
+    let (entries, keys) = AtlasEntry::from_iter_with_keys(images);
+    assert_eq!(keys, ["a", "b", "c"]);
+    for (i, entry) in entries.iter().enumerate() {
+        let extent = i as u32 + 1;
+        assert_eq!(entry.texture.dimensions(), (extent, extent));
+        assert_eq!(entry.mip, AtlasEntryMipOption::Clamp);
+    }
+}
+
+#[test]
+fn sub_block_size() {
+    let entries = (0..64)
+        .map(|i| {
+            AtlasEntry::new(image::RgbaImage::from_pixel(
+                4,
+                4,
+                image::Rgba([i, 0, 0, 255]),
+            ))
+        })
+        .collect::<Vec<_>>();
+    let desc = AtlasDescriptor {
+        max_page_count: 0,
+        size: 128,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 32),
+        entries: &entries,
+        ..Default::default()
+    };
+    let atlas = create_atlas(&desc).unwrap();
+    assert_eq!(atlas.page_count, 16);
+
+    let mut atlas = create_atlas(&AtlasDescriptor {
+        sub_block_size: Some(8),
+        ..desc.clone()
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 1);
+    assert_eq!(atlas.mip_level_count, 4);
+    atlas.verify_layout().unwrap();
+    for (i, texcoord) in atlas.texcoords.iter().enumerate() {
+        assert_eq!(texcoord.max_x - texcoord.min_x, 4);
+        let pixel = atlas.textures[0].mip_maps[0].get_pixel(texcoord.min_x, texcoord.min_y);
+        assert_eq!(pixel[0], i as u8);
+    }
+
+    let entry = AtlasEntry::new(image::RgbaImage::from_pixel(4, 4, image::Rgba([255; 4])));
+    atlas.update_entry(3, &entry).unwrap();
+    let texcoord = atlas.texcoords[3];
+    let pixel = atlas.textures[0].mip_maps[0].get_pixel(texcoord.min_x, texcoord.min_y);
+    assert_eq!(pixel[0], 255);
+    atlas.verify_layout().unwrap();
+
+    let result = create_atlas(&AtlasDescriptor {
+        sub_block_size: Some(32),
+        ..desc
+    });
+    assert!(matches!(result, Err(AtlasError::InvalidSubBlockSize(32))));
+}
+
+#[test]
+fn volume_atlas() {
+    let brick = |value| {
+        let texture =
+            image::RgbaImage::from_fn(4, 4 * 4, |_, y| image::Rgba([value, y as u8 / 4, 0, 255]));
+        AtlasEntry::new(texture).depth(4)
+    };
+    let entries = [
+        brick(1),
+        brick(2),
+        AtlasEntry::new(image::RgbaImage::from_pixel(
+            4,
+            4,
+            image::Rgba([3, 0, 0, 255]),
+        )),
+    ];
+    let atlas = create_volume_atlas(&AtlasDescriptor {
+        max_page_count: 0,
+        size: 8,
+        mip: AtlasMipOption::NoMip,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 1);
+    assert_eq!(atlas.pages[0].len(), 8);
+
+    for (i, texcoord) in atlas.texcoords.iter().enumerate() {
+        let depth = if i < 2 { 4 } else { 1 };
+        assert_eq!(texcoord.max_z - texcoord.min_z, depth);
+        for z in 0..depth {
+            let slice = &atlas.pages[0][(texcoord.min_z + z) as usize];
+            let pixel = slice.get_pixel(texcoord.min_x, texcoord.min_y);
+            assert_eq!(pixel[0], i as u8 + 1);
+            assert_eq!(pixel[1], z as u8);
+        }
+    }
+    // bricks do not overlap in 3D.
+    let [a, b, c] = atlas.texcoords[..] else {
+        unreachable!()
+    };
+    for (a, b) in [(a, b), (a, c), (b, c)] {
+        assert!(
+            a.max_x <= b.min_x
+                || b.max_x <= a.min_x
+                || a.max_y <= b.min_y
+                || b.max_y <= a.min_y
+                || a.max_z <= b.min_z
+                || b.max_z <= a.min_z
+        );
+    }
+    assert_eq!(
+        atlas.texcoords[0].to_f32().1[2] - atlas.texcoords[0].to_f32().0[2],
+        0.5
+    );
+}
+
+#[test]
+fn entries_by_page() {
+    let entries = (0..5)
+        .map(|_| AtlasEntry::new(image::RgbaImage::new(32, 32)))
+        .collect::<Vec<_>>();
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 0,
+        size: 64,
+        mip: AtlasMipOption::NoMip,
+        page_base: 2,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(atlas.page_count, 2);
+
+    let pages = atlas.page_indices();
+    assert_eq!(pages.len(), 5);
+    let by_page = atlas.entries_by_page();
+    assert_eq!(by_page.len(), 2);
+    assert_eq!(by_page.iter().map(Vec::len).sum::<usize>(), 5);
+    for (i, indices) in by_page.iter().enumerate() {
+        assert!(indices.windows(2).all(|pair| pair[0] < pair[1]));
+        for &index in indices {
+            assert_eq!(pages[index], atlas.page_base + i as u32);
+        }
+    }
+}
+
+#[test]
+fn gutter_and_mip_wrap() {
+    let texture =
+        image::RgbaImage::from_fn(4, 4, |x, _| image::Rgba([(x as u8 & 1) * 255, 0, 0, 255]));
+    let create = |entry: AtlasEntry<image::RgbaImage>| {
+        create_atlas(&AtlasDescriptor {
+            max_page_count: 1,
+            size: 16,
+            mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 8),
+            entries: &[entry],
+            ..Default::default()
+        })
+        .unwrap()
+    };
+    let row = |atlas: &Atlas<image::Rgba<u8>>, mip_level: u32, from: i32, to: i32| {
+        let texcoord = atlas.texcoords[0].at_mip(mip_level);
+        let mip_map = &atlas.textures[0].mip_maps[mip_level as usize];
+        (from..to)
+            .map(|x| mip_map.get_pixel((texcoord.min_x as i32 + x) as u32, texcoord.min_y)[0])
+            .collect::<Vec<_>>()
+    };
+
+    // a periodic entry downscaled with repeat stays uniform, unlike with clamped edges.
+    let entry = AtlasEntry::with_mip(texture, AtlasEntryMipOption::Clamp);
+    let clamped = row(&create(entry.clone()), 1, 0, 2);
+    assert_ne!(clamped[0], clamped[1]);
+    let atlas = create(entry.mip_wrap(AtlasEntryMipOption::Repeat));
+    let repeated = row(&atlas, 1, 0, 2);
+    assert_eq!(repeated[0], repeated[1]);
+    assert_eq!(row(&atlas, 0, -1, 1), [0, 0]);
+}
+
+#[test]
+fn debug_texture_checkerboard() {
+    let texture = debug_texture(5, 3, 2);
+    assert_eq!(texture.dimensions(), (5, 3));
+    let magenta = image::Rgba([255, 0, 255, 255]);
+    let black = image::Rgba([0, 0, 0, 255]);
+    assert_eq!(texture.get_pixel(0, 0), &magenta);
+    assert_eq!(texture.get_pixel(1, 1), &magenta);
+    assert_eq!(texture.get_pixel(2, 0), &black);
+    assert_eq!(texture.get_pixel(2, 2), &magenta);
+    assert_eq!(texture.get_pixel(4, 2), &black);
+
+    let colors = [image::Luma([1u8]), image::Luma([2])];
+    let texture = checkerboard(2, 1, 0, colors);
+    assert_eq!(texture.into_raw(), [1, 2]);
+}
+
+#[test]
+fn max_entries_per_page() {
+    let entries = (0..10)
+        .map(|_| AtlasEntry::new(image::RgbaImage::new(8, 8)))
+        .collect::<Vec<_>>();
+    let desc = AtlasDescriptor {
+        max_page_count: 0,
+        size: 64,
+        mip: AtlasMipOption::NoMipWithPadding(1),
+        entries: &entries,
+        ..Default::default()
+    };
+    assert_eq!(create_atlas(&desc).unwrap().page_count, 1);
+
+    let desc = AtlasDescriptor {
+        max_entries_per_page: Some(4),
+        ..desc
+    };
+    let mut atlas = create_atlas(&desc).unwrap();
+    assert_eq!(atlas.page_count, 3);
+    let counts = atlas
+        .entries_by_page()
+        .iter()
+        .map(Vec::len)
+        .collect::<Vec<_>>();
+    assert_eq!(counts, [4, 4, 2]);
+
+    for _ in 0..3 {
+        atlas
+            .insert(&AtlasEntry::new(image::RgbaImage::new(8, 8)))
+            .unwrap();
+    }
+    let counts = atlas
+        .entries_by_page()
+        .iter()
+        .map(Vec::len)
+        .collect::<Vec<_>>();
+    assert_eq!(counts, [4, 4, 4, 1]);
+    atlas.verify_layout().unwrap();
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        ..desc.clone()
+    });
+    assert!(matches!(result, Err(AtlasError::Packing(_))));
+    let result = create_atlas(&AtlasDescriptor {
+        max_entries_per_page: Some(0),
+        ..desc
+    });
+    assert!(matches!(result, Err(AtlasError::ZeroMaxEntriesPerPage)));
+}
+
+#[test]
+fn luma_atlas() {
+    let mips = [
+        AtlasMipOption::NoMip,
+        AtlasMipOption::NoMipWithPadding(2),
+        AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        AtlasMipOption::MipWithPadding(AtlasMipFilter::Linear, 4),
+        AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 8),
+        AtlasMipOption::MipWithBlockRect(AtlasMipFilter::Linear, 8, 16),
+    ];
+    for mip in mips {
+        for color_space in [AtlasMipColorSpace::Linear, AtlasMipColorSpace::Srgb] {
+            let gradient =
+                image::GrayImage::from_fn(8, 8, |x, y| image::Luma([(x * 16 + y) as u8]));
+            let entries = [
+                AtlasEntry::new(gradient.clone()),
+                AtlasEntry::new(image::GrayImage::from_pixel(16, 16, image::Luma([200]))),
+            ];
+            let atlas = create_atlas(&AtlasDescriptor {
+                max_page_count: 1,
+                size: 64,
+                mip,
+                mip_color_space: color_space,
+                entries: &entries,
+                ..Default::default()
+            })
+            .unwrap();
+            let texcoord = atlas.texcoords[0];
+            let base = &atlas.textures[0].mip_maps[0];
+            for (x, y, pixel) in gradient.enumerate_pixels() {
+                assert_eq!(
+                    base.get_pixel(texcoord.min_x + x, texcoord.min_y + y),
+                    pixel,
+                    "{mip:?}"
+                );
+            }
+            // neighbors bleed into the entry once the gutter vanishes.
+            let texture = &atlas.textures[0];
+            for mip_level in
+                (1..atlas.mip_level_count).filter(|&level| texture.mip_padding(level) > 0)
+            {
+                let texcoord = atlas.texcoords[1].at_mip(mip_level);
+                let mip_map = &atlas.textures[0].mip_maps[mip_level as usize];
+                let value = mip_map.get_pixel(texcoord.min_x, texcoord.min_y)[0];
+                assert!(
+                    value.abs_diff(200) <= 2,
+                    "{mip:?} {color_space:?} {mip_level} {value}"
+                );
+            }
+        }
+
+        let entries = [AtlasEntry::new(image::GrayAlphaImage::from_pixel(
+            16,
+            16,
+            image::LumaA([120, 128]),
+        ))];
+        let atlas = create_atlas(&AtlasDescriptor {
+            max_page_count: 1,
+            size: 64,
+            mip,
+            mip_color_space: AtlasMipColorSpace::Srgb,
+            premultiply_alpha: !matches!(
+                mip,
+                AtlasMipOption::NoMip | AtlasMipOption::NoMipWithPadding(_)
+            ),
+            entries: &entries,
+            ..Default::default()
+        })
+        .unwrap();
+        let texture = &atlas.textures[0];
+        for mip_level in
+            (0..atlas.mip_level_count).filter(|&level| texture.mip_padding(level) > 0 || level == 0)
+        {
+            let texcoord = atlas.texcoords[0].at_mip(mip_level);
+            let mip_map = &atlas.textures[0].mip_maps[mip_level as usize];
+            let image::LumaA([luma, alpha]) = *mip_map.get_pixel(texcoord.min_x, texcoord.min_y);
+            assert!(
+                luma.abs_diff(120) <= 2 && alpha.abs_diff(128) <= 2,
+                "{mip:?} {mip_level} {luma} {alpha}"
+            );
+        }
+    }
+}
+
+#[test]
+fn packed_bytes() {
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 16,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        entries: &[AtlasEntry::new(image::RgbaImage::from_pixel(
+            8,
+            8,
+            image::Rgba([1, 2, 3, 4]),
+        ))],
+        ..Default::default()
+    })
+    .unwrap();
+    let texture = &atlas.textures[0];
+    let bytes = texture.packed_bytes();
+    assert_eq!(bytes.len(), (256 + 64 + 16 + 4 + 1) * 4);
+
+    let mut offset = 0;
+    for mip_map in &texture.mip_maps {
+        let raw = mip_map.as_raw();
+        assert_eq!(&bytes[offset..offset + raw.len()], raw.as_slice());
+        offset += raw.len();
+    }
+    assert_eq!(texture.packed_subpixels(), bytes);
+}
+
+#[test]
+fn bake_rotations() {
+    let texture =
+        image::RgbaImage::from_fn(3, 2, |x, y| image::Rgba([(x + y * 3) as u8, 0, 0, 255]));
+    let entries = [
+        AtlasEntry::new(image::RgbaImage::new(2, 2)),
+        AtlasEntry::new(texture.clone()).bake_rotations(true),
+    ];
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        mip: AtlasMipOption::NoMipWithPadding(1),
+        collect_packed_rects: true,
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_eq!(atlas.texcoords.len(), 2);
+    assert_eq!(atlas.block_waste.len(), 2);
+    assert!(atlas.rotation_texcoords(0).is_none());
+    assert!(atlas.rotation_texcoords(2).is_none());
+    assert!(atlas.packed_rects.iter().all(|rect| rect.index < 2));
+    assert_eq!(atlas.stats().used_area, 2 * 2 + 4 * 3 * 2);
+    atlas.verify_layout().unwrap();
+
+    let expected = [
+        texture.clone(),
+        image::imageops::rotate90(&texture),
+        image::imageops::rotate180(&texture),
+        image::imageops::rotate270(&texture),
+    ];
+    let texcoords = atlas.rotation_texcoords(1).unwrap();
+    assert_eq!(texcoords[0], atlas.texcoords[1]);
+    for (texcoord, expected) in texcoords.iter().zip(&expected) {
+        let view = image::imageops::crop_imm(
+            &atlas.textures[0].mip_maps[0],
+            texcoord.min_x,
+            texcoord.min_y,
+            texcoord.max_x - texcoord.min_x,
+            texcoord.max_y - texcoord.min_y,
+        );
+        assert_eq!(&view.to_image(), expected);
+    }
+}
+
+#[test]
+fn relaxed_mip() {
+    let entries = [AtlasEntry::new(image::RgbaImage::new(8, 8))];
+    let desc = AtlasDescriptor {
+        max_page_count: 1,
+        size: 24,
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Linear),
+        entries: &entries,
+        ..Default::default()
+    };
+    let result = create_atlas(&desc);
+    assert!(matches!(result, Err(AtlasError::InvalidSize(24))));
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        relaxed_mip: true,
+        ..desc.clone()
+    })
+    .unwrap();
+    assert_eq!(atlas.mip_level_count, 4);
+    let sizes = atlas.textures[0]
+        .mip_maps
+        .iter()
+        .map(|mip_map| mip_map.dimensions())
+        .collect::<Vec<_>>();
+    assert_eq!(sizes, [(24, 24), (12, 12), (6, 6), (3, 3)]);
+    atlas.verify_layout().unwrap();
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 8),
+        relaxed_mip: true,
+        size: 36,
+        ..desc.clone()
+    })
+    .unwrap();
+    assert_eq!(atlas.mip_level_count, 3);
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        relaxed_mip: true,
+        size: 64,
+        ..desc.clone()
+    })
+    .unwrap();
+    assert_eq!(atlas.mip_level_count, 7);
+
+    let result = create_atlas(&AtlasDescriptor {
+        relaxed_mip: true,
+        size: 0,
+        ..desc
+    });
+    assert!(matches!(result, Err(AtlasError::InvalidSize(0))));
+}
+
+#[test]
+fn entry_view() {
+    let entries = [
+        AtlasEntry::new(image::RgbaImage::from_pixel(
+            8,
+            4,
+            image::Rgba([255, 0, 0, 255]),
+        )),
+        AtlasEntry::new(image::RgbaImage::from_pixel(
+            4,
+            4,
+            image::Rgba([0, 255, 0, 255]),
+        )),
+    ];
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 32,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 4),
+        entries: &entries,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let view = atlas.entry_view(0, 0).unwrap();
+    assert_eq!(view.dimensions(), (8, 4));
+    assert!(view
+        .pixels()
+        .all(|(.., pixel)| pixel == image::Rgba([255, 0, 0, 255])));
+    let view = atlas.entry_view(1, 2).unwrap();
+    let rect = atlas.texcoords[1].at_mip(2);
+    assert_eq!(view.offsets(), (rect.min_x, rect.min_y));
+    assert_eq!(
+        view.dimensions(),
+        (rect.max_x - rect.min_x, rect.max_y - rect.min_y)
+    );
+
+    assert!(matches!(
+        atlas.entry_view(2, 0),
+        Err(AtlasError::NoEntry { index: 2 })
+    ));
+    assert!(matches!(
+        atlas.entry_view(0, 3),
+        Err(AtlasError::MipLevelOutOfRange { mip_level: 3, .. })
+    ));
+}
+
+#[test]
+fn strict_gutter() {
+    let entries = [AtlasEntry::new(image::RgbaImage::new(8, 8))];
+    let desc = AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::MipWithPadding(AtlasMipFilter::Linear, 4),
+        max_mip_level_count: Some(4),
+        strict_gutter: true,
+        entries: &entries,
+        ..Default::default()
+    };
+    let result = create_atlas(&desc);
+    assert!(matches!(
+        result,
+        Err(AtlasError::InsufficientGutter { needed: 8, got: 4 })
+    ));
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "padding 4 is narrower than 8 needed by the mip filter."
+    );
+
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_mip_level_count: Some(3),
+        ..desc.clone()
+    })
+    .unwrap();
+    assert_eq!(atlas.mip_level_count, 3);
+
+    let result = create_atlas(&AtlasDescriptor {
+        mip: AtlasMipOption::MipWithPadding(AtlasMipFilter::Lanczos3, 4),
+        max_mip_level_count: Some(3),
+        ..desc.clone()
+    });
+    assert!(matches!(
+        result,
+        Err(AtlasError::InsufficientGutter { needed: 12, got: 4 })
+    ));
+
+    let result = create_atlas(&AtlasDescriptor {
+        mip: AtlasMipOption::Mip(AtlasMipFilter::Nearest),
+        max_mip_level_count: None,
+        ..desc.clone()
+    });
+    assert!(result.is_ok());
+
+    let result = create_atlas(&AtlasDescriptor {
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Lanczos3, 4),
+        max_mip_level_count: None,
+        ..desc
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn pin_page() {
+    let entries = [
+        AtlasEntry::new(image::RgbaImage::new(12, 12)),
+        AtlasEntry::new(image::RgbaImage::new(12, 12)).pin_page(2),
+        AtlasEntry::new(image::RgbaImage::new(12, 12)),
+        AtlasEntry::new(image::RgbaImage::new(12, 12)).pin_page(2),
+    ];
+    let desc = AtlasDescriptor {
+        max_page_count: 0,
+        size: 32,
+        mip: AtlasMipOption::NoMipWithPadding(1),
+        entries: &entries,
+        ..Default::default()
+    };
+    let atlas = create_atlas(&desc).unwrap();
+    assert_eq!(atlas.textures.len(), 3);
+    assert_eq!(atlas.texcoords[0].page, 0);
+    assert_eq!(atlas.texcoords[1].page, 2);
+    assert_eq!(atlas.texcoords[2].page, 0);
+    assert_eq!(atlas.texcoords[3].page, 2);
+    assert!(atlas.verify_layout().is_ok());
+
+    let block = create_atlas(&AtlasDescriptor {
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 4),
+        ..desc.clone()
+    })
+    .unwrap();
+    assert_eq!(block.texcoords[1].page, 2);
+    assert_eq!(block.texcoords[3].page, 2);
+
+    let result = create_atlas(&AtlasDescriptor {
+        max_page_count: 2,
+        ..desc.clone()
+    });
+    assert!(matches!(
+        result,
+        Err(AtlasError::PinnedPageOverflow { page: 2 })
+    ));
+
+    let entries = [
+        AtlasEntry::new(image::RgbaImage::new(24, 24)).pin_page(0),
+        AtlasEntry::new(image::RgbaImage::new(24, 24)).pin_page(0),
+    ];
+    let result = create_atlas(&AtlasDescriptor {
+        entries: &entries,
+        ..desc.clone()
+    });
+    assert!(matches!(
+        result,
+        Err(AtlasError::PinnedPageOverflow { page: 0 })
+    ));
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "entries pinned to page 0 do not fit in it."
+    );
+
+    // an identical entry pinned elsewhere is not deduplicated into the unpinned one.
+    let texture = image::RgbaImage::from_pixel(12, 12, image::Rgba([255, 0, 0, 255]));
+    let entries = [
+        AtlasEntry::new(texture.clone()),
+        AtlasEntry::new(texture).pin_page(1),
+    ];
+    let atlas = create_atlas(&AtlasDescriptor {
+        deduplicate: true,
+        entries: &entries,
+        ..desc
+    })
+    .unwrap();
+    assert_eq!(atlas.texcoords[0].page, 0);
+    assert_eq!(atlas.texcoords[1].page, 1);
+}
+
+#[test]
+fn preserve() {
+    let entries = [(16, 16), (24, 12), (8, 20)].map(|(width, height)| {
+        AtlasEntry::new(image::RgbaImage::new(width, height)).anchor(AtlasEntryAnchor::Center)
+    });
+    let descs = [
+        AtlasDescriptor {
+            max_page_count: 1,
+            size: 64,
+            mip: AtlasMipOption::NoMipWithPadding(1),
+            origin_alignment: 4,
+            entries: &entries,
+            ..Default::default()
+        },
+        AtlasDescriptor {
+            max_page_count: 1,
+            size: 64,
+            mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Linear, 8),
+            entries: &entries,
+            ..Default::default()
+        },
+    ];
+    for desc in descs {
+        let atlas = create_atlas(&desc).unwrap();
+
+        let mut rebaked_entries = vec![AtlasEntry::new(image::RgbaImage::new(40, 40))];
+        rebaked_entries.extend(entries.iter().cloned());
+        let previous = (0..entries.len())
+            .map(|i| (i + 1, atlas.texcoords[i]))
+            .collect::<Vec<_>>();
+        let rebaked = create_atlas_preserving(
+            &AtlasDescriptor {
+                max_page_count: 0,
+                entries: &rebaked_entries,
+                ..desc.clone()
+            },
+            &previous,
+        )
+        .unwrap();
+        for &(i, texcoord) in &previous {
+            assert_eq!(rebaked.texcoords[i], texcoord);
+        }
+        assert!(rebaked.verify_layout().is_ok());
+
+        let result = create_atlas_preserving(&desc, &[(3, atlas.texcoords[0])]);
+        assert!(matches!(result, Err(AtlasError::NoEntry { index: 3 })));
+
+        let moved = Texcoord {
+            page: 1,
+            ..atlas.texcoords[0]
+        };
+        let result = create_atlas_preserving(&desc, &[(0, moved)]);
+        assert!(matches!(
+            result,
+            Err(AtlasError::PreservedMismatch { index: 0 })
+        ));
+
+        let previous = [(0, atlas.texcoords[0]), (1, atlas.texcoords[0])];
+        let result = create_atlas_preserving(&desc, &previous);
+        assert!(matches!(
+            result,
+            Err(AtlasError::PreservedMismatch { index: 1 })
+        ));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "entry 1 can not keep its previous texcoord."
+        );
+
+        // identical entries kept apart previously are not deduplicated into one location.
+        let twins = [entries[0].clone(), entries[0].clone()];
+        let desc = AtlasDescriptor {
+            entries: &twins,
+            ..desc
+        };
+        let atlas = create_atlas(&desc).unwrap();
+        let previous = [(0, atlas.texcoords[0]), (1, atlas.texcoords[1])];
+        let desc = AtlasDescriptor {
+            deduplicate: true,
+            ..desc
+        };
+        let rebaked = create_atlas_preserving(&desc, &previous).unwrap();
+        assert_eq!(rebaked.texcoords, atlas.texcoords);
+    }
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn save_pages() {
+    let atlas = create_atlas(&AtlasDescriptor {
+        max_page_count: 1,
+        size: 64,
+        mip: AtlasMipOption::MipWithBlock(AtlasMipFilter::Nearest, 16),
+        entries: &[AtlasEntry::new(image::RgbaImage::from_pixel(
+            8,
+            8,
+            image::Rgba([255, 0, 0, 255]),
+        ))],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let dir_path = path::Path::new("target/img/pages");
+    fs::create_dir_all(dir_path).unwrap();
+    atlas.save_pages(dir_path, "atlas").unwrap();
+    atlas.save_ktx(dir_path, "atlas").unwrap();
+
+    for (mip_level, mip_map) in atlas.textures[0].mip_maps.iter().enumerate() {
+        let path = dir_path.join(format!("atlas-0-{}.png", mip_level));
+        assert_eq!(&image::open(path).unwrap().into_rgba8(), mip_map);
+    }
+    assert!(dir_path.join("atlas-0.ktx2").exists());
+}
+
+#[test]
+fn invalid_sdf_spread() {
+    let entries = [AtlasEntry::new(image::GrayImage::new(8, 8))];
+    for spread in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        let result = create_atlas(&AtlasDescriptor {
+            max_page_count: 1,
+            size: 32,
+            mip: AtlasMipOption::NoMip,
+            sdf: Some(SdfParams { spread }),
+            entries: &entries,
+            ..Default::default()
+        });
+        assert!(matches!(result, Err(AtlasError::InvalidSdfSpread(_))));
+    }
+    let result = AtlasDescriptor::builder()
+        .max_page_count(1)
+        .size(32)
+        .sdf(SdfParams { spread: 0.0 })
+        .entries(&entries)
+        .build();
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "sdf spread 0 is not a positive finite number."
+    );
+}